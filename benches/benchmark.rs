@@ -1,10 +1,12 @@
-use perfect_rand::{PerfectRng, PerfectRng32};
+use perfect_rand::{
+    Backend, PerfectRng, PerfectRng128, PerfectRng16, PerfectRng32, PerfectRngConst,
+};
 
 fn main() {
     divan::main();
 }
 
-#[divan::bench(args = [256, 65_536, 4_294_967_296, 65_536/3, 4_294_967_296/3])]
+#[divan::bench(args = [256, 65_536, 4_294_967_296, 65_536/3, 4_294_967_296/3, 65_537, 4_294_967_297])]
 fn encrypt(range: u64) {
     let randomizer = PerfectRng::new(range, 0, 4);
 
@@ -14,6 +16,26 @@ fn encrypt(range: u64) {
     }
 }
 
+#[divan::bench(args = [256, 65_536, 4_294_967_296, 65_536/3, 4_294_967_296/3])]
+fn encrypt_const(range: u64) {
+    let randomizer = PerfectRngConst::<4>::new(range, 0);
+
+    for i in 0..256 {
+        let shuffled_i = randomizer.shuffle(i);
+        divan::black_box(shuffled_i);
+    }
+}
+
+#[divan::bench(args = [256, 65_537, 4_294_967_297, 65_536/3, 4_294_967_296/3])]
+fn encrypt_tight(range: u64) {
+    let randomizer = PerfectRng::new(range, 0, 4).with_tight_domain();
+
+    for i in 0..256 {
+        let shuffled_i = randomizer.shuffle(i);
+        divan::black_box(shuffled_i);
+    }
+}
+
 #[divan::bench(args = [256, 65_536, 4_294_967_295, 65_536/3, 4_294_967_295/3])]
 fn encrypt32(range: u32) {
     let randomizer = PerfectRng32::new(range, 0, 4);
@@ -23,3 +45,79 @@ fn encrypt32(range: u32) {
         divan::black_box(shuffled_i);
     }
 }
+
+#[divan::bench(args = [256, 65_536, 1 << 64, u128::MAX / 3])]
+fn encrypt128(range: u128) {
+    let randomizer = PerfectRng128::new(range, 0, 4);
+
+    for i in 0..256 {
+        let shuffled_i = randomizer.shuffle(i);
+        divan::black_box(shuffled_i);
+    }
+}
+
+#[divan::bench(args = [256, 65_535, 65_535/3])]
+fn encrypt16(range: u16) {
+    let randomizer = PerfectRng16::new(range, 0, 4);
+
+    for i in 0..256 {
+        let shuffled_i = randomizer.shuffle(i);
+        divan::black_box(shuffled_i);
+    }
+}
+
+#[divan::bench(args = [Backend::SipHash, Backend::Aes, Backend::ChaCha, Backend::Philox, Backend::Blackrock2, Backend::SwapOrNot, Backend::Thorp, Backend::EvenMansour])]
+fn encrypt_backend(backend: Backend) {
+    let randomizer = PerfectRng::new(4_294_967_296u64, 0, 4).with_backend(backend);
+
+    for i in 0..256 {
+        let shuffled_i = randomizer.shuffle(i);
+        divan::black_box(shuffled_i);
+    }
+}
+
+#[divan::bench(args = [1, 2, 4, 8])]
+fn encrypt_siprounds(siprounds: usize) {
+    let randomizer = PerfectRng::new(4_294_967_296u64, 0, 4).with_siprounds(siprounds);
+
+    for i in 0..256 {
+        let shuffled_i = randomizer.shuffle(i);
+        divan::black_box(shuffled_i);
+    }
+}
+
+#[divan::bench(args = [256, 65_536, 4_294_967_296, 65_536/3, 4_294_967_296/3])]
+fn encrypt_batch(range: u64) {
+    let randomizer = PerfectRng::new(range, 0, 4);
+
+    let mut values = [0; 256];
+    for (i, value) in values.iter_mut().enumerate() {
+        *value = i as u64;
+    }
+    randomizer.shuffle_batch(&mut values);
+    divan::black_box(values);
+}
+
+#[divan::bench(args = [256, 65_536, 4_294_967_295, 65_536/3, 4_294_967_295/3])]
+fn encrypt32_batch(range: u32) {
+    let randomizer = PerfectRng32::new(range, 0, 4);
+
+    let mut values = [0; 256];
+    for (i, value) in values.iter_mut().enumerate() {
+        *value = i as u32;
+    }
+    randomizer.shuffle_batch(&mut values);
+    divan::black_box(values);
+}
+
+#[divan::bench(args = [65_536, 4_294_967_296, 65_536/3, 4_294_967_296/3])]
+fn iter(range: u64) {
+    let randomizer = PerfectRng::new(range, 0, 4);
+    divan::black_box(randomizer.iter().take(256).fold(0, |a, b| a ^ b));
+}
+
+#[divan::bench(args = [65_536, 4_294_967_296, 65_536/3, 4_294_967_296/3])]
+fn iter_sequential(range: u64) {
+    let randomizer = PerfectRng::new(range, 0, 4);
+    divan::black_box(randomizer.iter_sequential().take(256).fold(0, |a, b| a ^ b));
+}
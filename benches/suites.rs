@@ -0,0 +1,95 @@
+//! Benchmark suites over the fixtures in `bench_support`, so results can be
+//! compared between machines. Run them with
+//! `cargo bench --features bench_support --bench suites`.
+
+use perfect_rand::{
+    bench_support::{self, RangeSize},
+    Backend,
+};
+
+fn main() {
+    divan::main();
+}
+
+/// The number of values shuffled in each iteration of the suites.
+const N: usize = 256;
+
+/// Every range of every size in [`bench_support`].
+fn ranges() -> impl Iterator<Item = u64> {
+    RangeSize::ALL
+        .into_iter()
+        .flat_map(|size| size.ranges().iter().copied())
+}
+
+/// Every range that fits in an integer with `bits` bits.
+fn ranges_within(bits: u32) -> impl Iterator<Item = u64> {
+    ranges().filter(move |&range| range - 1 <= u64::MAX >> (64 - bits))
+}
+
+/// Every backend on every range, with the default of 4 rounds.
+mod backends {
+    use super::*;
+
+    fn args() -> impl Iterator<Item = (Backend, u64)> {
+        bench_support::backends()
+            .into_iter()
+            .flat_map(|backend| ranges().map(move |range| (backend, range)))
+    }
+
+    #[divan::bench(args = args())]
+    fn shuffle(bencher: divan::Bencher, (backend, range): (Backend, u64)) {
+        let randomizer = bench_support::rng(range, 4, backend);
+        let inputs = bench_support::inputs(range, N);
+        bencher.bench_local(|| bench_support::shuffle_all(&randomizer, divan::black_box(&inputs)));
+    }
+}
+
+/// Every round count on every range, with [`Backend::SipHash`].
+mod rounds {
+    use super::*;
+
+    fn args() -> impl Iterator<Item = (usize, u64)> {
+        bench_support::ROUNDS
+            .into_iter()
+            .flat_map(|rounds| ranges().map(move |range| (rounds, range)))
+    }
+
+    #[divan::bench(args = args())]
+    fn shuffle(bencher: divan::Bencher, (rounds, range): (usize, u64)) {
+        let randomizer = bench_support::rng(range, rounds, Backend::SipHash);
+        let inputs = bench_support::inputs(range, N);
+        bencher.bench_local(|| bench_support::shuffle_all(&randomizer, divan::black_box(&inputs)));
+    }
+}
+
+/// Every integer width on every range that fits in it, with
+/// [`Backend::SipHash`] and 4 rounds.
+mod widths {
+    use super::*;
+
+    fn bench<T: perfect_rand::UnsignedInt>(bencher: divan::Bencher, range: T) {
+        let randomizer = bench_support::rng(range, 4, Backend::SipHash);
+        let inputs = bench_support::inputs(range, N);
+        bencher.bench_local(|| bench_support::shuffle_all(&randomizer, divan::black_box(&inputs)));
+    }
+
+    #[divan::bench(args = ranges_within(16))]
+    fn u16(bencher: divan::Bencher, range: u64) {
+        bench(bencher, range as u16);
+    }
+
+    #[divan::bench(args = ranges_within(32))]
+    fn u32(bencher: divan::Bencher, range: u64) {
+        bench(bencher, range as u32);
+    }
+
+    #[divan::bench(args = ranges())]
+    fn u64(bencher: divan::Bencher, range: u64) {
+        bench(bencher, range);
+    }
+
+    #[divan::bench(args = ranges())]
+    fn u128(bencher: divan::Bencher, range: u64) {
+        bench(bencher, u128::from(range));
+    }
+}
@@ -0,0 +1,80 @@
+//! When built with `RUSTFLAGS="--cfg masscan_ffi"`, compile masscan's
+//! Blackrock2 from `MASSCAN_SRC` so the tests can compare
+//! `Backend::Blackrock2` with it. Otherwise this does nothing.
+
+use std::{env, path::PathBuf, process::Command};
+
+/// Builds `crypto-blackrock2.c` as part of this file, with functions that
+/// don't depend on the layout of masscan's `struct BlackRock`, so it doesn't
+/// have to be mirrored in Rust.
+const SHIM: &str = r#"
+#include "crypto-blackrock2.c"
+
+uint64_t perfect_rand_blackrock2_shuffle(uint64_t range, uint64_t seed, unsigned rounds, uint64_t m) {
+    struct BlackRock br;
+    blackrock2_init(&br, range, seed, rounds);
+    return blackrock2_shuffle(&br, m);
+}
+
+uint64_t perfect_rand_blackrock2_unshuffle(uint64_t range, uint64_t seed, unsigned rounds, uint64_t c) {
+    struct BlackRock br;
+    blackrock2_init(&br, range, seed, rounds);
+    return blackrock2_unshuffle(&br, c);
+}
+
+/* the benchmark and selftest need these from the rest of masscan, but
+   they're never called */
+uint64_t pixie_nanotime(void) {
+    return 0;
+}
+
+void *CALLOC(size_t count, size_t size) {
+    return calloc(count, size);
+}
+"#;
+
+fn main() {
+    println!("cargo:rustc-check-cfg=cfg(masscan_ffi)");
+    println!("cargo:rerun-if-changed=build.rs");
+    if env::var_os("CARGO_CFG_MASSCAN_FFI").is_none() {
+        return;
+    }
+    println!("cargo:rerun-if-env-changed=MASSCAN_SRC");
+    println!("cargo:rerun-if-env-changed=CC");
+    println!("cargo:rerun-if-env-changed=AR");
+    let src = PathBuf::from(
+        env::var_os("MASSCAN_SRC")
+            .expect("set MASSCAN_SRC to masscan's src directory to build with cfg(masscan_ffi)"),
+    );
+    println!(
+        "cargo:rerun-if-changed={}",
+        src.join("crypto-blackrock2.c").display()
+    );
+
+    let out = PathBuf::from(env::var_os("OUT_DIR").unwrap());
+    let shim = out.join("blackrock2_shim.c");
+    std::fs::write(&shim, SHIM).unwrap();
+
+    let object = out.join("blackrock2_shim.o");
+    let status = Command::new(env::var_os("CC").unwrap_or_else(|| "cc".into()))
+        .args(["-c", "-O1", "-fPIC", "-I"])
+        .arg(&src)
+        .arg(&shim)
+        .arg("-o")
+        .arg(&object)
+        .status()
+        .expect("couldn't run the C compiler");
+    assert!(status.success(), "couldn't compile {}", shim.display());
+
+    let status = Command::new(env::var_os("AR").unwrap_or_else(|| "ar".into()))
+        .arg("crs")
+        .arg(out.join("libmasscan_blackrock2.a"))
+        .arg(&object)
+        .status()
+        .expect("couldn't run ar");
+    assert!(status.success(), "couldn't archive the masscan objects");
+
+    println!("cargo:rustc-link-search=native={}", out.display());
+    println!("cargo:rustc-link-lib=static=masscan_blackrock2");
+    println!("cargo:rustc-link-lib=m");
+}
@@ -0,0 +1,28 @@
+//! Like `roundtrip`, but with the full width of `u128`, where the halves are
+//! as big as they can be.
+
+#![no_main]
+
+use libfuzzer_sys::{arbitrary::Arbitrary, fuzz_target};
+use perfect_rand::{PerfectRng128, MAX_ROUNDS};
+
+#[derive(Debug, Arbitrary)]
+struct Input {
+    max: u128,
+    key: [u128; 2],
+    rounds: u8,
+    x: u128,
+}
+
+fuzz_target!(|input: Input| {
+    let rounds = usize::from(input.rounds) % MAX_ROUNDS + 1;
+    let randomizer = PerfectRng128::with_key_inclusive(0..=input.max, input.key, rounds);
+
+    let x = match input.max.checked_add(1) {
+        Some(range) => input.x % range,
+        None => input.x,
+    };
+    let shuffled = randomizer.shuffle(x);
+    assert!(shuffled <= input.max, "{shuffled} isn't in the range for {input:?}");
+    assert_eq!(randomizer.unshuffle(shuffled), x, "{input:?}");
+});
@@ -0,0 +1,175 @@
+//! The derive macro for `perfect_rand`'s `Shuffleable` trait. Use it through
+//! `perfect_rand` with the `derive` feature instead of depending on this
+//! crate directly.
+
+use proc_macro::TokenStream;
+use proc_macro2::{Span, TokenStream as TokenStream2};
+use quote::{format_ident, quote};
+use syn::{parse_macro_input, Data, DeriveInput, Error, Fields, Index};
+
+/// Implement `Shuffleable` for a field-less enum, or a struct whose fields
+/// are all `Shuffleable`.
+///
+/// The variants of an enum are numbered in the order they're declared. A
+/// struct is numbered like a mixed-radix number, where each field is a digit
+/// and the first field changes fastest. The index is always a `u64`, so the
+/// number of values of a struct has to fit in one.
+#[proc_macro_derive(Shuffleable)]
+pub fn derive_shuffleable(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    match expand(&input) {
+        Ok(tokens) => tokens.into(),
+        Err(error) => error.to_compile_error().into(),
+    }
+}
+
+fn expand(input: &DeriveInput) -> syn::Result<TokenStream2> {
+    if !input.generics.params.is_empty() {
+        return Err(Error::new_spanned(
+            &input.generics,
+            "Shuffleable can't be derived for generic types",
+        ));
+    }
+    let name = &input.ident;
+    let (max_index, to_index, from_index) = match &input.data {
+        Data::Enum(data) => {
+            if data.variants.is_empty() {
+                return Err(Error::new_spanned(
+                    name,
+                    "Shuffleable can't be derived for an enum without variants",
+                ));
+            }
+            let mut variants = Vec::new();
+            for variant in &data.variants {
+                if !matches!(variant.fields, Fields::Unit) {
+                    return Err(Error::new_spanned(
+                        variant,
+                        "Shuffleable can only be derived for enums without fields",
+                    ));
+                }
+                variants.push(&variant.ident);
+            }
+            let indices = (0..variants.len() as u64).collect::<Vec<_>>();
+            let max_index = variants.len() as u64 - 1;
+            (
+                quote!(#max_index),
+                quote! {
+                    match self {
+                        #(#name::#variants => #indices,)*
+                    }
+                },
+                quote! {
+                    match index {
+                        #(#indices => #name::#variants,)*
+                        _ => panic!("the index must be at most MAX_INDEX"),
+                    }
+                },
+            )
+        }
+        Data::Struct(data) => {
+            let types = data
+                .fields
+                .iter()
+                .map(|field| &field.ty)
+                .collect::<Vec<_>>();
+            let bindings = (0..types.len())
+                .map(|i| format_ident!("field{i}"))
+                .collect::<Vec<_>>();
+            let pattern = match &data.fields {
+                Fields::Named(fields) => {
+                    let names = fields.named.iter().map(|field| &field.ident);
+                    quote!(#name { #(#names: #bindings),* })
+                }
+                Fields::Unnamed(_) => quote!(#name(#(#bindings),*)),
+                Fields::Unit => quote!(#name),
+            };
+            let construct = match &data.fields {
+                Fields::Named(fields) => {
+                    let names = fields.named.iter().map(|field| &field.ident);
+                    quote!(#name { #(#names: #bindings),* })
+                }
+                Fields::Unnamed(fields) => {
+                    let indices = (0..fields.unnamed.len()).map(Index::from);
+                    quote!(#name { #(#indices: #bindings),* })
+                }
+                Fields::Unit => quote!(#name),
+            };
+            let max_indices = types
+                .iter()
+                .map(|ty| quote!((<#ty as ::perfect_rand::Shuffleable>::MAX_INDEX as u128)))
+                .collect::<Vec<_>>();
+            // the number of values of each field, which wraps to 0 for u128
+            // fields with every value, but then the struct can't fit in a
+            // u64 anyway and MAX_INDEX fails to compile
+            let radixes = max_indices
+                .iter()
+                .map(|max_index| quote!(#max_index.wrapping_add(1)))
+                .collect::<Vec<_>>();
+
+            // the first field is the least significant digit
+            let mut to_index = quote!(0);
+            for (binding, radix) in bindings.iter().zip(&radixes).rev() {
+                to_index = quote! {
+                    ::perfect_rand::Shuffleable::to_index(#binding) as u128 + #radix * (#to_index)
+                };
+            }
+            let divisors = (0..radixes.len()).map(|i| {
+                let previous = &radixes[..i];
+                quote!(1 #(* #previous)*)
+            });
+
+            (
+                quote! {{
+                    let len: u128 = 1;
+                    #(
+                        let len = match #max_indices.checked_add(1) {
+                            Some(radix) => match len.checked_mul(radix) {
+                                Some(len) => len,
+                                None => panic!("the struct has more values than fit in a u64"),
+                            },
+                            None => panic!("the struct has more values than fit in a u64"),
+                        };
+                    )*
+                    assert!(len - 1 <= u64::MAX as u128, "the struct has more values than fit in a u64");
+                    (len - 1) as u64
+                }},
+                quote! {
+                    let #pattern = self;
+                    (#to_index) as u64
+                },
+                quote! {
+                    assert!(index <= Self::MAX_INDEX, "the index must be at most MAX_INDEX");
+                    #(
+                        let #bindings = <#types as ::perfect_rand::Shuffleable>::from_index(
+                            (index as u128 / (#divisors) % #radixes)
+                                as <#types as ::perfect_rand::Shuffleable>::Index,
+                        );
+                    )*
+                    #construct
+                },
+            )
+        }
+        Data::Union(_) => {
+            return Err(Error::new(
+                Span::call_site(),
+                "Shuffleable can't be derived for unions",
+            ))
+        }
+    };
+
+    Ok(quote! {
+        #[automatically_derived]
+        impl ::perfect_rand::Shuffleable for #name {
+            type Index = u64;
+            const MAX_INDEX: u64 = #max_index;
+
+            fn to_index(self) -> u64 {
+                #to_index
+            }
+
+            fn from_index(index: u64) -> Self {
+                #from_index
+            }
+        }
+    })
+}
@@ -0,0 +1,80 @@
+use perfect_rand::{PerfectRng, Shuffleable};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Shuffleable)]
+enum Suit {
+    Clubs,
+    Diamonds,
+    Hearts,
+    Spades,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Shuffleable)]
+struct Card {
+    suit: Suit,
+    rank: Rank,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Shuffleable)]
+struct Rank(Face, bool);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Shuffleable)]
+enum Face {
+    Low,
+    Middle,
+    High,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Shuffleable)]
+struct Unit;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Shuffleable)]
+struct Wide {
+    port: u16,
+    byte: u8,
+}
+
+#[test]
+fn enums() {
+    assert_eq!(Suit::MAX_INDEX, 3);
+    assert_eq!(Suit::Clubs.to_index(), 0);
+    assert_eq!(Suit::Spades.to_index(), 3);
+    assert_eq!(Suit::from_index(2), Suit::Hearts);
+}
+
+#[test]
+fn structs() {
+    assert_eq!(Rank::MAX_INDEX, 5);
+    assert_eq!(Card::MAX_INDEX, 23);
+    assert_eq!(Unit::MAX_INDEX, 0);
+    assert_eq!(Unit::from_index(0), Unit);
+    assert_eq!(Wide::MAX_INDEX, (1 << 24) - 1);
+
+    // the first field changes fastest, so this is Diamonds (1) plus 4 times
+    // the rank, which is Middle (1) plus 3 times true (1)
+    let card = Card {
+        suit: Suit::Diamonds,
+        rank: Rank(Face::Middle, true),
+    };
+    assert_eq!(card.to_index(), 17);
+    for index in 0..=Card::MAX_INDEX {
+        assert_eq!(Card::from_index(index).to_index(), index);
+    }
+    let wide = Wide { port: 443, byte: 7 };
+    assert_eq!(Wide::from_index(wide.to_index()), wide);
+}
+
+#[test]
+fn shuffle_typed() {
+    let randomizer = PerfectRng::with_key_inclusive(0..=Card::MAX_INDEX, [1, 2], 4);
+    let mut cards = (0..=Card::MAX_INDEX)
+        .map(|i| randomizer.shuffle_typed::<Card>(i).to_index())
+        .collect::<Vec<_>>();
+    cards.sort_unstable();
+    assert!(cards.into_iter().eq(0..=Card::MAX_INDEX));
+}
+
+#[test]
+#[should_panic(expected = "at most MAX_INDEX")]
+fn out_of_range() {
+    let _ = Card::from_index(24);
+}
@@ -0,0 +1,234 @@
+//! AES-128, used by the AES-based backends.
+//!
+//! This uses AES-NI when the CPU supports it, and otherwise falls back to a
+//! portable implementation. Only encryption is implemented, since everything
+//! that uses AES here only needs it as a pseudorandom function.
+
+/// The AES S-box, computed from the multiplicative inverse in GF(2^8) and the
+/// affine transform so it doesn't have to be written out by hand.
+const SBOX: [u8; 256] = {
+    let mut sbox = [0; 256];
+    let mut x = 0;
+    while x < 256 {
+        // x^254 is the inverse of x, and 0 maps to 0
+        let mut inverse = 1;
+        let mut i = 0;
+        while i < 254 {
+            inverse = gf_mul(inverse, x as u8);
+            i += 1;
+        }
+        let b = if x == 0 { 0 } else { inverse };
+        sbox[x] =
+            b ^ b.rotate_left(1) ^ b.rotate_left(2) ^ b.rotate_left(3) ^ b.rotate_left(4) ^ 0x63;
+        x += 1;
+    }
+    sbox
+};
+
+const fn gf_mul(mut a: u8, mut b: u8) -> u8 {
+    let mut product = 0;
+    while b != 0 {
+        if b & 1 != 0 {
+            product ^= a;
+        }
+        a = xtime(a);
+        b >>= 1;
+    }
+    product
+}
+
+#[inline]
+const fn xtime(a: u8) -> u8 {
+    (a << 1) ^ if a & 0x80 != 0 { 0x1b } else { 0 }
+}
+
+/// An AES-128 key schedule.
+#[cfg(feature = "fpe")]
+#[derive(Clone)]
+pub(crate) struct Aes128 {
+    round_keys: [[u8; 16]; 11],
+}
+
+#[cfg(feature = "fpe")]
+impl Aes128 {
+    pub(crate) fn new(key: [u8; 16]) -> Self {
+        const RCON: [u8; 10] = [0x01, 0x02, 0x04, 0x08, 0x10, 0x20, 0x40, 0x80, 0x1b, 0x36];
+
+        let mut round_keys = [[0; 16]; 11];
+        round_keys[0] = key;
+        for i in 1..11 {
+            let prev = round_keys[i - 1];
+            let mut word = [prev[13], prev[14], prev[15], prev[12]].map(|b| SBOX[b as usize]);
+            word[0] ^= RCON[i - 1];
+
+            for column in 0..4 {
+                for row in 0..4 {
+                    word[row] ^= prev[column * 4 + row];
+                    round_keys[i][column * 4 + row] = word[row];
+                }
+            }
+        }
+        Aes128 { round_keys }
+    }
+
+    /// Encrypt a single block.
+    pub(crate) fn encrypt(&self, block: [u8; 16]) -> [u8; 16] {
+        #[cfg(target_arch = "x86_64")]
+        if std::arch::is_x86_feature_detected!("aes") {
+            // SAFETY: we just checked that the CPU supports AES-NI
+            return unsafe { x86::encrypt(&self.round_keys, block) };
+        }
+
+        self.soft_encrypt(block)
+    }
+
+    fn soft_encrypt(&self, block: [u8; 16]) -> [u8; 16] {
+        let mut state = xor(block, self.round_keys[0]);
+        for round_key in &self.round_keys[1..10] {
+            state = soft_round(state, *round_key);
+        }
+        xor(shift_rows(sub_bytes(state)), self.round_keys[10])
+    }
+}
+
+/// Do two AES rounds (`SubBytes`, `ShiftRows`, `MixColumns`, then
+/// `AddRoundKey`), like x86's `aesenc` instruction.
+#[inline]
+pub(crate) fn two_rounds(block: u128, key1: u128, key2: u128) -> u128 {
+    #[cfg(target_arch = "x86_64")]
+    if std::arch::is_x86_feature_detected!("aes") {
+        // SAFETY: we just checked that the CPU supports AES-NI
+        return unsafe { x86::two_rounds(block, key1, key2) };
+    }
+
+    let block = soft_round(block.to_le_bytes(), key1.to_le_bytes());
+    u128::from_le_bytes(soft_round(block, key2.to_le_bytes()))
+}
+
+#[inline]
+fn soft_round(block: [u8; 16], round_key: [u8; 16]) -> [u8; 16] {
+    xor(mix_columns(shift_rows(sub_bytes(block))), round_key)
+}
+
+#[inline]
+fn xor(a: [u8; 16], b: [u8; 16]) -> [u8; 16] {
+    let mut out = [0; 16];
+    for i in 0..16 {
+        out[i] = a[i] ^ b[i];
+    }
+    out
+}
+
+#[inline]
+fn sub_bytes(block: [u8; 16]) -> [u8; 16] {
+    block.map(|b| SBOX[b as usize])
+}
+
+#[inline]
+fn shift_rows(block: [u8; 16]) -> [u8; 16] {
+    // the state is stored column by column, and row r is rotated left by r
+    let mut out = [0; 16];
+    for column in 0..4 {
+        for row in 0..4 {
+            out[column * 4 + row] = block[((column + row) % 4) * 4 + row];
+        }
+    }
+    out
+}
+
+#[inline]
+fn mix_columns(block: [u8; 16]) -> [u8; 16] {
+    let mut out = [0; 16];
+    for column in 0..4 {
+        let [a0, a1, a2, a3] = [0, 1, 2, 3].map(|row| block[column * 4 + row]);
+        let all = a0 ^ a1 ^ a2 ^ a3;
+        out[column * 4] = a0 ^ all ^ xtime(a0 ^ a1);
+        out[column * 4 + 1] = a1 ^ all ^ xtime(a1 ^ a2);
+        out[column * 4 + 2] = a2 ^ all ^ xtime(a2 ^ a3);
+        out[column * 4 + 3] = a3 ^ all ^ xtime(a3 ^ a0);
+    }
+    out
+}
+
+#[cfg(target_arch = "x86_64")]
+mod x86 {
+    use std::arch::x86_64::{__m128i, _mm_aesenc_si128};
+
+    #[cfg(feature = "fpe")]
+    #[inline]
+    #[target_feature(enable = "aes")]
+    pub(super) unsafe fn encrypt(round_keys: &[[u8; 16]; 11], block: [u8; 16]) -> [u8; 16] {
+        use std::arch::x86_64::{_mm_aesenclast_si128, _mm_xor_si128};
+
+        let load = |bytes: [u8; 16]| std::mem::transmute::<[u8; 16], __m128i>(bytes);
+        let mut state = _mm_xor_si128(load(block), load(round_keys[0]));
+        for round_key in &round_keys[1..10] {
+            state = _mm_aesenc_si128(state, load(*round_key));
+        }
+        std::mem::transmute(_mm_aesenclast_si128(state, load(round_keys[10])))
+    }
+
+    #[inline]
+    #[target_feature(enable = "aes")]
+    pub(super) unsafe fn two_rounds(block: u128, key1: u128, key2: u128) -> u128 {
+        // u128 and __m128i have the same layout, and on x86 both are little
+        // endian, so this is the same as loading the bytes
+        let block: __m128i = std::mem::transmute(block);
+        let block = _mm_aesenc_si128(block, std::mem::transmute::<u128, __m128i>(key1));
+        let block = _mm_aesenc_si128(block, std::mem::transmute::<u128, __m128i>(key2));
+        std::mem::transmute(block)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{soft_round, SBOX};
+
+    #[test]
+    fn sbox() {
+        assert_eq!(SBOX[0x00], 0x63);
+        assert_eq!(SBOX[0x01], 0x7c);
+        assert_eq!(SBOX[0x53], 0xed);
+        assert_eq!(SBOX[0xff], 0x16);
+    }
+
+    #[test]
+    fn fips_197_round() {
+        // the first round of the example in FIPS-197 appendix B
+        let state = 0x193de3bea0f4e22b9ac68d2ae9f84808_u128.to_be_bytes();
+        let round_key = 0xa0fafe1788542cb123a339392a6c7605_u128.to_be_bytes();
+        assert_eq!(
+            u128::from_be_bytes(soft_round(state, round_key)),
+            0xa49c7ff2689f352b6b5bea43026a5049
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "fpe")]
+    fn fips_197() {
+        // FIPS-197 appendix C.1
+        let aes = super::Aes128::new(0x000102030405060708090a0b0c0d0e0f_u128.to_be_bytes());
+        let plaintext = 0x00112233445566778899aabbccddeeff_u128.to_be_bytes();
+        for ciphertext in [aes.encrypt(plaintext), aes.soft_encrypt(plaintext)] {
+            assert_eq!(
+                u128::from_be_bytes(ciphertext),
+                0x69c4e0d86a7b0430d8cdb78070b4c55a
+            );
+        }
+    }
+
+    #[test]
+    fn two_rounds_matches_software() {
+        let block = 0x00112233445566778899aabbccddeeff_u128;
+        let key1 = 0x000102030405060708090a0b0c0d0e0f_u128;
+        let key2 = 0xa0fafe1788542cb123a339392a6c7605_u128;
+        let expected = soft_round(
+            soft_round(block.to_le_bytes(), key1.to_le_bytes()),
+            key2.to_le_bytes(),
+        );
+        assert_eq!(
+            super::two_rounds(block, key1, key2),
+            u128::from_le_bytes(expected)
+        );
+    }
+}
@@ -0,0 +1,70 @@
+use crate::{PerfectRng, UnsignedInt};
+
+/// A version of the algorithm that turns a key and range into a permutation,
+/// like how the domain is split and what the rounds do.
+///
+/// Every [`PerfectRng`] records its version, and it's saved with it by
+/// `serde` and [`ScanState`](crate::ScanState). Improvements that would
+/// change the permutation are added as new versions instead of changing an
+/// old one, so the same key and range always give the same permutation with
+/// the same version. The constructors always use [`Algorithm::V1`].
+///
+/// ```
+/// # use perfect_rand::{Algorithm, PerfectRng};
+/// let randomizer = PerfectRng::from_range(1000u64).with_algorithm(Algorithm::V1);
+/// assert_eq!(randomizer.algorithm(), Algorithm::V1);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub enum Algorithm {
+    /// A Feistel network over the bits of the domain, with cycle walking, and
+    /// the round functions of the [`Backend`](crate::Backend)s as they are
+    /// in 0.4.
+    #[default]
+    V1,
+}
+
+impl Algorithm {
+    /// A number that identifies the version in saved state.
+    pub(crate) fn id(self) -> u8 {
+        match self {
+            Algorithm::V1 => 0,
+        }
+    }
+
+    /// The version with the given [`Algorithm::id`], or `None` if it's from
+    /// a newer version of the crate.
+    pub(crate) fn from_id(id: u8) -> Option<Algorithm> {
+        match id {
+            0 => Some(Algorithm::V1),
+            _ => None,
+        }
+    }
+}
+
+impl<T: UnsignedInt> PerfectRng<T> {
+    /// Use a specific version of the algorithm. See [`Algorithm`].
+    #[must_use]
+    pub fn with_algorithm(mut self, algorithm: Algorithm) -> Self {
+        self.algorithm = algorithm;
+        self
+    }
+
+    /// The version of the algorithm this uses.
+    #[must_use]
+    pub fn algorithm(&self) -> Algorithm {
+        self.algorithm
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Algorithm;
+
+    #[test]
+    fn ids() {
+        assert_eq!(Algorithm::from_id(Algorithm::V1.id()), Some(Algorithm::V1));
+        assert_eq!(Algorithm::from_id(1), None);
+    }
+}
@@ -0,0 +1,203 @@
+use crate::{PerfectRng, PerfectRngError};
+
+/// A permutation of the strings of a fixed length over any alphabet, like
+/// hex, base32, or a custom set of characters.
+///
+/// Every string is encoded as a number in base `alphabet.len()`, with the
+/// first character being the most significant, and that number is shuffled
+/// with a [`PerfectRng<u128>`](PerfectRng).
+///
+/// ```
+/// # use perfect_rand::AlphabetRng;
+/// let hex = AlphabetRng::new("0123456789abcdef", 6).unwrap();
+/// let shuffled = hex.shuffle("c0ffee").unwrap();
+/// assert!(shuffled.chars().all(|c| c.is_ascii_hexdigit()));
+/// assert_eq!(hex.unshuffle(&shuffled).unwrap(), "c0ffee");
+///
+/// // not in the alphabet
+/// assert_eq!(hex.shuffle("C0FFEE"), None);
+/// ```
+#[derive(Debug)]
+pub struct AlphabetRng {
+    alphabet: Vec<char>,
+    len: usize,
+    rng: PerfectRng<u128>,
+}
+
+impl AlphabetRng {
+    /// Create a permutation of the strings of `len` characters from
+    /// `alphabet`, with a random key and default rounds.
+    ///
+    /// This returns an error if the alphabet has fewer than two characters
+    /// or repeats any, if `len` is 0, or if there are more than 2^128
+    /// strings.
+    pub fn new(alphabet: &str, len: usize) -> Result<Self, PerfectRngError> {
+        Self::with_key(alphabet, len, rand::random(), 4)
+    }
+
+    /// Like [`AlphabetRng::new`], but keyed like [`PerfectRng::with_key`].
+    pub fn with_key(
+        alphabet: &str,
+        len: usize,
+        key: [u128; 2],
+        rounds: usize,
+    ) -> Result<Self, PerfectRngError> {
+        let alphabet = alphabet.chars().collect::<Vec<_>>();
+        if alphabet.len() < 2
+            || alphabet
+                .iter()
+                .enumerate()
+                .any(|(i, c)| alphabet[..i].contains(c))
+        {
+            return Err(PerfectRngError::InvalidAlphabet);
+        }
+        if len == 0 {
+            return Err(PerfectRngError::ZeroRange);
+        }
+
+        // validate the rounds
+        PerfectRng::<u128>::try_with_key(1, key, rounds)?;
+
+        // the highest value is radix^len - 1, which is computed as
+        // p * (radix - 1) + p - 1 with p = radix^(len - 1) so that exactly
+        // 2^128 strings still fit
+        let radix = alphabet.len() as u128;
+        let max = u32::try_from(len - 1)
+            .ok()
+            .and_then(|exp| radix.checked_pow(exp))
+            .and_then(|p| p.checked_mul(radix - 1)?.checked_add(p - 1))
+            .ok_or(PerfectRngError::DomainTooLarge)?;
+
+        Ok(AlphabetRng {
+            alphabet,
+            len,
+            rng: PerfectRng::with_key_inclusive(0..=max, key, rounds),
+        })
+    }
+
+    /// The number of characters in every string.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Always false, since the strings can't be empty.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        false
+    }
+
+    /// The characters that the strings are made of, in the order of their
+    /// values.
+    #[must_use]
+    pub fn alphabet(&self) -> &[char] {
+        &self.alphabet
+    }
+
+    /// Shuffle a string of exactly [`AlphabetRng::len`] characters from the
+    /// alphabet, or return `None` if it isn't one.
+    #[must_use]
+    pub fn shuffle(&self, s: &str) -> Option<String> {
+        let m = self.decode(s)?;
+        Some(self.encode(self.rng.shuffle(m)))
+    }
+
+    /// Reverse [`AlphabetRng::shuffle`].
+    #[must_use]
+    pub fn unshuffle(&self, s: &str) -> Option<String> {
+        let c = self.decode(s)?;
+        Some(self.encode(self.rng.unshuffle(c)))
+    }
+
+    /// The [`PerfectRng`] that the encoded strings are shuffled with.
+    #[must_use]
+    pub fn rng(&self) -> &PerfectRng<u128> {
+        &self.rng
+    }
+
+    /// Convert a string to the number it encodes.
+    fn decode(&self, s: &str) -> Option<u128> {
+        let radix = self.alphabet.len() as u128;
+        let mut n = 0u128;
+        let mut len = 0;
+        for c in s.chars() {
+            let digit = self.alphabet.iter().position(|&a| a == c)?;
+            // can't overflow if the string has the right length, since the
+            // domain fits in a u128
+            n = n.checked_mul(radix)?.checked_add(digit as u128)?;
+            len += 1;
+        }
+        (len == self.len).then_some(n)
+    }
+
+    /// Convert a number in the domain to its string.
+    pub(crate) fn encode(&self, mut n: u128) -> String {
+        let radix = self.alphabet.len() as u128;
+        let mut chars = vec![self.alphabet[0]; self.len];
+        for c in chars.iter_mut().rev() {
+            *c = self.alphabet[(n % radix) as usize];
+            n /= radix;
+        }
+        chars.into_iter().collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::AlphabetRng;
+    use crate::PerfectRngError;
+
+    #[test]
+    fn is_a_permutation() {
+        let rng = AlphabetRng::with_key("abc", 4, [1, 2], 4).unwrap();
+        let mut seen = std::collections::HashSet::new();
+        for i in 0..81u32 {
+            let input = (0..4)
+                .map(|d| ['a', 'b', 'c'][(i / 3u32.pow(d) % 3) as usize])
+                .collect::<String>();
+            let shuffled = rng.shuffle(&input).unwrap();
+            assert_eq!(shuffled.chars().count(), 4);
+            assert_eq!(rng.unshuffle(&shuffled).unwrap(), input);
+            assert!(seen.insert(shuffled));
+        }
+    }
+
+    #[test]
+    fn unicode() {
+        let rng = AlphabetRng::with_key("αβγδ", 3, [1, 2], 4).unwrap();
+        let shuffled = rng.shuffle("αβγ").unwrap();
+        assert!(shuffled.chars().all(|c| "αβγδ".contains(c)));
+        assert_eq!(rng.unshuffle(&shuffled).unwrap(), "αβγ");
+    }
+
+    #[test]
+    fn full_domain() {
+        // 16^32 = 2^128 strings
+        let rng = AlphabetRng::with_key("0123456789abcdef", 32, [1, 2], 4).unwrap();
+        let input = "ffffffffffffffffffffffffffffffff";
+        assert_eq!(rng.unshuffle(&rng.shuffle(input).unwrap()).unwrap(), input);
+        assert_eq!(
+            AlphabetRng::new("0123456789abcdef", 33).unwrap_err(),
+            PerfectRngError::DomainTooLarge
+        );
+    }
+
+    #[test]
+    fn invalid() {
+        for alphabet in ["", "a", "abca"] {
+            assert_eq!(
+                AlphabetRng::new(alphabet, 4).unwrap_err(),
+                PerfectRngError::InvalidAlphabet
+            );
+        }
+        assert_eq!(
+            AlphabetRng::new("ab", 0).unwrap_err(),
+            PerfectRngError::ZeroRange
+        );
+
+        let rng = AlphabetRng::new("ab", 4).unwrap();
+        for input in ["", "aba", "ababa", "abcd"] {
+            assert_eq!(rng.shuffle(input), None);
+        }
+    }
+}
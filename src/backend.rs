@@ -0,0 +1,582 @@
+use std::{
+    fmt,
+    hash::{Hash, Hasher},
+    mem,
+    str::FromStr,
+};
+
+use crate::{aes, PerfectRngError, UnsignedInt, DEFAULT_SIPROUNDS};
+
+/// The round function that a [`PerfectRng`](crate::PerfectRng) uses to
+/// scramble the halves of its input in every Feistel round.
+///
+/// Every backend gives a different permutation for the same key, so the
+/// backend has to be the same for a shuffled order to be reproduced.
+///
+/// ```
+/// # use perfect_rand::{Backend, PerfectRng};
+/// let randomizer = PerfectRng::new(1000u64, 1, 4).with_backend(Backend::Aes);
+/// let shuffled = randomizer.shuffle(10);
+/// assert_eq!(randomizer.unshuffle(shuffled), 10);
+/// ```
+///
+/// Backends can also be parsed from their names, so they can be picked in a
+/// config file. The names are the same as what they're displayed as.
+///
+/// ```
+/// # use perfect_rand::{Backend, PerfectRng};
+/// let backend: Backend = "chacha".parse().unwrap();
+/// assert_eq!(backend, Backend::ChaCha);
+/// assert_eq!(backend.to_string(), "chacha");
+/// ```
+#[derive(Debug, Clone, Copy, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub enum Backend {
+    /// SipHash rounds that are the same width as the integer type. There are
+    /// four by default, which can be changed with
+    /// [`PerfectRng::with_siprounds`](crate::PerfectRng::with_siprounds).
+    #[default]
+    SipHash,
+    /// Two AES rounds. This uses AES-NI when the CPU supports it, where it's
+    /// about as fast as [`Backend::SipHash`] or faster, and a much slower
+    /// portable implementation otherwise.
+    Aes,
+    /// Two ChaCha double rounds, for platforms without AES acceleration.
+    ChaCha,
+    /// Four rounds of the Philox 2x64 counter-based generator, which are
+    /// each a 64-bit multiply and a few xors. This is cheaper than
+    /// [`Backend::SipHash`] and matches the generator that's commonly used on
+    /// GPUs.
+    Philox,
+    /// The DES-based round function from masscan's Blackrock2, which also
+    /// splits the domain into halves with division like masscan does. Only
+    /// the first half of the key is used, as masscan's seed.
+    ///
+    /// This is much slower than the other backends and only supports ranges
+    /// that fit in a `u64`. It's a port of masscan's algorithm, but its
+    /// outputs haven't been checked against masscan's yet.
+    Blackrock2,
+    /// FF1 format-preserving encryption from NIST SP 800-38G, over the binary
+    /// representation of the input. This always does FF1's 10 rounds, so the
+    /// number of rounds is ignored.
+    ///
+    /// The AES-128 key is made of both halves of the key, each folded to 64
+    /// bits and encoded as little-endian, so for `PerfectRng<u64>` it's the
+    /// key passed to [`PerfectRng::with_key_bytes`](crate::PerfectRng::with_key_bytes).
+    ///
+    /// NIST requires the domain to have at least a million values, which
+    /// isn't enforced here.
+    #[cfg(feature = "fpe")]
+    Ff1,
+    /// FF3-1 format-preserving encryption from NIST SP 800-38G Rev. 1, over
+    /// the binary representation of the input. This does 8 rounds instead of
+    /// FF1's 10, so it's faster, and the number of rounds is ignored too.
+    ///
+    /// The key is the same as for [`Backend::Ff1`], and the tweak is the first
+    /// 56 bits of the tweak set with
+    /// [`PerfectRng::with_tweak`](crate::PerfectRng::with_tweak).
+    #[cfg(feature = "fpe")]
+    Ff3_1,
+    /// The swap-or-not shuffle, which has provable security bounds even for
+    /// small domains where a Feistel network with cycle walking is weak. The
+    /// number of rounds is per bit of the domain, so a range of 1000 with 6
+    /// rounds does 60 swap-or-not rounds.
+    ///
+    /// Each swap-or-not round is cheaper than a Feistel round, but many more
+    /// of them are needed, so this is slower than [`Backend::SipHash`] on big
+    /// domains.
+    SwapOrNot,
+    /// The Thorp shuffle, another card shuffle with known security bounds
+    /// for small domains. Like [`Backend::SwapOrNot`], the number of rounds
+    /// is per bit of the domain.
+    Thorp,
+    /// An Even-Mansour construction: the input is xored with a key, put
+    /// through a fixed public permutation, and xored with another key. The
+    /// public permutation does one cheap multiply-xorshift round for each
+    /// round, and works on the whole domain at once instead of splitting it
+    /// into halves, so it's cheaper than a Feistel network for big domains.
+    ///
+    /// The security of this relies on the public permutation looking random,
+    /// which is a heuristic, so prefer the Feistel backends when the
+    /// permutation has to be hard to recover.
+    EvenMansour,
+    /// A round function from outside of this crate. See [`RoundFunction`].
+    ///
+    /// This can't be serialized, since there's no way to find the round
+    /// function again when deserializing.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    Custom(&'static dyn RoundFunction),
+}
+
+/// A Feistel round function that can be plugged into a [`PerfectRng`] with
+/// [`Backend::Custom`], to reuse the domain splitting and cycle walking with
+/// a round function that this crate doesn't have.
+///
+/// Backends are compared by their [`RoundFunction::name`], and the name is
+/// part of the hash that [`Checkpoint`](crate::Checkpoint)s are checked
+/// against, so it should be different for every round function.
+///
+/// ```
+/// # use perfect_rand::{Backend, PerfectRng, RoundFunction};
+/// struct Multiply;
+///
+/// impl RoundFunction for Multiply {
+///     fn name(&self) -> &str {
+///         "multiply"
+///     }
+///
+///     fn round(&self, key: u128, j: u128, right: u128) -> u128 {
+///         let x = (right ^ key ^ j).wrapping_mul(0x9e3779b97f4a7c15);
+///         x ^ (x >> 64)
+///     }
+/// }
+///
+/// let randomizer = PerfectRng::new(1000u64, 1, 4).with_backend(Backend::Custom(&Multiply));
+/// assert_eq!(randomizer.unshuffle(randomizer.shuffle(10)), 10);
+/// ```
+///
+/// [`PerfectRng`]: crate::PerfectRng
+pub trait RoundFunction: Send + Sync {
+    /// A name that identifies this round function.
+    fn name(&self) -> &str;
+
+    /// Scramble the right half of the input. Only the lowest bits of the
+    /// output are used, as many as the left half has.
+    ///
+    /// `key` is both halves of the round key, `j` is the round number xored
+    /// with the tweak, and `right` is never more than 64 bits. For integer
+    /// types smaller than `u128`, `key` is the two halves next to each other
+    /// and `j` is never more than 64 bits either.
+    fn round(&self, key: u128, j: u128, right: u128) -> u128;
+}
+
+impl fmt::Display for Backend {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Backend::SipHash => "siphash",
+            Backend::Aes => "aes",
+            Backend::ChaCha => "chacha",
+            Backend::Philox => "philox",
+            Backend::Blackrock2 => "blackrock2",
+            #[cfg(feature = "fpe")]
+            Backend::Ff1 => "ff1",
+            #[cfg(feature = "fpe")]
+            Backend::Ff3_1 => "ff3-1",
+            Backend::SwapOrNot => "swap-or-not",
+            Backend::Thorp => "thorp",
+            Backend::EvenMansour => "even-mansour",
+            Backend::Custom(round_function) => round_function.name(),
+        })
+    }
+}
+
+impl FromStr for Backend {
+    type Err = PerfectRngError;
+
+    /// Parse the name of one of this crate's backends, ignoring case. Custom
+    /// backends can't be parsed.
+    fn from_str(s: &str) -> Result<Self, PerfectRngError> {
+        [
+            Backend::SipHash,
+            Backend::Aes,
+            Backend::ChaCha,
+            Backend::Philox,
+            Backend::Blackrock2,
+            #[cfg(feature = "fpe")]
+            Backend::Ff1,
+            #[cfg(feature = "fpe")]
+            Backend::Ff3_1,
+            Backend::SwapOrNot,
+            Backend::Thorp,
+            Backend::EvenMansour,
+        ]
+        .into_iter()
+        .find(|backend| backend.to_string().eq_ignore_ascii_case(s))
+        .ok_or(PerfectRngError::UnknownBackend)
+    }
+}
+
+impl fmt::Debug for dyn RoundFunction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl PartialEq for Backend {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Backend::Custom(a), Backend::Custom(b)) => a.name() == b.name(),
+            _ => mem::discriminant(self) == mem::discriminant(other),
+        }
+    }
+}
+
+impl Eq for Backend {}
+
+impl Hash for Backend {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        mem::discriminant(self).hash(state);
+        if let Backend::Custom(round_function) = self {
+            round_function.name().hash(state);
+        }
+    }
+}
+
+impl Backend {
+    /// Run the round function. `j` is the round number xored with the tweak,
+    /// and `siprounds` is only used by [`Backend::SipHash`].
+    #[inline]
+    pub(crate) fn round<T: UnsignedInt>(self, key: [T; 2], j: T, right: T, siprounds: usize) -> T {
+        match self {
+            // a constant number of siprounds lets the default be unrolled
+            Backend::SipHash if siprounds == DEFAULT_SIPROUNDS => {
+                T::round(key, j, right, DEFAULT_SIPROUNDS)
+            }
+            Backend::SipHash => T::round(key, j, right, siprounds),
+            Backend::Aes => T::from_u128(aes_round(fold_key(key), j.as_u128(), right.as_u128())),
+            Backend::ChaCha => {
+                T::from_u128(chacha_round(fold_key(key), j.as_u128(), right.as_u128()))
+            }
+            Backend::Philox => {
+                T::from_u128(philox_round(fold_key(key), j.as_u128(), right.as_u128()))
+            }
+            Backend::Custom(round_function) => {
+                T::from_u128(round_function.round(fold_key(key), j.as_u128(), right.as_u128()))
+            }
+            Backend::Blackrock2 => unreachable!("Blackrock2 has its own Feistel network"),
+            #[cfg(feature = "fpe")]
+            Backend::Ff1 => unreachable!("FF1 has its own Feistel network"),
+            #[cfg(feature = "fpe")]
+            Backend::Ff3_1 => unreachable!("FF3-1 has its own Feistel network"),
+            Backend::SwapOrNot | Backend::Thorp => {
+                unreachable!("card shuffles aren't Feistel networks")
+            }
+            Backend::EvenMansour => unreachable!("Even-Mansour isn't a Feistel network"),
+        }
+    }
+
+    /// Whether this is a round function for the Feistel network that splits
+    /// the input by bits, rather than having its own encryption.
+    pub(crate) fn splits_bits(self) -> bool {
+        match self {
+            Backend::SipHash
+            | Backend::Aes
+            | Backend::ChaCha
+            | Backend::Philox
+            | Backend::Custom(_) => true,
+            Backend::Blackrock2 | Backend::SwapOrNot | Backend::Thorp | Backend::EvenMansour => {
+                false
+            }
+            #[cfg(feature = "fpe")]
+            Backend::Ff1 | Backend::Ff3_1 => false,
+        }
+    }
+
+    /// A number that identifies the backend in the config hash. Custom
+    /// backends also have their name hashed.
+    pub(crate) fn id(self) -> u8 {
+        match self {
+            Backend::SipHash => 0,
+            Backend::Aes => 1,
+            Backend::ChaCha => 2,
+            Backend::Blackrock2 => 3,
+            #[cfg(feature = "fpe")]
+            Backend::Ff1 => 4,
+            #[cfg(feature = "fpe")]
+            Backend::Ff3_1 => 5,
+            Backend::SwapOrNot => 6,
+            Backend::Thorp => 7,
+            Backend::Philox => 8,
+            Backend::EvenMansour => 9,
+            Backend::Custom(_) => 255,
+        }
+    }
+
+    /// The backend with the given [`Backend::id`], or `None` if there isn't
+    /// one or it needs a feature that isn't enabled. Custom backends can't be
+    /// looked up.
+    pub(crate) fn from_id(id: u8) -> Option<Backend> {
+        Some(match id {
+            0 => Backend::SipHash,
+            1 => Backend::Aes,
+            2 => Backend::ChaCha,
+            3 => Backend::Blackrock2,
+            #[cfg(feature = "fpe")]
+            4 => Backend::Ff1,
+            #[cfg(feature = "fpe")]
+            5 => Backend::Ff3_1,
+            6 => Backend::SwapOrNot,
+            7 => Backend::Thorp,
+            8 => Backend::Philox,
+            9 => Backend::EvenMansour,
+            _ => return None,
+        })
+    }
+}
+
+/// Combine both halves of a round key into one `u128`. For `u64` and smaller
+/// this is just the two halves next to each other.
+#[inline]
+fn fold_key<T: UnsignedInt>(key: [T; 2]) -> u128 {
+    key[0].as_u128() ^ key[1].as_u128().rotate_left(64)
+}
+
+#[inline]
+fn aes_round(key: u128, j: u128, right: u128) -> u128 {
+    // the right half is never more than 64 bits, so the round number (folded
+    // to 64 bits) fits next to it in the block
+    let j = j ^ (j >> 64);
+    let block = (right ^ (j << 64)) ^ key;
+    let x = aes::two_rounds(block, key, key.rotate_left(32));
+    x ^ (x >> 64)
+}
+
+#[inline]
+const fn quarter_round(s: &mut [u32; 16], a: usize, b: usize, c: usize, d: usize) {
+    s[a] = s[a].wrapping_add(s[b]);
+    s[d] = (s[d] ^ s[a]).rotate_left(16);
+    s[c] = s[c].wrapping_add(s[d]);
+    s[b] = (s[b] ^ s[c]).rotate_left(12);
+    s[a] = s[a].wrapping_add(s[b]);
+    s[d] = (s[d] ^ s[a]).rotate_left(8);
+    s[c] = s[c].wrapping_add(s[d]);
+    s[b] = (s[b] ^ s[c]).rotate_left(7);
+}
+
+#[inline]
+fn chacha_round(key: u128, j: u128, right: u128) -> u128 {
+    let k = [
+        key as u32,
+        (key >> 32) as u32,
+        (key >> 64) as u32,
+        (key >> 96) as u32,
+    ];
+    let j = j ^ (j >> 64);
+    // laid out like ChaCha with a 128-bit key, with the round number as the
+    // block counter and the right half as the nonce
+    let input = [
+        0x61707865,
+        0x3120646e,
+        0x79622d36,
+        0x6b206574,
+        k[0],
+        k[1],
+        k[2],
+        k[3],
+        k[0],
+        k[1],
+        k[2],
+        k[3],
+        j as u32,
+        (j >> 32) as u32,
+        right as u32,
+        (right >> 32) as u32,
+    ];
+
+    let mut s = input;
+    for _ in 0..2 {
+        quarter_round(&mut s, 0, 4, 8, 12);
+        quarter_round(&mut s, 1, 5, 9, 13);
+        quarter_round(&mut s, 2, 6, 10, 14);
+        quarter_round(&mut s, 3, 7, 11, 15);
+        quarter_round(&mut s, 0, 5, 10, 15);
+        quarter_round(&mut s, 1, 6, 11, 12);
+        quarter_round(&mut s, 2, 7, 8, 13);
+        quarter_round(&mut s, 3, 4, 9, 14);
+    }
+
+    let mut out = 0;
+    for i in 0..4 {
+        out |= (s[i].wrapping_add(input[i]) as u128) << (i * 32);
+    }
+    out
+}
+
+/// The multiplier from Random123's Philox 2x64.
+const PHILOX_M: u64 = 0xd2b74407b1ce6e93;
+/// The Weyl sequence constant that's added to the key after every round, the
+/// golden ratio.
+const PHILOX_W: u64 = 0x9e3779b97f4a7c15;
+
+#[inline]
+fn philox2x64(mut x: [u64; 2], mut key: u64, rounds: usize) -> [u64; 2] {
+    for _ in 0..rounds {
+        let product = x[0] as u128 * PHILOX_M as u128;
+        x = [(product >> 64) as u64 ^ key ^ x[1], product as u64];
+        key = key.wrapping_add(PHILOX_W);
+    }
+    x
+}
+
+#[inline]
+fn philox_round(key: u128, j: u128, right: u128) -> u128 {
+    // Philox only has a 64-bit key, so the other half of the key is mixed
+    // into the counter along with the round number
+    let j = j ^ (j >> 64);
+    let x = philox2x64([right as u64 ^ (key >> 64) as u64, j as u64], key as u64, 4);
+    x[0] as u128 | (x[1] as u128) << 64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Backend, RoundFunction};
+    use crate::{PerfectRng, PerfectRng128, PerfectRng32, PerfectRngError};
+
+    #[test]
+    fn chacha_quarter_round() {
+        // RFC 8439 section 2.2.1
+        let mut s = [
+            0x879531e0, 0xc5ecf37d, 0x516461b1, 0xc9a62f8a, 0x44c20ef3, 0x3390af7f, 0xd9fc690b,
+            0x2a5f714c, 0x53372767, 0xb00a5631, 0x974c541a, 0x359e9963, 0x5c971061, 0x3d631689,
+            0x2098d9d6, 0x91dbd320,
+        ];
+        super::quarter_round(&mut s, 2, 7, 8, 13);
+        assert_eq!(
+            s,
+            [
+                0x879531e0, 0xc5ecf37d, 0xbdb886dc, 0xc9a62f8a, 0x44c20ef3, 0x3390af7f, 0xd9fc690b,
+                0xcfacafd2, 0xe46bea80, 0xb00a5631, 0x974c541a, 0x359e9963, 0x5c971061, 0xccc07c79,
+                0x2098d9d6, 0x91dbd320,
+            ]
+        );
+    }
+
+    #[test]
+    fn chacha_is_a_permutation() {
+        let randomizer = PerfectRng::with_key(1000u64, [1, 2], 4).with_backend(Backend::ChaCha);
+        let mut seen = vec![false; 1000];
+        for i in 0..1000 {
+            let x = randomizer.shuffle(i);
+            assert_eq!(randomizer.unshuffle(x), i);
+            assert!(!seen[x as usize]);
+            seen[x as usize] = true;
+        }
+        assert!(!randomizer
+            .iter()
+            .eq(PerfectRng::with_key(1000u64, [1, 2], 4).iter()));
+    }
+
+    #[test]
+    fn chacha_vectors() {
+        // these only change if the permutation does
+        let randomizer = PerfectRng::with_key(1u64 << 32, [1, 2], 4).with_backend(Backend::ChaCha);
+        assert_eq!(
+            [0, 1, 2, 3].map(|i| randomizer.shuffle(i)),
+            [1594902057, 3280157487, 3695578847, 396441787]
+        );
+    }
+
+    #[test]
+    fn parse() {
+        for backend in [
+            Backend::SipHash,
+            Backend::Aes,
+            Backend::ChaCha,
+            Backend::Philox,
+            Backend::Blackrock2,
+            Backend::SwapOrNot,
+            Backend::Thorp,
+            Backend::EvenMansour,
+        ] {
+            assert_eq!(backend.to_string().parse(), Ok(backend));
+        }
+        assert_eq!("SipHash".parse(), Ok(Backend::SipHash));
+        assert_eq!(
+            "des".parse::<Backend>(),
+            Err(PerfectRngError::UnknownBackend)
+        );
+    }
+
+    #[test]
+    fn custom() {
+        struct Xor;
+        impl RoundFunction for Xor {
+            fn name(&self) -> &str {
+                "xor"
+            }
+            fn round(&self, key: u128, j: u128, right: u128) -> u128 {
+                key ^ j ^ right
+            }
+        }
+        struct Other;
+        impl RoundFunction for Other {
+            fn name(&self) -> &str {
+                "other"
+            }
+            fn round(&self, key: u128, j: u128, right: u128) -> u128 {
+                key.wrapping_add(j) ^ right.rotate_left(7)
+            }
+        }
+
+        assert_eq!(Backend::Custom(&Xor), Backend::Custom(&Xor));
+        assert_ne!(Backend::Custom(&Xor), Backend::Custom(&Other));
+        assert_ne!(Backend::Custom(&Xor), Backend::SipHash);
+        assert_eq!(format!("{:?}", Backend::Custom(&Xor)), "Custom(xor)");
+
+        let randomizer =
+            PerfectRng::with_key(1000u64, [1, 2], 4).with_backend(Backend::Custom(&Xor));
+        let mut seen = vec![false; 1000];
+        for i in 0..1000 {
+            let x = randomizer.shuffle(i);
+            assert_eq!(randomizer.unshuffle(x), i);
+            assert!(!seen[x as usize]);
+            seen[x as usize] = true;
+        }
+        let other = PerfectRng::with_key(1000u64, [1, 2], 4).with_backend(Backend::Custom(&Other));
+        assert_ne!(randomizer.config_hash(), other.config_hash());
+    }
+
+    #[test]
+    fn philox_kat() {
+        // from Random123's kat_vectors
+        assert_eq!(
+            super::philox2x64([0, 0], 0, 10),
+            [0xca00a0459843d731, 0x66c24222c9a845b5]
+        );
+    }
+
+    #[test]
+    fn philox_is_a_permutation() {
+        let randomizer = PerfectRng::with_key(1000u64, [1, 2], 4).with_backend(Backend::Philox);
+        let mut seen = vec![false; 1000];
+        for i in 0..1000 {
+            let x = randomizer.shuffle(i);
+            assert_eq!(randomizer.unshuffle(x), i);
+            assert!(!seen[x as usize]);
+            seen[x as usize] = true;
+        }
+
+        let range = u128::MAX / 3;
+        let randomizer = PerfectRng128::new(range, 1, 4).with_backend(Backend::Philox);
+        for i in [0, 1, range / 2, range - 1] {
+            assert_eq!(randomizer.unshuffle(randomizer.shuffle(i)), i);
+        }
+    }
+
+    #[test]
+    fn aes_is_a_permutation() {
+        let randomizer = PerfectRng::new(1000u64, 1, 4).with_backend(Backend::Aes);
+        let plain = PerfectRng::new(1000u64, 1, 4);
+        assert!(!randomizer.iter().eq(plain.iter()));
+
+        let mut seen = vec![false; 1000];
+        for i in 0..1000 {
+            let x = randomizer.shuffle(i);
+            assert_eq!(randomizer.unshuffle(x), i);
+            assert!(!seen[x as usize]);
+            seen[x as usize] = true;
+        }
+
+        let randomizer = PerfectRng32::new(12345, 1, 3).with_backend(Backend::Aes);
+        for i in 0..12345 {
+            assert_eq!(randomizer.unshuffle(randomizer.shuffle(i)), i);
+        }
+
+        let range = u128::MAX / 3;
+        let randomizer = PerfectRng128::new(range, 1, 4).with_backend(Backend::Aes);
+        for i in [0, 1, range / 2, range - 1] {
+            assert_eq!(randomizer.unshuffle(randomizer.shuffle(i)), i);
+        }
+    }
+}
@@ -0,0 +1,223 @@
+use crate::{PerfectRng, UnsignedInt};
+
+/// The number of values that are encrypted together by
+/// [`PerfectRng::shuffle_batch`].
+const LANES: usize = 8;
+
+impl<T: UnsignedInt> PerfectRng<T> {
+    /// Shuffle every value in the slice in place.
+    ///
+    /// This does the same thing as calling [`PerfectRng::shuffle`] on each
+    /// value. It's portable code with no SIMD intrinsics, but it runs the
+    /// Feistel networks for several values side by side, so the CPU can
+    /// overlap their rounds.
+    ///
+    /// ```
+    /// # use perfect_rand::PerfectRng;
+    /// let randomizer = PerfectRng::from_range(100u64);
+    ///
+    /// let mut values = [1, 2, 3, 4];
+    /// randomizer.shuffle_batch(&mut values);
+    /// assert_eq!(values[2], randomizer.shuffle(3));
+    /// ```
+    #[inline]
+    pub fn shuffle_batch(&self, values: &mut [T]) {
+        if !self.backend.splits_bits() || self.split[0] != T::ZERO || self.constant_time {
+            for value in values {
+                *value = self.shuffle(*value);
+            }
+            return;
+        }
+
+        self.shuffle_batch_lanes(values);
+    }
+
+    /// Shuffle every value in `input` and write the results to `output`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the slices aren't the same length.
+    ///
+    /// ```
+    /// # use perfect_rand::PerfectRng;
+    /// let randomizer = PerfectRng::from_range(100u64);
+    ///
+    /// let input = [1, 2, 3, 4];
+    /// let mut output = [0; 4];
+    /// randomizer.shuffle_batch_into(&input, &mut output);
+    /// assert_eq!(output[2], randomizer.shuffle(3));
+    /// ```
+    #[inline]
+    pub fn shuffle_batch_into(&self, input: &[T], output: &mut [T]) {
+        assert_eq!(input.len(), output.len());
+
+        output.copy_from_slice(input);
+        self.shuffle_batch(output);
+    }
+
+    /// Shuffle `start..start + out.len()` into `out`, so `out[i]` is
+    /// `shuffle(start + i)`. This is like [`PerfectRng::shuffle_batch`] for
+    /// consumers that go through the permutation in blocks, without having to
+    /// fill the buffer with the inputs first.
+    ///
+    /// # Panics
+    ///
+    /// Panics if any of the inputs aren't in the range.
+    ///
+    /// ```
+    /// # use perfect_rand::PerfectRng;
+    /// let randomizer = PerfectRng::from_range(1000u64);
+    ///
+    /// let mut block = [0; 64];
+    /// for start in (0..1000).step_by(64) {
+    ///     let block = &mut block[..64.min(1000 - start as usize)];
+    ///     randomizer.shuffle_n(start, block);
+    ///     assert_eq!(block[0], randomizer.shuffle(start));
+    /// }
+    /// ```
+    #[inline]
+    pub fn shuffle_n(&self, start: T, out: &mut [T]) {
+        if out.is_empty() {
+            return;
+        }
+        assert!(
+            start >= self.offset
+                && (start - self.offset).as_u128() + (out.len() as u128 - 1) <= self.max.as_u128(),
+            "the inputs must be in the range"
+        );
+
+        let mut m = start;
+        for value in out.iter_mut() {
+            *value = m;
+            // this can't overflow until after the last value
+            m = m.wrapping_add(T::ONE);
+        }
+        self.shuffle_batch(out);
+    }
+
+    #[inline(always)]
+    fn shuffle_batch_lanes(&self, values: &mut [T]) {
+        let mut chunks = values.chunks_exact_mut(LANES);
+        for chunk in &mut chunks {
+            let chunk: &mut [T; LANES] = chunk.try_into().unwrap();
+            assert!(chunk
+                .iter()
+                .all(|&m| m >= self.offset && m - self.offset <= self.max));
+
+            let shuffled = self.shuffle_lanes(chunk.map(|m| m - self.offset));
+            *chunk = shuffled.map(|c| c + self.offset);
+        }
+        for value in chunks.into_remainder() {
+            *value = self.shuffle(*value);
+        }
+    }
+
+    #[inline(always)]
+    fn shuffle_lanes(&self, m: [T; LANES]) -> [T; LANES] {
+        let mut c = self.encrypt_lanes(m);
+        let mut walks = [0; LANES];
+        // every lane has to keep cycle walking until they're all in range,
+        // but only the lanes that were out of range get updated
+        while c.iter().any(|&c| c > self.max) {
+            let next = self.encrypt_lanes(c);
+            for ((c, next), walks) in c.iter_mut().zip(next).zip(&mut walks) {
+                if *c > self.max {
+                    *c = next;
+                    *walks += 1;
+                }
+            }
+        }
+        for walks in walks {
+            self.counters.record(walks);
+        }
+        c
+    }
+
+    #[inline(always)]
+    fn encrypt_lanes(&self, m: [T; LANES]) -> [T; LANES] {
+        let mut left = m.map(|m| m & self.a_mask);
+        let mut right = m.map(|m| m >> self.a_bits);
+
+        let round = |left: &mut [T; LANES], right: &mut [T; LANES], j: usize, mask: T| {
+            for (left, right) in left.iter_mut().zip(right.iter_mut()) {
+                let tmp = left.wrapping_add(self.round(j, *right)) & mask;
+                *left = *right;
+                *right = tmp;
+            }
+        };
+        // two rounds at a time like encrypt, so the masks are known without
+        // checking whether each round is odd or even
+        let mut j = 1;
+        while j < self.rounds {
+            round(&mut left, &mut right, j, self.a_mask);
+            round(&mut left, &mut right, j + 1, self.b_mask);
+            j += 2;
+        }
+        if j == self.rounds {
+            round(&mut left, &mut right, j, self.a_mask);
+        }
+
+        let mut c = [T::ZERO; LANES];
+        for ((c, left), right) in c.iter_mut().zip(left).zip(right) {
+            *c = if !self.rounds.is_multiple_of(2) {
+                (left << self.a_bits) + right
+            } else {
+                (right << self.a_bits) + left
+            };
+        }
+        c
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{PerfectRng, PerfectRng32};
+
+    #[test]
+    fn batch_matches_shuffle() {
+        for rounds in [0, 1, 2, 3, 4] {
+            let randomizer = PerfectRng::new(1000u64, 0, rounds);
+            // not a multiple of the number of lanes
+            let mut values = (0..999).collect::<Vec<_>>();
+            randomizer.shuffle_batch(&mut values);
+            assert_eq!(values, randomizer.iter().take(999).collect::<Vec<_>>());
+        }
+    }
+
+    #[test]
+    fn batch_offset() {
+        let randomizer = PerfectRng::from_range_bounds(100u64..200);
+        let mut values = (100..200).collect::<Vec<_>>();
+        randomizer.shuffle_batch(&mut values);
+        assert!(values.into_iter().eq(randomizer.iter()));
+    }
+
+    #[test]
+    fn shuffle_n() {
+        let randomizer = PerfectRng::from_range_bounds(100u64..1100);
+        let mut values = vec![0; 1000];
+        for (i, block) in values.chunks_mut(77).enumerate() {
+            randomizer.shuffle_n(100 + i as u64 * 77, block);
+        }
+        assert!(values.into_iter().eq(randomizer.iter()));
+
+        let full = PerfectRng::with_key_inclusive(0..=u16::MAX, [1, 2], 4);
+        let mut end = [0; 3];
+        full.shuffle_n(u16::MAX - 2, &mut end);
+        assert_eq!(end[2], full.shuffle(u16::MAX));
+    }
+
+    #[test]
+    #[should_panic]
+    fn shuffle_n_past_range() {
+        PerfectRng::from_range(1000u64).shuffle_n(990, &mut [0; 11]);
+    }
+
+    #[test]
+    fn batch_lanes_matches_shuffle() {
+        let randomizer = PerfectRng32::new(65536 / 3, 0, 4);
+        let mut values = (0..65536 / 3).collect::<Vec<_>>();
+        randomizer.shuffle_batch_lanes(&mut values);
+        assert_eq!(values, randomizer.iter().collect::<Vec<_>>());
+    }
+}
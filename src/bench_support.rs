@@ -0,0 +1,149 @@
+//! Deterministic fixtures for benchmarking, with the `bench_support` feature.
+//!
+//! These are what the crate's `suites` benchmark uses, so numbers from
+//! `cargo bench --features bench_support --bench suites` can be reproduced
+//! on other hardware, and downstream benchmarks can be compared against
+//! them. The fixtures are fixed: the same size always gives the same ranges,
+//! keys, and inputs.
+//!
+//! ```
+//! use perfect_rand::{bench_support, Backend};
+//!
+//! for &range in bench_support::RangeSize::Medium.ranges() {
+//!     let randomizer = bench_support::rng(range, 4, Backend::SipHash);
+//!     let inputs = bench_support::inputs(range, 256);
+//!     std::hint::black_box(bench_support::shuffle_all(&randomizer, &inputs));
+//! }
+//! ```
+
+use crate::{Backend, PerfectRng, UnsignedInt};
+
+/// The key that every fixture is created with.
+pub const KEY: [u64; 2] = [0x0123_4567_89ab_cdef, 0xfedc_ba98_7654_3210];
+
+/// The round counts that are compared by the benchmarks.
+pub const ROUNDS: [usize; 4] = [2, 3, 4, 8];
+
+/// How big the ranges for a benchmark are. Each size has a power of two,
+/// which is never cycle walked, and a range just over a power of two, which
+/// is walked the most.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RangeSize {
+    /// Ranges that fit in a 16-bit integer.
+    Small,
+    /// Ranges that fit in a 32-bit integer.
+    Medium,
+    /// Ranges that need a 64-bit integer.
+    Huge,
+}
+
+impl RangeSize {
+    /// Every size, from smallest to biggest.
+    pub const ALL: [RangeSize; 3] = [RangeSize::Small, RangeSize::Medium, RangeSize::Huge];
+
+    /// The ranges of this size.
+    #[must_use]
+    pub fn ranges(self) -> &'static [u64] {
+        match self {
+            RangeSize::Small => &[1 << 8, (1 << 8) + 1, 1000],
+            RangeSize::Medium => &[1 << 24, (1 << 24) + 1, 1_000_000_000],
+            RangeSize::Huge => &[1 << 48, (1 << 48) + 1, u64::MAX / 3],
+        }
+    }
+}
+
+/// Every built-in backend that's enabled, in the order they were added.
+#[must_use]
+pub fn backends() -> Vec<Backend> {
+    let mut backends = vec![
+        Backend::SipHash,
+        Backend::Aes,
+        Backend::ChaCha,
+        Backend::Blackrock2,
+    ];
+    #[cfg(feature = "fpe")]
+    backends.extend([Backend::Ff1, Backend::Ff3_1]);
+    backends.extend([
+        Backend::SwapOrNot,
+        Backend::Thorp,
+        Backend::Philox,
+        Backend::EvenMansour,
+    ]);
+    backends
+}
+
+/// A [`PerfectRng`] over `0..range` keyed with [`KEY`].
+///
+/// # Panics
+///
+/// Panics if `range` is 0, or it's too big for the backend.
+#[must_use]
+pub fn rng<T: UnsignedInt>(range: T, rounds: usize, backend: Backend) -> PerfectRng<T> {
+    let key = KEY.map(|k| T::from_u128(u128::from(k)));
+    PerfectRng::with_key(range, key, rounds).with_backend(backend)
+}
+
+/// `n` inputs spread over `0..range`, in an order that's hard for the CPU to
+/// predict. These are the same every time, and for every integer type.
+///
+/// # Panics
+///
+/// Panics if `range` is 0.
+#[must_use]
+pub fn inputs<T: UnsignedInt>(range: T, n: usize) -> Vec<T> {
+    assert_ne!(range, T::ZERO);
+    let range = range.as_u128();
+    let mut state = KEY[0];
+    (0..n)
+        .map(|_| {
+            // splitmix64, which is enough to not look sequential
+            state = state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+            let mut z = state;
+            z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+            z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+            z ^= z >> 31;
+            T::from_u128(u128::from(z) % range)
+        })
+        .collect()
+}
+
+/// Shuffle every input and combine the results into one value, so the work
+/// can be passed to `black_box` without allocating for the outputs.
+///
+/// # Panics
+///
+/// Panics if an input isn't in the range of `rng`.
+#[must_use]
+#[inline]
+pub fn shuffle_all<T: UnsignedInt>(rng: &PerfectRng<T>, inputs: &[T]) -> T {
+    inputs.iter().fold(T::ZERO, |acc, &m| acc ^ rng.shuffle(m))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{backends, inputs, rng, shuffle_all, RangeSize, ROUNDS};
+
+    #[test]
+    fn frozen_inputs() {
+        // changing these would make old numbers incomparable
+        assert_eq!(inputs(1000u64, 4), [965, 243, 966, 740]);
+        assert_eq!(inputs(1000u32, 4), [965, 243, 966, 740]);
+    }
+
+    #[test]
+    fn fixtures() {
+        for size in RangeSize::ALL {
+            for &range in size.ranges() {
+                let inputs = inputs(range, 64);
+                assert!(inputs.iter().all(|&m| m < range));
+
+                for backend in backends() {
+                    let _ = shuffle_all(&rng(range, 4, backend), &inputs);
+                }
+                for rounds in ROUNDS {
+                    let _ = shuffle_all(&rng(range, rounds, crate::Backend::SipHash), &inputs);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,368 @@
+//! A port of the round function and domain split from masscan's
+//! [`crypto-blackrock2.c`](https://github.com/robertdavidgraham/masscan/blob/master/src/crypto-blackrock2.c),
+//! for [`Backend::Blackrock2`](crate::Backend::Blackrock2).
+//!
+//! Unlike the other backends, this splits the domain into two halves with
+//! division instead of bit masks, and the round function uses the DES
+//! S-boxes.
+//!
+//! Nothing checked into the tests comes from masscan itself yet, so it isn't
+//! known to give the same outputs. The outputs pinned in `pinned_vectors`
+//! come from this implementation and only catch accidental changes. To
+//! check this against the C implementation, run
+//!
+//! ```sh
+//! MASSCAN_SRC=path/to/masscan/src RUSTFLAGS="--cfg masscan_ffi" cargo test masscan
+//! ```
+//!
+//! The build script then links masscan's `crypto-blackrock2.c` into the
+//! tests, and `matches_masscan_ffi` compares shuffling and unshuffling for
+//! a thousand random ranges, seeds, and round counts.
+
+use crate::{PerfectRng, UnsignedInt};
+
+/// The DES S-boxes, indexed by the 6-bit input in the usual order (the outer
+/// bits pick the row and the middle four bits pick the column).
+const S_BOXES: [[[u8; 16]; 4]; 8] = [
+    [
+        [14, 4, 13, 1, 2, 15, 11, 8, 3, 10, 6, 12, 5, 9, 0, 7],
+        [0, 15, 7, 4, 14, 2, 13, 1, 10, 6, 12, 11, 9, 5, 3, 8],
+        [4, 1, 14, 8, 13, 6, 2, 11, 15, 12, 9, 7, 3, 10, 5, 0],
+        [15, 12, 8, 2, 4, 9, 1, 7, 5, 11, 3, 14, 10, 0, 6, 13],
+    ],
+    [
+        [15, 1, 8, 14, 6, 11, 3, 4, 9, 7, 2, 13, 12, 0, 5, 10],
+        [3, 13, 4, 7, 15, 2, 8, 14, 12, 0, 1, 10, 6, 9, 11, 5],
+        [0, 14, 7, 11, 10, 4, 13, 1, 5, 8, 12, 6, 9, 3, 2, 15],
+        [13, 8, 10, 1, 3, 15, 4, 2, 11, 6, 7, 12, 0, 5, 14, 9],
+    ],
+    [
+        [10, 0, 9, 14, 6, 3, 15, 5, 1, 13, 12, 7, 11, 4, 2, 8],
+        [13, 7, 0, 9, 3, 4, 6, 10, 2, 8, 5, 14, 12, 11, 15, 1],
+        [13, 6, 4, 9, 8, 15, 3, 0, 11, 1, 2, 12, 5, 10, 14, 7],
+        [1, 10, 13, 0, 6, 9, 8, 7, 4, 15, 14, 3, 11, 5, 2, 12],
+    ],
+    [
+        [7, 13, 14, 3, 0, 6, 9, 10, 1, 2, 8, 5, 11, 12, 4, 15],
+        [13, 8, 11, 5, 6, 15, 0, 3, 4, 7, 2, 12, 1, 10, 14, 9],
+        [10, 6, 9, 0, 12, 11, 7, 13, 15, 1, 3, 14, 5, 2, 8, 4],
+        [3, 15, 0, 6, 10, 1, 13, 8, 9, 4, 5, 11, 12, 7, 2, 14],
+    ],
+    [
+        [2, 12, 4, 1, 7, 10, 11, 6, 8, 5, 3, 15, 13, 0, 14, 9],
+        [14, 11, 2, 12, 4, 7, 13, 1, 5, 0, 15, 10, 3, 9, 8, 6],
+        [4, 2, 1, 11, 10, 13, 7, 8, 15, 9, 12, 5, 6, 3, 0, 14],
+        [11, 8, 12, 7, 1, 14, 2, 13, 6, 15, 0, 9, 10, 4, 5, 3],
+    ],
+    [
+        [12, 1, 10, 15, 9, 2, 6, 8, 0, 13, 3, 4, 14, 7, 5, 11],
+        [10, 15, 4, 2, 7, 12, 9, 5, 6, 1, 13, 14, 0, 11, 3, 8],
+        [9, 14, 15, 5, 2, 8, 12, 3, 7, 0, 4, 10, 1, 13, 11, 6],
+        [4, 3, 2, 12, 9, 5, 15, 10, 11, 14, 1, 7, 6, 0, 8, 13],
+    ],
+    [
+        [4, 11, 2, 14, 15, 0, 8, 13, 3, 12, 9, 7, 5, 10, 6, 1],
+        [13, 0, 11, 7, 4, 9, 1, 10, 14, 3, 5, 12, 2, 15, 8, 6],
+        [1, 4, 11, 13, 12, 3, 7, 14, 10, 15, 6, 8, 0, 5, 9, 2],
+        [6, 11, 13, 8, 1, 4, 10, 7, 9, 5, 0, 15, 14, 2, 3, 12],
+    ],
+    [
+        [13, 2, 8, 4, 6, 15, 11, 1, 10, 9, 3, 14, 5, 0, 12, 7],
+        [1, 15, 13, 8, 10, 3, 7, 4, 12, 5, 6, 11, 0, 14, 9, 2],
+        [7, 11, 4, 1, 9, 12, 14, 2, 0, 6, 10, 13, 15, 3, 5, 8],
+        [2, 1, 14, 7, 4, 10, 8, 13, 15, 12, 9, 0, 3, 5, 6, 11],
+    ],
+];
+
+/// The DES P permutation. Bit `i` of the output (counting from 1 at the most
+/// significant bit) is bit `P[i - 1]` of the input.
+const P: [u32; 32] = [
+    16, 7, 20, 21, 29, 12, 28, 17, 1, 15, 23, 26, 5, 18, 31, 10, 2, 8, 24, 14, 32, 27, 3, 9, 19,
+    13, 30, 6, 22, 11, 4, 25,
+];
+
+/// The combined S-box and P permutation tables that DES implementations like
+/// masscan's use (`SP1` to `SP8`), rotated left by one bit like theirs are.
+/// These are computed from the S-boxes instead of being written out by hand.
+const SP: [[u32; 64]; 8] = {
+    let mut sp = [[0; 64]; 8];
+    let mut n = 0;
+    while n < 8 {
+        let mut i = 0;
+        while i < 64 {
+            let row = ((i >> 4) & 2) | (i & 1);
+            let column = (i >> 1) & 0xf;
+            let s = (S_BOXES[n][row][column] as u32) << (28 - 4 * n);
+
+            let mut permuted: u32 = 0;
+            let mut bit = 0;
+            while bit < 32 {
+                if s & (1 << (32 - P[bit])) != 0 {
+                    permuted |= 1 << (31 - bit);
+                }
+                bit += 1;
+            }
+            sp[n][i] = permuted.rotate_left(1);
+            i += 1;
+        }
+        n += 1;
+    }
+    sp
+};
+
+/// Blackrock2's round function. The seed is rotated left by the round number
+/// and mixed into the right half, which then goes through the DES f-function.
+#[inline]
+pub(crate) fn round(r: u64, right: u64, seed: u64) -> u64 {
+    // `(seed << r) ^ (seed >> (64 - r))` in masscan
+    let t = right ^ seed.rotate_left(r as u32);
+
+    let work = (t as u32).rotate_right(4);
+    let mut f = SP[6][(work & 0x3f) as usize]
+        | SP[4][((work >> 8) & 0x3f) as usize]
+        | SP[2][((work >> 16) & 0x3f) as usize]
+        | SP[0][((work >> 24) & 0x3f) as usize];
+    let work = (t >> 32) as u32;
+    f |= SP[7][(work & 0x3f) as usize]
+        | SP[5][((work >> 8) & 0x3f) as usize]
+        | SP[3][((work >> 16) & 0x3f) as usize]
+        | SP[1][((work >> 24) & 0x3f) as usize];
+    f as u64
+}
+
+/// Split a domain of `range` values into halves of size `a` and `b` where
+/// `a * b > range`, like masscan's `blackrock2_init`.
+///
+/// Returns `None` if `a * b` doesn't fit in a `u64`.
+pub(crate) fn split(range: u64) -> Option<[u64; 2]> {
+    // small ranges are special-cased to keep them from being too non-random
+    let (a, mut b) = match range {
+        0 => (0, 0),
+        1 => (1, 1),
+        2 => (1, 2),
+        3 => (2, 2),
+        4..=6 => (2, 3),
+        7 | 8 => (3, 3),
+        // masscan uses a floating point square root, which gives the same
+        // split as this until the range is too big for an f64 to hold
+        // exactly. Above 2^53 it can round up and give a different split.
+        _ => {
+            let root = range.isqrt();
+            (root - 2, root + 3)
+        }
+    };
+    while a as u128 * b as u128 <= range as u128 {
+        b += 1;
+    }
+    u64::try_from(a as u128 * b as u128).ok()?;
+    Some([a, b])
+}
+
+impl<T: UnsignedInt> PerfectRng<T> {
+    #[inline]
+    pub(crate) fn blackrock2_encrypt(&self, m: T) -> T {
+        let [a, b] = self.split.map(|n| n.as_u128() as u64);
+        let seed = self.key[0].as_u128() as u64;
+        let m = m.as_u128() as u64;
+
+        let mut left = m % a;
+        let mut right = m / a;
+        for j in 1..=self.rounds {
+            let modulus = if j % 2 != 0 { a } else { b };
+            let tmp = (left + round(j as u64, right, seed)) % modulus;
+            left = right;
+            right = tmp;
+        }
+
+        T::from_u128(if !self.rounds.is_multiple_of(2) {
+            a * left + right
+        } else {
+            a * right + left
+        } as u128)
+    }
+
+    #[inline]
+    pub(crate) fn blackrock2_decrypt(&self, c: T) -> T {
+        let [a, b] = self.split.map(|n| n.as_u128() as u64);
+        let seed = self.key[0].as_u128() as u64;
+        let c = c.as_u128() as u64;
+
+        let (mut left, mut right) = if !self.rounds.is_multiple_of(2) {
+            (c / a, c % a)
+        } else {
+            (c % a, c / a)
+        };
+        for j in (1..=self.rounds).rev() {
+            let modulus = if j % 2 != 0 { a } else { b };
+            let f = round(j as u64, left, seed) % modulus;
+            let tmp = (right + modulus - f) % modulus;
+            right = left;
+            left = tmp;
+        }
+
+        T::from_u128((a * right + left) as u128)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{split, SP};
+    use crate::{Backend, PerfectRng};
+
+    #[test]
+    fn sp_tables() {
+        // the first entries of the tables in common DES implementations
+        assert_eq!(SP[0][..4], [0x01010400, 0x00000000, 0x00010000, 0x01010404]);
+        assert_eq!(SP[7][..4], [0x10001040, 0x00001000, 0x00040000, 0x10041040]);
+    }
+
+    #[test]
+    fn splits() {
+        for range in [
+            1,
+            2,
+            5,
+            8,
+            9,
+            100,
+            1000,
+            1 << 32,
+            u64::MAX / 2,
+            u64::MAX - (1 << 33),
+        ] {
+            let [a, b] = split(range).unwrap();
+            assert!(a as u128 * b as u128 > range as u128, "{range}");
+        }
+        assert_eq!(split(u64::MAX), None);
+    }
+
+    #[test]
+    fn splits_near_squares() {
+        // one less than a square, which a floating point square root would
+        // round up to the square for the bigger ones
+        for root in [
+            100,
+            1 << 26,
+            (1 << 26) + 1,
+            (1 << 31) + 12345,
+            u32::MAX as u64,
+        ] {
+            let [a, b] = split(root * root - 1).unwrap();
+            assert_eq!([a, b], [root - 3, root + 4], "{root}");
+            assert!(a as u128 * b as u128 > (root * root - 1) as u128);
+        }
+    }
+
+    #[test]
+    fn splits_like_masscan() {
+        // masscan's `(uint64_t)(sqrt((double)range) - 2)` and
+        // `(uint64_t)(sqrt((double)range) + 3)`, which an f64 computes exactly
+        // for ranges this small
+        for range in [
+            9,
+            10,
+            100,
+            1000,
+            65535,
+            1 << 24,
+            1 << 32,
+            (1 << 52) - 1,
+            ((1 << 26) - 1) * ((1 << 26) - 1) - 1,
+        ] {
+            let root = (range as f64).sqrt();
+            let (a, mut b) = ((root - 2.) as u64, (root + 3.) as u64);
+            while a * b <= range {
+                b += 1;
+            }
+            assert_eq!(split(range), Some([a, b]), "{range}");
+        }
+    }
+
+    #[test]
+    fn near_max() {
+        for range in [u64::MAX - (1 << 34), (u32::MAX as u64).pow(2) - 1] {
+            let randomizer = PerfectRng::masscan_compatible(range, 1234);
+            for i in [0, 1, 2, range / 2, range - 2, range - 1] {
+                let shuffled = randomizer.shuffle(i);
+                assert!(shuffled < range);
+                assert_eq!(randomizer.unshuffle(shuffled), i);
+            }
+        }
+    }
+
+    #[test]
+    fn pinned_vectors() {
+        // these come from this implementation, not from masscan, so they only
+        // catch accidental changes to the permutation
+        let randomizer = PerfectRng::masscan_compatible(1000, 1234);
+        assert_eq!(
+            [0, 1, 2, 3].map(|i| randomizer.shuffle(i)),
+            [576, 588, 186, 361]
+        );
+        let randomizer = PerfectRng::masscan_compatible(1 << 32, 0xdeadbeef);
+        assert_eq!(
+            [0, 1, 2, 3].map(|i| randomizer.shuffle(i)),
+            [3907680603, 3694476740, 2305542941, 3690472564]
+        );
+    }
+
+    #[cfg(masscan_ffi)]
+    extern "C" {
+        fn perfect_rand_blackrock2_shuffle(range: u64, seed: u64, rounds: u32, m: u64) -> u64;
+        fn perfect_rand_blackrock2_unshuffle(range: u64, seed: u64, rounds: u32, c: u64) -> u64;
+    }
+
+    /// Compare random configurations against masscan's C implementation,
+    /// which is linked in by the build script with `--cfg masscan_ffi`.
+    #[test]
+    #[cfg(masscan_ffi)]
+    fn matches_masscan_ffi() {
+        use rand::{rngs::StdRng, Rng, SeedableRng};
+
+        let mut rng = StdRng::seed_from_u64(0);
+        for _ in 0..1000 {
+            let range = match rng.gen_range(0..3) {
+                0 => rng.gen_range(1..1000),
+                1 => rng.gen_range(1..1 << 32),
+                _ => rng.gen_range(1..1 << 48),
+            };
+            let seed = rng.gen();
+            let rounds = rng.gen_range(1..=14);
+            let randomizer = PerfectRng::new(range, seed, rounds).with_backend(Backend::Blackrock2);
+            for _ in 0..8 {
+                let i = rng.gen_range(0..range);
+                let expected =
+                    unsafe { perfect_rand_blackrock2_shuffle(range, seed, rounds as u32, i) };
+                assert_eq!(
+                    randomizer.shuffle(i),
+                    expected,
+                    "range {range}, seed {seed}, rounds {rounds}, index {i}"
+                );
+                assert_eq!(
+                    unsafe {
+                        perfect_rand_blackrock2_unshuffle(range, seed, rounds as u32, expected)
+                    },
+                    i
+                );
+                assert_eq!(randomizer.unshuffle(expected), i);
+            }
+        }
+    }
+
+    #[test]
+    fn is_a_permutation() {
+        for range in [1u64, 2, 7, 10, 1000, 12345] {
+            for rounds in [3, 4] {
+                let randomizer =
+                    PerfectRng::new(range, 1234, rounds).with_backend(Backend::Blackrock2);
+                let mut seen = vec![false; range as usize];
+                for i in 0..range {
+                    let x = randomizer.shuffle(i);
+                    assert_eq!(randomizer.unshuffle(x), i);
+                    assert!(!seen[x as usize]);
+                    seen[x as usize] = true;
+                }
+            }
+        }
+    }
+}
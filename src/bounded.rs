@@ -0,0 +1,217 @@
+use crate::{Backend, PerfectRng, UnsignedInt};
+
+impl<T: UnsignedInt> PerfectRng<T> {
+    /// Like [`PerfectRng::shuffle`], but gives up and returns `None` if the
+    /// value would have to be encrypted again more than `max_walks` times.
+    ///
+    /// Cycle walking always finishes, but the number of walks isn't bounded:
+    /// each one has about a 1 in 2 chance of landing outside the range (much
+    /// less with [`PerfectRng::with_tight_domain`]), so `k` walks happen with
+    /// a probability of around `2^-k`. This is for callers that want to
+    /// notice when that happens.
+    ///
+    /// A value can't be mapped to something else when the limit is hit,
+    /// since then two inputs could give the same output. If there's a hard
+    /// limit on latency, use [`PerfectRng::bounded_fallback`] from the start
+    /// instead, which never walks, so this never gives up on it.
+    ///
+    /// This never does the walks in constant time, even with
+    /// [`PerfectRng::with_constant_time`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `m` isn't in the range.
+    ///
+    /// ```
+    /// # use perfect_rand::PerfectRng;
+    /// let randomizer = PerfectRng::from_range(1000u64);
+    /// if let Some(shuffled) = randomizer.shuffle_bounded(5, 8) {
+    ///     assert_eq!(shuffled, randomizer.shuffle(5));
+    /// }
+    ///
+    /// // never walks, so it never gives up
+    /// let bounded = PerfectRng::from_range(1000u64).bounded_fallback();
+    /// let shuffled = bounded.shuffle_bounded(5, 0).unwrap();
+    /// assert!(shuffled < 1000);
+    /// ```
+    #[must_use]
+    pub fn shuffle_bounded(&self, m: T, max_walks: u32) -> Option<T> {
+        assert!(m >= self.offset && m - self.offset <= self.max);
+
+        let c = self.walk_bounded(self.encrypt(m - self.offset), max_walks, |c| {
+            self.encrypt(c)
+        })?;
+        Some(c + self.offset)
+    }
+
+    /// Like [`PerfectRng::unshuffle`], but gives up and returns `None` if the
+    /// value would have to be decrypted again more than `max_walks` times.
+    /// It gives up for exactly the outputs of
+    /// [`PerfectRng::shuffle_bounded`] that gave up.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `c` isn't in the range.
+    #[must_use]
+    pub fn unshuffle_bounded(&self, c: T, max_walks: u32) -> Option<T> {
+        assert!(c >= self.offset && c - self.offset <= self.max);
+
+        let m = self.walk_bounded(self.decrypt(c - self.offset), max_walks, |m| {
+            self.decrypt(m)
+        })?;
+        Some(m + self.offset)
+    }
+
+    fn walk_bounded(&self, mut c: T, max_walks: u32, f: impl Fn(T) -> T) -> Option<T> {
+        let mut walks = 0;
+        while c > self.max {
+            if walks == max_walks {
+                self.counters.record(u64::from(walks));
+                return None;
+            }
+            c = f(c);
+            walks += 1;
+        }
+        self.counters.record(u64::from(walks));
+        Some(c)
+    }
+
+    /// A `PerfectRng` with the same configuration but a different key, which
+    /// is derived from this one's. It's a different permutation of the same
+    /// range, which walks different values.
+    ///
+    /// The derived key is always the same for the same key, so rekeying can
+    /// be repeated on another machine without storing anything new.
+    ///
+    /// ```
+    /// # use perfect_rand::PerfectRng;
+    /// let randomizer = PerfectRng::with_key(1000u64, [1, 2], 4);
+    /// let rekeyed = randomizer.rekeyed();
+    /// assert!(!randomizer.iter().eq(rekeyed.iter()));
+    /// assert!(rekeyed.iter().eq(PerfectRng::with_key(1000u64, [1, 2], 4).rekeyed().iter()));
+    /// ```
+    #[must_use]
+    pub fn rekeyed(&self) -> Self {
+        // round keys are derived from round numbers starting at 1, so 0 is
+        // never used by the rounds
+        let mut rng = Self::with_key_max(self.max, T::round_key(self.key, 0), self.rounds);
+        rng.offset = self.offset;
+        rng.siprounds = self.siprounds;
+        rng.secret = self.secret;
+        rng.constant_time = self.constant_time;
+        rng.algorithm = self.algorithm;
+        rng.tight = self.tight;
+        rng.set_tweak(self.tweak);
+        rng.set_backend(self.backend)
+            .expect("the backend already worked with this range");
+        rng
+    }
+
+    /// A `PerfectRng` over the same range with the same key, but using
+    /// [`Backend::SwapOrNot`], which works on any range directly and never
+    /// cycle walks. Every shuffle takes the same number of rounds, so
+    /// [`PerfectRng::shuffle_bounded`] never gives up on it, even with a
+    /// `max_walks` of 0. It's slower than the Feistel backends.
+    ///
+    /// It's a different permutation, so pick one or the other for the whole
+    /// range up front rather than switching when a value gives up.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the `PerfectRng` is in constant-time mode, since
+    /// [`Backend::SwapOrNot`] isn't constant-time.
+    ///
+    /// ```
+    /// # use perfect_rand::PerfectRng;
+    /// let fallback = PerfectRng::with_key(1000u64, [1, 2], 4).bounded_fallback();
+    /// let shuffled = fallback.shuffle_bounded(10, 0).unwrap();
+    /// assert_eq!(fallback.unshuffle_bounded(shuffled, 0), Some(10));
+    /// ```
+    #[must_use]
+    pub fn bounded_fallback(&self) -> Self {
+        assert!(
+            !self.constant_time,
+            "the bounded fallback isn't constant-time"
+        );
+        let mut rng = Self::with_key_max(self.max, self.key, self.rounds);
+        rng.offset = self.offset;
+        rng.siprounds = self.siprounds;
+        rng.secret = self.secret;
+        rng.algorithm = self.algorithm;
+        rng.set_tweak(self.tweak);
+        rng.set_backend(Backend::SwapOrNot)
+            .expect("SwapOrNot works with any range");
+        rng
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::PerfectRng;
+
+    #[test]
+    fn gives_up() {
+        let randomizer = PerfectRng::with_key_inclusive(100..=1124u64, [1, 2], 4);
+        let mut gave_up = 0;
+        for i in 100..=1124 {
+            let x = randomizer.shuffle(i);
+            assert_eq!(randomizer.shuffle_bounded(i, u32::MAX), Some(x));
+            assert_eq!(randomizer.unshuffle_bounded(x, u32::MAX), Some(i));
+
+            let bounded = randomizer.shuffle_bounded(i, 0);
+            assert!(bounded.is_none_or(|c| c == x));
+            assert_eq!(
+                bounded.is_none(),
+                randomizer.unshuffle_bounded(x, 0).is_none()
+            );
+            gave_up += usize::from(bounded.is_none());
+        }
+        assert!(gave_up > 0);
+
+        let exact = PerfectRng::from_range(1024u32);
+        assert!((0..1024).all(|i| exact.shuffle_bounded(i, 0).is_some()));
+    }
+
+    #[test]
+    fn rekeyed() {
+        let randomizer = PerfectRng::with_key_inclusive(100..=1099u64, [1, 2], 4)
+            .with_tweak(b"tweak")
+            .with_tight_domain();
+        let rekeyed = randomizer.rekeyed();
+        assert!(rekeyed.is_tight_domain());
+        assert_eq!(rekeyed.raw_max(), randomizer.raw_max());
+
+        let mut values = rekeyed.iter().collect::<Vec<_>>();
+        assert!(!randomizer.iter().eq(values.iter().copied()));
+        values.sort_unstable();
+        assert!(values.into_iter().eq(100..1100));
+    }
+
+    #[test]
+    fn bounded_fallback() {
+        let randomizer = PerfectRng::with_key_inclusive(100..=1124u64, [1, 2], 4).with_tweak(b"a");
+        let fallback = randomizer.bounded_fallback();
+        let mut values = Vec::new();
+        for i in 100..=1124 {
+            let x = fallback.shuffle_bounded(i, 0).unwrap();
+            assert_eq!(fallback.unshuffle_bounded(x, 0), Some(i));
+            values.push(x);
+        }
+        assert!(!randomizer.iter().eq(values.iter().copied()));
+        values.sort_unstable();
+        assert!(values.into_iter().eq(100..=1124));
+
+        let again = PerfectRng::with_key_inclusive(100..=1124u64, [1, 2], 4)
+            .with_tweak(b"a")
+            .bounded_fallback();
+        assert!(again.iter().eq(fallback.iter()));
+    }
+
+    #[test]
+    #[should_panic(expected = "isn't constant-time")]
+    fn bounded_fallback_constant_time() {
+        let _ = PerfectRng::from_range(1000u64)
+            .with_constant_time()
+            .bounded_fallback();
+    }
+}
@@ -0,0 +1,240 @@
+use std::fmt;
+
+use crate::{
+    Backend, BuildError, PerfectRng, SecurityLevel, UnsignedInt, DEFAULT_SIPROUNDS, REDACT_KEYS,
+};
+
+/// A builder for a [`PerfectRng`] that checks its configuration instead of
+/// panicking.
+///
+/// ```
+/// # use perfect_rand::PerfectRngBuilder;
+/// let randomizer = PerfectRngBuilder::new()
+///     .range(2u64.pow(32))
+///     .seed(1234)
+///     .rounds(3)
+///     .backend("chacha".parse().unwrap())
+///     .build()
+///     .unwrap();
+/// ```
+#[derive(Clone)]
+pub struct PerfectRngBuilder<T: UnsignedInt = u64> {
+    range: Option<T>,
+    key: Option<[T; 2]>,
+    rounds: usize,
+    siprounds: usize,
+    backend: Backend,
+}
+
+impl<T: UnsignedInt> Default for PerfectRngBuilder<T> {
+    fn default() -> Self {
+        Self {
+            range: None,
+            key: None,
+            rounds: 4,
+            siprounds: DEFAULT_SIPROUNDS,
+            backend: Backend::SipHash,
+        }
+    }
+}
+
+/// The key is redacted like it is for [`PerfectRng`].
+impl<T: UnsignedInt> fmt::Debug for PerfectRngBuilder<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut debug = f.debug_struct("PerfectRngBuilder");
+        debug.field("range", &self.range);
+        match self.key {
+            Some(_) if REDACT_KEYS => debug.field("key", &format_args!("Some(<redacted>)")),
+            key => debug.field("key", &key),
+        };
+        debug
+            .field("rounds", &self.rounds)
+            .field("siprounds", &self.siprounds)
+            .field("backend", &self.backend)
+            .finish()
+    }
+}
+
+impl<T: UnsignedInt> PerfectRngBuilder<T> {
+    /// Create a builder with a random key and the default number of rounds.
+    /// The range must be set with [`PerfectRngBuilder::range`].
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the number of possible values. See [`PerfectRng::new`].
+    #[must_use]
+    pub fn range(mut self, range: T) -> Self {
+        self.range = Some(range);
+        self
+    }
+
+    /// Set the seed. If neither this nor [`PerfectRngBuilder::with_key`] is
+    /// called, a random key is used.
+    #[must_use]
+    pub fn seed(mut self, seed: T) -> Self {
+        self.key = Some([seed, T::ZERO]);
+        self
+    }
+
+    /// Set both halves of the key. See [`PerfectRng::with_key`].
+    #[must_use]
+    pub fn with_key(mut self, key: [T; 2]) -> Self {
+        self.key = Some(key);
+        self
+    }
+
+    /// Set the number of rounds. Defaults to 4.
+    #[must_use]
+    pub fn rounds(mut self, rounds: usize) -> Self {
+        self.rounds = rounds;
+        self
+    }
+
+    /// Set the number of SipHash rounds in each Feistel round. Defaults to
+    /// 4. See [`PerfectRng::with_siprounds`].
+    #[must_use]
+    pub fn siprounds(mut self, siprounds: usize) -> Self {
+        self.siprounds = siprounds;
+        self
+    }
+
+    /// Set the number of rounds and siprounds from a preset. This is the same
+    /// as calling [`PerfectRngBuilder::rounds`] and
+    /// [`PerfectRngBuilder::siprounds`] with the preset's values.
+    #[must_use]
+    pub fn security(self, level: SecurityLevel) -> Self {
+        self.rounds(level.rounds()).siprounds(level.siprounds())
+    }
+
+    /// Set the round function. Defaults to [`Backend::SipHash`]. See
+    /// [`PerfectRng::with_backend`].
+    #[must_use]
+    pub fn backend(mut self, backend: Backend) -> Self {
+        self.backend = backend;
+        self
+    }
+
+    /// Validate the configuration and create the [`PerfectRng`].
+    ///
+    /// ```
+    /// # use perfect_rand::{BuildError, PerfectRngBuilder};
+    /// let result = PerfectRngBuilder::new().range(0u64).build();
+    /// assert_eq!(result.unwrap_err(), BuildError::ZeroRange);
+    /// ```
+    pub fn build(self) -> Result<PerfectRng<T>, BuildError> {
+        let range = self.range.ok_or(BuildError::MissingRange)?;
+        let key = self.key.unwrap_or_else(|| [T::random(), T::random()]);
+        if self.siprounds == 0 {
+            return Err(BuildError::ZeroSiprounds);
+        }
+        let mut rng =
+            PerfectRng::try_with_key(range, key, self.rounds)?.with_siprounds(self.siprounds);
+        rng.set_backend(self.backend)?;
+        Ok(rng)
+    }
+}
+
+impl<T: UnsignedInt> PerfectRng<T> {
+    /// Create a [`PerfectRngBuilder`] for this type.
+    ///
+    /// ```
+    /// # use perfect_rand::PerfectRng;
+    /// let randomizer = PerfectRng::<u32>::builder().range(100).build().unwrap();
+    /// ```
+    #[must_use]
+    pub fn builder() -> PerfectRngBuilder<T> {
+        PerfectRngBuilder::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::PerfectRngBuilder;
+    use crate::{Backend, BuildError, PerfectRng, SecurityLevel};
+
+    #[test]
+    #[cfg(any(feature = "zeroize", not(feature = "expose_seed")))]
+    fn debug_hides_key() {
+        let builder = PerfectRngBuilder::new()
+            .range(10u64)
+            .with_key([123456789, 987654321]);
+        let debug = format!("{builder:?}");
+        assert!(!debug.contains("123456789"), "{debug}");
+        assert!(!debug.contains("987654321"), "{debug}");
+        assert!(debug.contains("key: Some(<redacted>)"), "{debug}");
+
+        let debug = format!("{:?}", PerfectRngBuilder::<u64>::new());
+        assert!(debug.contains("key: None"), "{debug}");
+    }
+
+    #[test]
+    fn build_matches_new() {
+        let built = PerfectRngBuilder::new()
+            .range(1000u64)
+            .seed(5)
+            .rounds(3)
+            .build()
+            .unwrap();
+        let new = PerfectRng::new(1000u64, 5, 3);
+        assert!(built.iter().eq(new.iter()));
+    }
+
+    #[test]
+    fn build_errors() {
+        assert_eq!(
+            PerfectRngBuilder::<u64>::new().build().unwrap_err(),
+            BuildError::MissingRange
+        );
+        assert_eq!(
+            PerfectRngBuilder::new()
+                .range(10u64)
+                .rounds(0)
+                .build()
+                .unwrap_err(),
+            BuildError::ZeroRounds
+        );
+        assert_eq!(
+            PerfectRngBuilder::new()
+                .range(10u64)
+                .siprounds(0)
+                .build()
+                .unwrap_err(),
+            BuildError::ZeroSiprounds
+        );
+        assert_eq!(
+            PerfectRngBuilder::new()
+                .range(u64::MAX)
+                .backend(Backend::Blackrock2)
+                .build()
+                .unwrap_err(),
+            BuildError::DomainTooLarge
+        );
+    }
+
+    #[test]
+    fn build_security() {
+        let built = PerfectRngBuilder::new()
+            .range(1000u64)
+            .seed(5)
+            .security(SecurityLevel::Hardened)
+            .build()
+            .unwrap();
+        let new = PerfectRng::new(1000u64, 5, 8).with_siprounds(8);
+        assert!(built.iter().eq(new.iter()));
+    }
+
+    #[test]
+    fn build_backend() {
+        let built = PerfectRngBuilder::new()
+            .range(1000u64)
+            .seed(5)
+            .backend(Backend::Aes)
+            .build()
+            .unwrap();
+        assert_eq!(built.backend(), Backend::Aes);
+        let new = PerfectRng::new(1000u64, 5, 4).with_backend(Backend::Aes);
+        assert!(built.iter().eq(new.iter()));
+    }
+}
@@ -0,0 +1,202 @@
+//! `const` versions of the constructor and [`PerfectRng::shuffle`]. Trait
+//! methods can't be called in `const fn`s, so these are implemented
+//! separately for every integer type instead of once generically.
+
+use crate::{
+    stats::Counters,
+    uint::{
+        round128, round16, round32, round64, round_key128, round_key16, round_key32, round_key64,
+    },
+    Algorithm, Backend, PerfectRng, DEFAULT_SIPROUNDS, MAX_ROUNDS,
+};
+
+macro_rules! impl_const {
+    ($ty:ty, $round_key:ident, $round:ident) => {
+        impl PerfectRng<$ty> {
+            /// A `const` version of [`PerfectRng::new`], for building
+            /// permutation tables at compile time.
+            ///
+            /// This creates exactly the same permutation as
+            /// [`PerfectRng::new`] with the same arguments.
+            ///
+            /// If the `zeroize` feature is enabled, `PerfectRng` has a
+            /// destructor, which can't run at compile time. Pass a
+            /// `PerfectRng` created inside a `const` block to
+            /// [`std::mem::forget`] when you're done with it.
+            #[must_use]
+            pub const fn new_const(range: $ty, seed: $ty, rounds: usize) -> Self {
+                Self::with_key_const(range, [seed, 0], rounds)
+            }
+
+            /// A `const` version of [`PerfectRng::with_key`].
+            #[must_use]
+            pub const fn with_key_const(range: $ty, key: [$ty; 2], rounds: usize) -> Self {
+                assert!(range != 0, "the range must not be 0");
+                assert!(rounds <= MAX_ROUNDS);
+
+                let max = range - 1;
+                let bits = <$ty>::BITS - max.leading_zeros();
+                let b = bits / 2;
+                // if an odd number of bits, a gets the leftover bit
+                let a = bits - b;
+
+                let mut round_keys = [[0; 2]; MAX_ROUNDS];
+                let mut round_tweaks = [0; MAX_ROUNDS];
+                let mut j = 1;
+                while j <= MAX_ROUNDS {
+                    if j <= rounds {
+                        round_keys[j - 1] = $round_key(key, j);
+                    }
+                    // there's no tweak yet
+                    round_tweaks[j - 1] = j as $ty;
+                    j += 1;
+                }
+
+                PerfectRng {
+                    max,
+                    offset: 0,
+                    key,
+                    rounds,
+                    siprounds: DEFAULT_SIPROUNDS,
+                    tweak: 0,
+                    backend: Backend::SipHash,
+                    constant_time: false,
+                    algorithm: Algorithm::V1,
+                    exact: max & max.wrapping_add(1) == 0,
+                    secret: false,
+                    a_bits: a,
+                    a_mask: (1 << a) - 1,
+                    b_mask: (1 << b) - 1,
+                    tight: false,
+                    split: [0; 2],
+                    round_keys,
+                    round_tweaks,
+                    counters: Counters::new(),
+                }
+            }
+
+            /// A `const` version of [`PerfectRng::shuffle`].
+            ///
+            /// # Panics
+            ///
+            /// Panics if the backend isn't [`Backend::SipHash`], or if the
+            /// domain is split with division, like with
+            /// [`PerfectRng::with_tight_domain`].
+            ///
+            /// ```
+            /// # use perfect_rand::PerfectRng;
+            /// const SHUFFLED: [u64; 4] = {
+            ///     let randomizer = PerfectRng::<u64>::new_const(4, 1234, 4);
+            ///     let shuffled = [
+            ///         randomizer.shuffle_const(0),
+            ///         randomizer.shuffle_const(1),
+            ///         randomizer.shuffle_const(2),
+            ///         randomizer.shuffle_const(3),
+            ///     ];
+            ///     // in case the zeroize feature is enabled
+            ///     std::mem::forget(randomizer);
+            ///     shuffled
+            /// };
+            /// ```
+            #[must_use]
+            pub const fn shuffle_const(&self, m: $ty) -> $ty {
+                assert!(m >= self.offset && m - self.offset <= self.max);
+                // the other backends aren't const
+                assert!(matches!(self.backend, Backend::SipHash));
+                // neither is the division in the tight split
+                assert!(
+                    self.split[0] == 0,
+                    "shuffle_const only supports the bit split"
+                );
+
+                let mut c = self.encrypt_const(m - self.offset);
+                while c > self.max {
+                    c = self.encrypt_const(c);
+                }
+                c + self.offset
+            }
+
+            const fn encrypt_const(&self, m: $ty) -> $ty {
+                let mut left = m & self.a_mask;
+                let mut right = m >> self.a_bits;
+
+                let mut j = 1;
+                while j <= self.rounds {
+                    let mask = if j % 2 != 0 { self.a_mask } else { self.b_mask };
+                    let tmp = left.wrapping_add($round(
+                        self.round_keys[j - 1],
+                        self.round_tweaks[j - 1],
+                        right,
+                        self.siprounds,
+                    )) & mask;
+                    left = right;
+                    right = tmp;
+                    j += 1;
+                }
+
+                if !self.rounds.is_multiple_of(2) {
+                    (left << self.a_bits) + right
+                } else {
+                    (right << self.a_bits) + left
+                }
+            }
+        }
+    };
+}
+
+impl_const!(u16, round_key16, round16);
+impl_const!(u32, round_key32, round32);
+impl_const!(u64, round_key64, round64);
+impl_const!(u128, round_key128, round128);
+
+#[cfg(test)]
+mod tests {
+    use crate::PerfectRng;
+
+    // forgetting the randomizer is only needed with the zeroize feature
+    #[allow(clippy::forget_non_drop)]
+    const TABLE: [u8; 256] = {
+        let randomizer = PerfectRng::<u16>::new_const(256, 1234, 4);
+        let mut table = [0; 256];
+        let mut i = 0;
+        while i < 256 {
+            table[i] = randomizer.shuffle_const(i as u16) as u8;
+            i += 1;
+        }
+        std::mem::forget(randomizer);
+        table
+    };
+
+    #[test]
+    fn const_table() {
+        let randomizer = PerfectRng::<u16>::new(256, 1234, 4);
+        for (i, &shuffled) in TABLE.iter().enumerate() {
+            assert_eq!(shuffled as u16, randomizer.shuffle(i as u16));
+        }
+    }
+
+    #[test]
+    fn const_matches_runtime() {
+        let const_rng = PerfectRng::<u64>::new_const(1000, 5, 3);
+        let rng = PerfectRng::<u64>::new(1000, 5, 3);
+        for i in 0..1000 {
+            assert_eq!(const_rng.shuffle_const(i), rng.shuffle(i));
+        }
+    }
+
+    #[test]
+    fn tight_domain() {
+        // powers of two keep the bit split with a tight domain
+        let rng = PerfectRng::<u64>::new_const(1024, 5, 3).with_tight_domain();
+        for i in 0..1024 {
+            assert_eq!(rng.shuffle_const(i), rng.shuffle(i));
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "only supports the bit split")]
+    fn tight_domain_split() {
+        let rng = PerfectRng::<u64>::new_const(1000, 5, 3).with_tight_domain();
+        let _ = rng.shuffle_const(0);
+    }
+}
@@ -0,0 +1,196 @@
+use crate::{PerfectRng, UnsignedInt, DEFAULT_SIPROUNDS, MAX_ROUNDS};
+
+/// A [`PerfectRng`] with the number of rounds known at compile time, so the
+/// Feistel network can be unrolled and doesn't have to check which half each
+/// round uses.
+///
+/// This always uses [`Backend::SipHash`](crate::Backend::SipHash) with the
+/// default siprounds, and it's the same permutation as a [`PerfectRng`] with
+/// the same range, key, and rounds.
+///
+/// ```
+/// # use perfect_rand::{PerfectRng, PerfectRngConst};
+/// let key = rand::random();
+/// let randomizer = PerfectRngConst::<4>::with_key(1000u64, key);
+/// assert_eq!(
+///     randomizer.shuffle(5),
+///     PerfectRng::with_key(1000u64, key, 4).shuffle(5)
+/// );
+/// ```
+#[derive(Debug)]
+pub struct PerfectRngConst<const ROUNDS: usize, T: UnsignedInt = u64> {
+    rng: PerfectRng<T>,
+}
+
+impl<const ROUNDS: usize, T: UnsignedInt> PerfectRngConst<ROUNDS, T> {
+    const VALID_ROUNDS: () = assert!(
+        ROUNDS > 0 && ROUNDS <= MAX_ROUNDS,
+        "rounds must be between 1 and MAX_ROUNDS"
+    );
+
+    /// Create a new perfect cipher with a specific range and seed. See
+    /// [`PerfectRng::new`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `range` is 0.
+    #[must_use]
+    pub fn new(range: T, seed: T) -> Self {
+        Self::with_key(range, [seed, T::ZERO])
+    }
+
+    /// Create a new perfect cipher keyed with two integers. See
+    /// [`PerfectRng::with_key`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `range` is 0.
+    #[must_use]
+    pub fn with_key(range: T, key: [T; 2]) -> Self {
+        let () = Self::VALID_ROUNDS;
+        PerfectRngConst {
+            rng: PerfectRng::with_key(range, key, ROUNDS),
+        }
+    }
+
+    /// Create a new perfect cipher with a random key.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `range` is 0.
+    #[must_use]
+    pub fn from_range(range: T) -> Self {
+        Self::with_key(range, [T::random(), T::random()])
+    }
+
+    /// The [`PerfectRng`] with the same permutation, for everything that
+    /// isn't specialized here.
+    #[must_use]
+    pub fn rng(&self) -> &PerfectRng<T> {
+        &self.rng
+    }
+
+    /// Randomize your input. See [`PerfectRng::shuffle`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `m` isn't in the range.
+    #[must_use]
+    #[inline]
+    pub fn shuffle(&self, m: T) -> T {
+        assert!(m <= self.rng.max);
+
+        let mut c = self.encrypt(m);
+        while c > self.rng.max {
+            c = self.encrypt(c);
+        }
+        c
+    }
+
+    /// Reverse [`PerfectRngConst::shuffle`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `c` isn't in the range.
+    #[must_use]
+    #[inline]
+    pub fn unshuffle(&self, c: T) -> T {
+        assert!(c <= self.rng.max);
+
+        let mut m = self.decrypt(c);
+        while m > self.rng.max {
+            m = self.decrypt(m);
+        }
+        m
+    }
+
+    #[inline]
+    fn round(&self, j: usize, right: T) -> T {
+        T::round(
+            self.rng.round_keys[j - 1],
+            self.rng.round_tweaks[j - 1],
+            right,
+            DEFAULT_SIPROUNDS,
+        )
+    }
+
+    #[inline]
+    fn encrypt(&self, m: T) -> T {
+        let rng = &self.rng;
+        let mut left = m & rng.a_mask;
+        let mut right = m >> rng.a_bits;
+
+        // ROUNDS is a constant, so this is unrolled and the masks are picked
+        // at compile time
+        for j in 1..=ROUNDS {
+            let mask = if j % 2 != 0 { rng.a_mask } else { rng.b_mask };
+            let tmp = left.wrapping_add(self.round(j, right)) & mask;
+            left = right;
+            right = tmp;
+        }
+
+        if !ROUNDS.is_multiple_of(2) {
+            (left << rng.a_bits) + right
+        } else {
+            (right << rng.a_bits) + left
+        }
+    }
+
+    #[inline]
+    fn decrypt(&self, c: T) -> T {
+        let rng = &self.rng;
+        let (mut left, mut right) = if !ROUNDS.is_multiple_of(2) {
+            (c >> rng.a_bits, c & rng.a_mask)
+        } else {
+            (c & rng.a_mask, c >> rng.a_bits)
+        };
+
+        for j in (1..=ROUNDS).rev() {
+            let mask = if j % 2 != 0 { rng.a_mask } else { rng.b_mask };
+            let tmp = right.wrapping_sub(self.round(j, left)) & mask;
+            right = left;
+            left = tmp;
+        }
+
+        (right << rng.a_bits) + left
+    }
+}
+
+impl<const ROUNDS: usize, T: UnsignedInt> From<PerfectRngConst<ROUNDS, T>> for PerfectRng<T> {
+    fn from(rng: PerfectRngConst<ROUNDS, T>) -> Self {
+        rng.rng
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::PerfectRngConst;
+    use crate::PerfectRng;
+
+    fn check<const ROUNDS: usize>(range: u32) {
+        let randomizer = PerfectRngConst::<ROUNDS, u32>::with_key(range, [1, 2]);
+        let expected = PerfectRng::with_key(range, [1, 2], ROUNDS);
+        for i in 0..range {
+            let x = randomizer.shuffle(i);
+            assert_eq!(x, expected.shuffle(i), "{ROUNDS} {range}");
+            assert_eq!(randomizer.unshuffle(x), i);
+        }
+    }
+
+    #[test]
+    fn same_as_perfect_rng() {
+        check::<1>(1000);
+        check::<2>(1000);
+        check::<3>(1000);
+        check::<4>(1000);
+        check::<5>(1 << 10);
+        check::<4>(1);
+    }
+
+    #[test]
+    fn into_perfect_rng() {
+        let randomizer = PerfectRngConst::<3>::new(1000u64, 5);
+        let first = randomizer.shuffle(0);
+        assert_eq!(PerfectRng::from(randomizer).shuffle(0), first);
+    }
+}
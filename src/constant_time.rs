@@ -0,0 +1,154 @@
+//! A mode where shuffling takes the same amount of time for every input, for
+//! when the permutation hides something sensitive like IDs.
+//!
+//! Cycle walking normally stops as soon as the output is in the range, so the
+//! time it takes reveals how many times the input had to be encrypted. In
+//! constant-time mode, a fixed number of encryptions is always done and the
+//! first one that's in the range is picked without branching.
+//!
+//! Rust can't guarantee that the compiler won't add branches back, so this is
+//! a best effort.
+
+use std::hint::black_box;
+
+use crate::{Backend, PerfectRng, PerfectRngError, UnsignedInt};
+
+/// The number of encryptions that are always done when the range isn't
+/// exactly the cipher's domain. At least half of the values that a Feistel
+/// network outputs are in the range, so the chance of a value still being
+/// outside of it after this many encryptions is at most 2^-32, and it keeps
+/// walking then.
+const WALKS: usize = 32;
+
+impl Backend {
+    /// Whether this backend's round function doesn't branch on or index memory
+    /// with secret data. Custom round functions are trusted to not do that
+    /// either.
+    pub(crate) fn is_constant_time(self) -> bool {
+        matches!(
+            self,
+            Backend::SipHash
+                | Backend::ChaCha
+                | Backend::Philox
+                | Backend::EvenMansour
+                | Backend::Custom(_)
+        )
+    }
+}
+
+impl<T: UnsignedInt> PerfectRng<T> {
+    /// Make shuffling and unshuffling take the same amount of time no matter
+    /// what the input is, so the timing doesn't leak whether the input had
+    /// to be cycle walked. Rust can't guarantee that the compiler won't add
+    /// branches back, so this is a best effort.
+    ///
+    /// This is much slower when the range isn't a power of two, since every
+    /// value is encrypted 32 times.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the backend isn't constant-time. Only [`Backend::SipHash`],
+    /// [`Backend::ChaCha`], [`Backend::Philox`], [`Backend::EvenMansour`], and
+    /// [`Backend::Custom`] are.
+    ///
+    /// ```
+    /// # use perfect_rand::PerfectRng;
+    /// let key = rand::random();
+    /// let randomizer = PerfectRng::with_key(1000u64, key, 4).with_constant_time();
+    /// // it's the same permutation, only slower
+    /// assert!(randomizer.iter().eq(PerfectRng::with_key(1000u64, key, 4).iter()));
+    /// ```
+    #[must_use]
+    pub fn with_constant_time(mut self) -> Self {
+        self.set_constant_time(true)
+            .expect("the backend isn't constant-time");
+        self
+    }
+
+    /// Like [`PerfectRng::with_constant_time`], but returns an error instead
+    /// of panicking.
+    pub(crate) fn set_constant_time(&mut self, constant_time: bool) -> Result<(), PerfectRngError> {
+        // division isn't constant-time on every CPU
+        if constant_time && (!self.backend.is_constant_time() || self.tight) {
+            return Err(PerfectRngError::NotConstantTime);
+        }
+        self.constant_time = constant_time;
+        Ok(())
+    }
+
+    /// Whether this is in constant-time mode. See
+    /// [`PerfectRng::with_constant_time`].
+    #[must_use]
+    pub fn is_constant_time(&self) -> bool {
+        self.constant_time
+    }
+
+    /// Cycle walk from `c` with `f`, always calling it the same number of
+    /// times. This isn't called when the range is exact, since nothing is
+    /// ever walked then.
+    #[inline]
+    pub(crate) fn walk_constant_time(&self, mut c: T, f: impl Fn(T) -> T) -> T {
+        let mut result = c;
+        let mut found: T = mask(c <= self.max);
+        for _ in 1..WALKS {
+            c = f(c);
+            let first = mask::<T>(c <= self.max) & (found ^ T::MAX);
+            result = (c & first) | (result & (first ^ T::MAX));
+            found = found | first;
+        }
+
+        let mut walks = WALKS as u64 - 1;
+        if found == T::ZERO {
+            while c > self.max {
+                c = f(c);
+                walks += 1;
+            }
+            result = c;
+        }
+        self.counters.record(walks);
+        result
+    }
+}
+
+/// All ones if `condition` is true, and zero otherwise.
+#[inline]
+fn mask<T: UnsignedInt>(condition: bool) -> T {
+    T::ZERO.wrapping_sub(T::from_usize(black_box(condition) as usize))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Backend, PerfectRng, PerfectRngError};
+
+    #[test]
+    fn matches_variable_time() {
+        for range in [1u64, 10, 1000, 1024] {
+            let randomizer = PerfectRng::with_key(range, [1, 2], 4);
+            let constant_time = PerfectRng::with_key(range, [1, 2], 4).with_constant_time();
+            assert!(constant_time.is_constant_time());
+            for i in 0..range {
+                let x = constant_time.shuffle(i);
+                assert_eq!(x, randomizer.shuffle(i));
+                assert_eq!(constant_time.unshuffle(x), i);
+            }
+
+            let mut values = (0..range).collect::<Vec<_>>();
+            constant_time.shuffle_batch(&mut values);
+            assert!(values.into_iter().eq(randomizer.iter()));
+        }
+    }
+
+    #[test]
+    fn unsupported_backend() {
+        let mut randomizer = PerfectRng::from_range(1000u64).with_backend(Backend::Blackrock2);
+        assert_eq!(
+            randomizer.set_constant_time(true).unwrap_err(),
+            PerfectRngError::NotConstantTime
+        );
+        let mut randomizer = PerfectRng::from_range(1000u64).with_constant_time();
+        assert_eq!(
+            randomizer.set_backend(Backend::Aes).unwrap_err(),
+            PerfectRngError::NotConstantTime
+        );
+    }
+}
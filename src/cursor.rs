@@ -0,0 +1,215 @@
+use crate::{PerfectRng, PerfectRngError, UnsignedInt};
+
+/// A position in a permutation that can be saved and resumed later, like
+/// masscan's `paused.conf`.
+///
+/// ```
+/// # use perfect_rand::{PerfectRng, ShuffleCursor};
+/// let key = rand::random();
+/// let mut cursor = ShuffleCursor::new(PerfectRng::with_key(1000u64, key, 4));
+/// cursor.next();
+/// cursor.next();
+/// let checkpoint = cursor.checkpoint().to_bytes();
+///
+/// // ...later, after a restart
+/// let checkpoint = perfect_rand::Checkpoint::from_bytes(&checkpoint).unwrap();
+/// let mut resumed =
+///     ShuffleCursor::resume(PerfectRng::with_key(1000u64, key, 4), checkpoint).unwrap();
+/// assert_eq!(resumed.next(), cursor.next());
+/// ```
+#[derive(Debug)]
+pub struct ShuffleCursor<T: UnsignedInt = u64> {
+    rng: PerfectRng<T>,
+    index: T,
+    /// Whether every value has been shuffled. This can't be stored as
+    /// `index == range`, since the range can be the full domain of `T`.
+    finished: bool,
+}
+
+/// A saved position of a [`ShuffleCursor`].
+///
+/// The checkpoint contains a hash of the cipher's configuration, so it can't
+/// accidentally be resumed with a different permutation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Checkpoint<T: UnsignedInt = u64> {
+    /// The index of the next value that the cursor will shuffle.
+    pub index: T,
+    /// A hash of the configuration of the [`PerfectRng`].
+    pub config_hash: u64,
+    /// Whether the cursor had already shuffled every value.
+    pub finished: bool,
+}
+
+impl<T: UnsignedInt> ShuffleCursor<T> {
+    /// Create a cursor at the start of the permutation.
+    #[must_use]
+    pub fn new(rng: PerfectRng<T>) -> Self {
+        ShuffleCursor {
+            rng,
+            index: T::ZERO,
+            finished: false,
+        }
+    }
+
+    /// Create a cursor that's already finished.
+    pub(crate) fn empty(rng: PerfectRng<T>) -> Self {
+        ShuffleCursor {
+            rng,
+            index: T::ZERO,
+            finished: true,
+        }
+    }
+
+    /// Create a cursor from a checkpoint saved by
+    /// [`ShuffleCursor::checkpoint`].
+    ///
+    /// This returns an error if the checkpoint was made with a differently
+    /// configured [`PerfectRng`].
+    pub fn resume(rng: PerfectRng<T>, checkpoint: Checkpoint<T>) -> Result<Self, PerfectRngError> {
+        if checkpoint.config_hash != rng.config_hash() {
+            return Err(PerfectRngError::CheckpointMismatch);
+        }
+        if checkpoint.index > rng.max {
+            return Err(PerfectRngError::InvalidCheckpoint);
+        }
+
+        Ok(ShuffleCursor {
+            rng,
+            index: checkpoint.index,
+            finished: checkpoint.finished,
+        })
+    }
+
+    /// Save the current position.
+    #[must_use]
+    pub fn checkpoint(&self) -> Checkpoint<T> {
+        Checkpoint {
+            index: self.index,
+            config_hash: self.rng.config_hash(),
+            finished: self.finished,
+        }
+    }
+
+    /// The index of the next value that will be shuffled.
+    #[must_use]
+    pub fn index(&self) -> T {
+        self.index
+    }
+
+    /// The [`PerfectRng`] this cursor is walking through.
+    #[must_use]
+    pub fn rng(&self) -> &PerfectRng<T> {
+        &self.rng
+    }
+}
+
+impl<T: UnsignedInt> Iterator for ShuffleCursor<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        if self.finished {
+            return None;
+        }
+        let shuffled = self.rng.shuffle_nth(self.index);
+        if self.index == self.rng.max {
+            self.finished = true;
+        } else {
+            self.index = self.index + T::ONE;
+        }
+        Some(shuffled)
+    }
+}
+
+impl<T: UnsignedInt> Checkpoint<T> {
+    /// The length of the output of [`Checkpoint::to_bytes`].
+    pub const LEN: usize = T::BITS as usize / 8 + 9;
+
+    /// Encode the checkpoint as little-endian bytes: the index, the config
+    /// hash, then a byte that's 1 if the cursor was finished.
+    #[must_use]
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(Self::LEN);
+        bytes.extend_from_slice(&self.index.as_u128().to_le_bytes()[..T::BITS as usize / 8]);
+        bytes.extend_from_slice(&self.config_hash.to_le_bytes());
+        bytes.push(u8::from(self.finished));
+        bytes
+    }
+
+    /// Decode a checkpoint encoded with [`Checkpoint::to_bytes`].
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, PerfectRngError> {
+        if bytes.len() != Self::LEN {
+            return Err(PerfectRngError::InvalidCheckpoint);
+        }
+        let (index, rest) = bytes.split_at(T::BITS as usize / 8);
+        let (config_hash, finished) = rest.split_at(8);
+        let finished = match finished[0] {
+            0 => false,
+            1 => true,
+            _ => return Err(PerfectRngError::InvalidCheckpoint),
+        };
+
+        let mut index_bytes = [0; 16];
+        index_bytes[..index.len()].copy_from_slice(index);
+        Ok(Checkpoint {
+            index: T::from_u128(u128::from_le_bytes(index_bytes)),
+            config_hash: u64::from_le_bytes(config_hash.try_into().unwrap()),
+            finished,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Checkpoint, ShuffleCursor};
+    use crate::{PerfectRng, PerfectRngError};
+
+    #[test]
+    fn resume_continues() {
+        let mut cursor = ShuffleCursor::new(PerfectRng::new(100u32, 1, 4));
+        let first = cursor.by_ref().take(40).collect::<Vec<_>>();
+        let checkpoint = Checkpoint::from_bytes(&cursor.checkpoint().to_bytes()).unwrap();
+        assert_eq!(checkpoint.index, 40);
+
+        let resumed = ShuffleCursor::resume(PerfectRng::new(100u32, 1, 4), checkpoint).unwrap();
+        let all = first.into_iter().chain(resumed).collect::<Vec<_>>();
+        assert!(all.into_iter().eq(PerfectRng::new(100u32, 1, 4).iter()));
+    }
+
+    #[test]
+    fn resume_different_config() {
+        let cursor = ShuffleCursor::new(PerfectRng::new(100u64, 1, 4));
+        let checkpoint = cursor.checkpoint();
+        for rng in [
+            PerfectRng::new(100u64, 2, 4),
+            PerfectRng::new(101u64, 1, 4),
+            PerfectRng::new(100u64, 1, 3),
+            PerfectRng::new(100u64, 1, 4).with_tweak(b"tweak"),
+        ] {
+            assert_eq!(
+                ShuffleCursor::resume(rng, checkpoint).unwrap_err(),
+                PerfectRngError::CheckpointMismatch
+            );
+        }
+    }
+
+    #[test]
+    fn resume_finished() {
+        let rng = || PerfectRng::<u16>::with_key_inclusive(0..=u16::MAX, [1, 2], 4);
+        let mut cursor = ShuffleCursor::new(rng());
+        assert_eq!(cursor.by_ref().count(), 65536);
+        let checkpoint = Checkpoint::from_bytes(&cursor.checkpoint().to_bytes()).unwrap();
+        assert!(checkpoint.finished);
+        assert_eq!(
+            ShuffleCursor::resume(rng(), checkpoint).unwrap().next(),
+            None
+        );
+    }
+
+    #[test]
+    fn invalid_checkpoint() {
+        assert_eq!(
+            Checkpoint::<u64>::from_bytes(&[0; 3]).unwrap_err(),
+            PerfectRngError::InvalidCheckpoint
+        );
+    }
+}
@@ -0,0 +1,136 @@
+use crate::PerfectRng;
+
+/// A permutation of the `n`-digit decimal strings, for obfuscating numbers
+/// like invoice or account numbers while keeping their printed format.
+///
+/// Leading zeros are part of the string, so `"0042"` is a different input
+/// from `"42"`, and outputs always have exactly `n` digits. See
+/// [`AlphabetRng`](crate::AlphabetRng) for other alphabets.
+///
+/// ```
+/// # use perfect_rand::PerfectRng;
+/// let digits = PerfectRng::digits(8);
+/// let shuffled = digits.shuffle("00001234").unwrap();
+/// assert_eq!(shuffled.len(), 8);
+/// assert_eq!(digits.unshuffle(&shuffled).unwrap(), "00001234");
+///
+/// // not 8 digits
+/// assert_eq!(digits.shuffle("1234"), None);
+/// ```
+#[derive(Debug)]
+pub struct DigitsRng {
+    digits: u32,
+    rng: PerfectRng<u64>,
+}
+
+impl PerfectRng<u64> {
+    /// Create a permutation of the `n`-digit decimal strings with a random
+    /// key and default rounds. See [`DigitsRng`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `n` is 0 or more than 19, since 10^20 doesn't fit in a
+    /// `u64`.
+    #[must_use]
+    pub fn digits(n: u32) -> DigitsRng {
+        DigitsRng::with_key(n, rand::random(), 4)
+    }
+}
+
+impl DigitsRng {
+    /// Create a permutation of the `n`-digit decimal strings, keyed like
+    /// [`PerfectRng::with_key`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `n` is 0 or more than 19.
+    #[must_use]
+    pub fn with_key(n: u32, key: [u64; 2], rounds: usize) -> Self {
+        assert!(
+            (1..=19).contains(&n),
+            "the number of digits must be between 1 and 19"
+        );
+        DigitsRng {
+            digits: n,
+            rng: PerfectRng::with_key(10u64.pow(n), key, rounds),
+        }
+    }
+
+    /// The number of digits in every string.
+    #[must_use]
+    pub fn digits(&self) -> u32 {
+        self.digits
+    }
+
+    /// Shuffle a string of exactly [`DigitsRng::digits`] decimal digits, or
+    /// return `None` if it isn't one.
+    #[must_use]
+    pub fn shuffle(&self, digits: &str) -> Option<String> {
+        let m = self.parse(digits)?;
+        Some(self.format(self.rng.shuffle(m)))
+    }
+
+    /// Reverse [`DigitsRng::shuffle`].
+    #[must_use]
+    pub fn unshuffle(&self, digits: &str) -> Option<String> {
+        let c = self.parse(digits)?;
+        Some(self.format(self.rng.unshuffle(c)))
+    }
+
+    /// The [`PerfectRng`] over `0..10^n` that the strings are shuffled with.
+    #[must_use]
+    pub fn rng(&self) -> &PerfectRng<u64> {
+        &self.rng
+    }
+
+    fn parse(&self, digits: &str) -> Option<u64> {
+        if digits.len() != self.digits as usize || !digits.bytes().all(|b| b.is_ascii_digit()) {
+            return None;
+        }
+        // can't overflow, since there are at most 19 digits
+        digits.parse().ok()
+    }
+
+    fn format(&self, n: u64) -> String {
+        format!("{n:0width$}", width = self.digits as usize)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::DigitsRng;
+
+    #[test]
+    fn is_a_permutation() {
+        let digits = DigitsRng::with_key(3, [1, 2], 4);
+        let mut seen = vec![false; 1000];
+        for i in 0..1000 {
+            let input = format!("{i:03}");
+            let shuffled = digits.shuffle(&input).unwrap();
+            assert_eq!(shuffled.len(), 3);
+            assert_eq!(digits.unshuffle(&shuffled).unwrap(), input);
+
+            let n = shuffled.parse::<usize>().unwrap();
+            assert!(!seen[n]);
+            seen[n] = true;
+        }
+    }
+
+    #[test]
+    fn invalid() {
+        let digits = DigitsRng::with_key(4, [1, 2], 4);
+        for input in ["", "123", "12345", "12a4", "+123", "-123", "１２３４"] {
+            assert_eq!(digits.shuffle(input), None);
+            assert_eq!(digits.unshuffle(input), None);
+        }
+    }
+
+    #[test]
+    fn max_digits() {
+        let digits = DigitsRng::with_key(19, [1, 2], 4);
+        let input = "9999999999999999999";
+        let shuffled = digits.shuffle(input).unwrap();
+        assert_eq!(shuffled.len(), 19);
+        assert_eq!(digits.unshuffle(&shuffled).unwrap(), input);
+    }
+}
@@ -0,0 +1,219 @@
+use std::iter::FusedIterator;
+
+use crate::{AlphabetRng, PerfectRngError, PermutedSlice};
+
+/// Candidate subdomains of a domain in shuffled order, for brute-forcing DNS
+/// names without shuffling a whole wordlist or label space in memory.
+///
+/// The labels come either from a wordlist, which is shuffled by index like a
+/// [`PermutedSlice`], or from every label of a fixed length over an
+/// alphabet, which is shuffled like an [`AlphabetRng`].
+///
+/// ```
+/// # use perfect_rand::Subdomains;
+/// let words = ["www", "mail", "vpn", "dev"];
+/// let subdomains = Subdomains::from_wordlist(&words, "example.com");
+/// let mut names = subdomains.iter().collect::<Vec<_>>();
+/// names.sort();
+/// assert_eq!(
+///     names,
+///     ["dev.example.com", "mail.example.com", "vpn.example.com", "www.example.com"]
+/// );
+///
+/// let subdomains = Subdomains::from_alphabet("abcdefghijklmnopqrstuvwxyz", 3, "example.com").unwrap();
+/// assert_eq!(subdomains.len(), 26 * 26 * 26);
+/// ```
+#[derive(Debug)]
+pub struct Subdomains<'a, S = &'a str> {
+    labels: Labels<'a, S>,
+    /// The domain with a leading dot, or empty if the labels are on their
+    /// own.
+    suffix: String,
+}
+
+// there's only one of these in each `Subdomains`, so boxing the bigger
+// variant wouldn't save anything
+#[allow(clippy::large_enum_variant)]
+#[derive(Debug)]
+enum Labels<'a, S> {
+    Wordlist(PermutedSlice<'a, S>),
+    Alphabet(AlphabetRng),
+}
+
+impl<'a, S: AsRef<str>> Subdomains<'a, S> {
+    /// Shuffle the words in a wordlist, with a random key and default
+    /// rounds.
+    ///
+    /// If `domain` is empty, the labels are yielded on their own.
+    #[must_use]
+    pub fn from_wordlist(words: &'a [S], domain: &str) -> Self {
+        Self::from_wordlist_with_key(words, domain, rand::random(), 4)
+    }
+
+    /// Shuffle the words in a wordlist, keyed like
+    /// [`PerfectRng::with_key`](crate::PerfectRng::with_key). See
+    /// [`Subdomains::from_wordlist`].
+    #[must_use]
+    pub fn from_wordlist_with_key(
+        words: &'a [S],
+        domain: &str,
+        key: [u64; 2],
+        rounds: usize,
+    ) -> Self {
+        Subdomains {
+            labels: Labels::Wordlist(PermutedSlice::with_key(words, key, rounds)),
+            suffix: suffix(domain),
+        }
+    }
+}
+
+impl Subdomains<'static> {
+    /// Shuffle every label of exactly `len` characters from `alphabet`, with
+    /// a random key and default rounds.
+    ///
+    /// This returns the same errors as [`AlphabetRng::new`].
+    pub fn from_alphabet(
+        alphabet: &str,
+        len: usize,
+        domain: &str,
+    ) -> Result<Self, PerfectRngError> {
+        Self::from_alphabet_with_key(alphabet, len, domain, rand::random(), 4)
+    }
+
+    /// Shuffle every label of exactly `len` characters from `alphabet`, keyed
+    /// like [`PerfectRng::with_key`](crate::PerfectRng::with_key). See
+    /// [`Subdomains::from_alphabet`].
+    pub fn from_alphabet_with_key(
+        alphabet: &str,
+        len: usize,
+        domain: &str,
+        key: [u128; 2],
+        rounds: usize,
+    ) -> Result<Self, PerfectRngError> {
+        Ok(Subdomains {
+            labels: Labels::Alphabet(AlphabetRng::with_key(alphabet, len, key, rounds)?),
+            suffix: suffix(domain),
+        })
+    }
+}
+
+fn suffix(domain: &str) -> String {
+    let domain = domain.trim_start_matches('.');
+    if domain.is_empty() {
+        String::new()
+    } else {
+        format!(".{domain}")
+    }
+}
+
+impl<'a, S: AsRef<str>> Subdomains<'a, S> {
+    /// The number of candidates, which saturates at `u128::MAX`.
+    #[must_use]
+    pub fn len(&self) -> u128 {
+        match &self.labels {
+            Labels::Wordlist(words) => words.len() as u128,
+            Labels::Alphabet(alphabet) => alphabet.rng().max.saturating_add(1),
+        }
+    }
+
+    /// Whether there are no candidates, which is only possible with an empty
+    /// wordlist.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Get the candidate at a position in the shuffled order, or `None` if
+    /// the index isn't less than [`Subdomains::len`].
+    #[must_use]
+    pub fn get(&self, index: u128) -> Option<String> {
+        if index >= self.len() {
+            return None;
+        }
+        let mut name = match &self.labels {
+            Labels::Wordlist(words) => words.get(index as usize)?.as_ref().to_owned(),
+            Labels::Alphabet(alphabet) => alphabet.encode(alphabet.rng().shuffle(index)),
+        };
+        name.push_str(&self.suffix);
+        Some(name)
+    }
+
+    /// Iterate over every candidate in shuffled order.
+    #[must_use]
+    pub fn iter(&self) -> SubdomainIter<'_, 'a, S> {
+        SubdomainIter {
+            subdomains: self,
+            index: 0,
+        }
+    }
+}
+
+/// An iterator over the candidates of a [`Subdomains`], in shuffled order.
+///
+/// This is created by [`Subdomains::iter`].
+#[derive(Debug)]
+pub struct SubdomainIter<'s, 'a, S> {
+    subdomains: &'s Subdomains<'a, S>,
+    index: u128,
+}
+
+impl<S: AsRef<str>> Iterator for SubdomainIter<'_, '_, S> {
+    type Item = String;
+
+    fn next(&mut self) -> Option<String> {
+        let name = self.subdomains.get(self.index)?;
+        self.index += 1;
+        Some(name)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        match usize::try_from(self.subdomains.len() - self.index) {
+            Ok(remaining) => (remaining, Some(remaining)),
+            Err(_) => (usize::MAX, None),
+        }
+    }
+}
+
+impl<S: AsRef<str>> FusedIterator for SubdomainIter<'_, '_, S> {}
+
+#[cfg(test)]
+mod tests {
+    use super::Subdomains;
+    use crate::PerfectRngError;
+
+    #[test]
+    fn wordlist() {
+        let words = (0..1000).map(|i| format!("host{i}")).collect::<Vec<_>>();
+        let subdomains = Subdomains::from_wordlist_with_key(&words, ".example.com", [1, 2], 4);
+        assert_eq!(subdomains.len(), 1000);
+        let mut names = subdomains.iter().collect::<Vec<_>>();
+        assert_ne!(names[0], "host0.example.com");
+        names.sort();
+        let mut expected = words
+            .iter()
+            .map(|word| format!("{word}.example.com"))
+            .collect::<Vec<_>>();
+        expected.sort();
+        assert_eq!(names, expected);
+
+        let empty = Subdomains::<&str>::from_wordlist(&[], "example.com");
+        assert!(empty.is_empty());
+        assert_eq!(empty.iter().next(), None);
+    }
+
+    #[test]
+    fn alphabet() {
+        let subdomains = Subdomains::from_alphabet_with_key("ab-", 4, "", [1, 2], 4).unwrap();
+        let mut names = subdomains.iter().collect::<Vec<_>>();
+        assert_eq!(names.len(), 81);
+        names.sort();
+        names.dedup();
+        assert_eq!(names.len(), 81);
+        assert!(names.iter().all(|name| name.len() == 4));
+
+        assert_eq!(
+            Subdomains::from_alphabet("aa", 3, "example.com").unwrap_err(),
+            PerfectRngError::InvalidAlphabet
+        );
+    }
+}
@@ -0,0 +1,239 @@
+use std::{iter::FusedIterator, ops::Range};
+
+use crate::{PerfectRng, PerfectRngError, ShuffledIter, UnsignedInt};
+
+/// A permutation over a union of disjoint ranges, like the list of subnets
+/// that a scan targets.
+///
+/// The ranges are laid out one after another in a dense index space, which
+/// is shuffled by a [`PerfectRng`] and then mapped back onto the ranges.
+///
+/// ```
+/// # use perfect_rand::DomainSet;
+/// let set = DomainSet::new([0..1000u64, 5000..9000]).unwrap();
+/// assert_eq!(set.len(), 5000);
+///
+/// let mut shuffled = set.iter().collect::<Vec<_>>();
+/// shuffled.sort();
+/// assert!(shuffled.into_iter().eq((0..1000).chain(5000..9000)));
+/// ```
+#[derive(Debug)]
+pub struct DomainSet<T: UnsignedInt = u64> {
+    /// The non-empty ranges, sorted by their start.
+    ranges: Vec<Range<T>>,
+    /// The index in the dense index space where each range starts.
+    starts: Vec<T>,
+    rng: PerfectRng<T>,
+}
+
+impl<T: UnsignedInt> DomainSet<T> {
+    /// Create a set from disjoint ranges, with a random key and default
+    /// rounds. The ranges don't have to be sorted, and empty ranges are
+    /// ignored.
+    ///
+    /// This returns an error if the ranges overlap or are all empty.
+    pub fn new(ranges: impl IntoIterator<Item = Range<T>>) -> Result<Self, PerfectRngError> {
+        Self::with_key(ranges, [T::random(), T::random()], 4)
+    }
+
+    /// Create a set from disjoint ranges, keyed like
+    /// [`PerfectRng::with_key`]. See [`DomainSet::new`].
+    pub fn with_key(
+        ranges: impl IntoIterator<Item = Range<T>>,
+        key: [T; 2],
+        rounds: usize,
+    ) -> Result<Self, PerfectRngError> {
+        let mut ranges = ranges
+            .into_iter()
+            .filter(|range| range.start < range.end)
+            .collect::<Vec<_>>();
+        ranges.sort_unstable_by_key(|range| range.start);
+
+        // the ranges are disjoint and exclusive, so the total length always
+        // fits in T
+        let mut starts = Vec::with_capacity(ranges.len());
+        let mut len = T::ZERO;
+        for (i, range) in ranges.iter().enumerate() {
+            if i > 0 && ranges[i - 1].end > range.start {
+                return Err(PerfectRngError::OverlappingRanges);
+            }
+            starts.push(len);
+            len = len + (range.end - range.start);
+        }
+
+        let rng = PerfectRng::try_with_key(len, key, rounds)?;
+        Ok(DomainSet {
+            ranges,
+            starts,
+            rng,
+        })
+    }
+
+    /// The number of values in the set.
+    #[must_use]
+    pub fn len(&self) -> T {
+        self.rng.max + T::ONE
+    }
+
+    /// Always `false`, since a set can't be created without any values.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        false
+    }
+
+    /// Whether the value is in one of the ranges.
+    #[must_use]
+    pub fn contains(&self, value: T) -> bool {
+        self.range_containing(value).is_some()
+    }
+
+    /// Get the value at a position in the shuffled set.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` isn't less than [`DomainSet::len`].
+    #[must_use]
+    pub fn shuffle(&self, index: T) -> T {
+        self.value_at(self.rng.shuffle(index))
+    }
+
+    /// Reverse [`DomainSet::shuffle`], getting the position of a value in the
+    /// shuffled set. Returns `None` if the value isn't in the set.
+    ///
+    /// ```
+    /// # use perfect_rand::DomainSet;
+    /// let set = DomainSet::new([10..20u64, 30..40]).unwrap();
+    /// let index = set.unshuffle(35).unwrap();
+    /// assert_eq!(set.shuffle(index), 35);
+    /// assert_eq!(set.unshuffle(25), None);
+    /// ```
+    #[must_use]
+    pub fn unshuffle(&self, value: T) -> Option<T> {
+        let i = self.range_containing(value)?;
+        let index = self.starts[i] + (value - self.ranges[i].start);
+        Some(self.rng.unshuffle(index))
+    }
+
+    /// Iterate over every value in the set in shuffled order.
+    #[must_use]
+    pub fn iter(&self) -> DomainSetIter<'_, T> {
+        DomainSetIter {
+            set: self,
+            iter: self.rng.iter(),
+        }
+    }
+
+    /// The [`PerfectRng`] that shuffles the dense index space.
+    #[must_use]
+    pub fn rng(&self) -> &PerfectRng<T> {
+        &self.rng
+    }
+
+    /// Map an index in the dense index space to the value in the ranges.
+    fn value_at(&self, index: T) -> T {
+        // the last range that starts at or before the index
+        let i = self.starts.partition_point(|&start| start <= index) - 1;
+        self.ranges[i].start + (index - self.starts[i])
+    }
+
+    fn range_containing(&self, value: T) -> Option<usize> {
+        let i = self
+            .ranges
+            .partition_point(|range| range.start <= value)
+            .checked_sub(1)?;
+        (value < self.ranges[i].end).then_some(i)
+    }
+}
+
+impl<'a, T: UnsignedInt> IntoIterator for &'a DomainSet<T> {
+    type Item = T;
+    type IntoIter = DomainSetIter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// An iterator over every value in a [`DomainSet`], in shuffled order.
+///
+/// This is created by [`DomainSet::iter`].
+#[derive(Debug, Clone)]
+pub struct DomainSetIter<'a, T: UnsignedInt = u64> {
+    set: &'a DomainSet<T>,
+    iter: ShuffledIter<'a, T>,
+}
+
+impl<T: UnsignedInt> DomainSetIter<'_, T> {
+    /// Jump to a position in the shuffled set. See [`ShuffledIter::seek`].
+    pub fn seek(&mut self, index: T) {
+        self.iter.seek(index);
+    }
+}
+
+impl<T: UnsignedInt> Iterator for DomainSetIter<'_, T> {
+    type Item = T;
+
+    #[inline]
+    fn next(&mut self) -> Option<T> {
+        self.iter.next().map(|index| self.set.value_at(index))
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+
+    #[inline]
+    fn nth(&mut self, n: usize) -> Option<T> {
+        self.iter.nth(n).map(|index| self.set.value_at(index))
+    }
+}
+
+impl<T: UnsignedInt> DoubleEndedIterator for DomainSetIter<'_, T> {
+    #[inline]
+    fn next_back(&mut self) -> Option<T> {
+        self.iter.next_back().map(|index| self.set.value_at(index))
+    }
+}
+
+impl<T: UnsignedInt> ExactSizeIterator for DomainSetIter<'_, T> {}
+
+impl<T: UnsignedInt> FusedIterator for DomainSetIter<'_, T> {}
+
+#[cfg(test)]
+mod tests {
+    use super::DomainSet;
+    use crate::PerfectRngError;
+
+    #[test]
+    fn covers_every_range() {
+        let set =
+            DomainSet::with_key([5000..9000u32, 0..1000, 20..20, 9000..9001], [1, 2], 4).unwrap();
+        assert_eq!(set.len(), 5001);
+
+        let mut shuffled = set.iter().collect::<Vec<_>>();
+        for (i, &value) in shuffled.iter().enumerate() {
+            assert_eq!(set.shuffle(i as u32), value);
+            assert_eq!(set.unshuffle(value), Some(i as u32));
+        }
+        shuffled.sort_unstable();
+        assert!(shuffled.into_iter().eq((0..1000).chain(5000..9001)));
+
+        assert!(set.contains(9000));
+        assert!(!set.contains(1000));
+        assert_eq!(set.unshuffle(4999), None);
+        assert_eq!(set.unshuffle(u32::MAX), None);
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(
+            DomainSet::new([0..10u64, 5..20]).unwrap_err(),
+            PerfectRngError::OverlappingRanges
+        );
+        assert_eq!(
+            DomainSet::new([0..0u64, 5..5]).unwrap_err(),
+            PerfectRngError::ZeroRange
+        );
+    }
+}
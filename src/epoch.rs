@@ -0,0 +1,141 @@
+use std::fmt;
+
+use crate::{PerfectRng, REDACT_KEYS};
+
+/// A different permutation of a dataset's indices for every training epoch,
+/// like shuffling in a data loader but without storing the indices.
+///
+/// The permutation for epoch `n` only depends on the base seed and `n`, so
+/// training can be resumed from any epoch, and workers on other machines
+/// agree on the order without communicating.
+///
+/// ```
+/// # use perfect_rand::EpochShuffler;
+/// let shuffler = EpochShuffler::new(1000, 42);
+/// let first = shuffler.epoch(0).iter().collect::<Vec<_>>();
+/// let second = shuffler.epoch(1).iter().collect::<Vec<_>>();
+/// assert_ne!(first, second);
+///
+/// // the same seed and epoch always give the same order
+/// assert!(EpochShuffler::new(1000, 42).epoch(1).iter().eq(second));
+/// ```
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct EpochShuffler {
+    len: u64,
+    base_seed: u64,
+}
+
+/// The base seed is redacted like the key of a [`PerfectRng`].
+impl fmt::Debug for EpochShuffler {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut debug = f.debug_struct("EpochShuffler");
+        debug.field("len", &self.len);
+        if REDACT_KEYS {
+            debug.field("base_seed", &format_args!("<redacted>"));
+        } else {
+            debug.field("base_seed", &self.base_seed);
+        }
+        debug.finish()
+    }
+}
+
+impl EpochShuffler {
+    /// Create a shuffler for a dataset with `dataset_len` examples.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `dataset_len` is 0.
+    #[must_use]
+    pub fn new(dataset_len: u64, base_seed: u64) -> Self {
+        assert!(dataset_len != 0, "the dataset must not be empty");
+        EpochShuffler {
+            len: dataset_len,
+            base_seed,
+        }
+    }
+
+    /// The number of examples in the dataset.
+    #[must_use]
+    pub fn len(&self) -> u64 {
+        self.len
+    }
+
+    /// Always `false`, since [`EpochShuffler::new`] doesn't allow empty
+    /// datasets.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        false
+    }
+
+    /// The permutation of `0..dataset_len` for epoch `n`. Use
+    /// [`PerfectRng::iter`] for the order of the whole epoch, or
+    /// [`PerfectRng::shuffle`] for the example at one step.
+    ///
+    /// ```
+    /// # use perfect_rand::EpochShuffler;
+    /// let epoch = EpochShuffler::new(1000, 42).epoch(3);
+    /// let step = 500;
+    /// let example = epoch.shuffle(step);
+    /// assert_eq!(epoch.unshuffle(example), step);
+    /// ```
+    #[must_use]
+    pub fn epoch(&self, n: u64) -> PerfectRng<u64> {
+        PerfectRng::new(self.len, self.base_seed, 4).with_tweak(&n.to_le_bytes())
+    }
+
+    /// The permutations for epochs 0, 1, 2, and so on.
+    ///
+    /// ```
+    /// # use perfect_rand::EpochShuffler;
+    /// let shuffler = EpochShuffler::new(100, 42);
+    /// for (n, epoch) in shuffler.epochs().enumerate().take(3) {
+    ///     assert_eq!(epoch, shuffler.epoch(n as u64));
+    /// }
+    /// ```
+    pub fn epochs(&self) -> impl Iterator<Item = PerfectRng<u64>> + '_ {
+        (0..).map(|n| self.epoch(n))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::EpochShuffler;
+
+    #[test]
+    fn epochs_are_permutations() {
+        let shuffler = EpochShuffler::new(1000, 1);
+        let mut orders = Vec::new();
+        for epoch in shuffler.epochs().take(5) {
+            let order = epoch.iter().collect::<Vec<_>>();
+            let mut sorted = order.clone();
+            sorted.sort_unstable();
+            assert!(sorted.into_iter().eq(0..1000));
+            assert!(!orders.contains(&order));
+            orders.push(order);
+        }
+    }
+
+    #[test]
+    fn seeds() {
+        let a = EpochShuffler::new(1000, 1);
+        let b = EpochShuffler::new(1000, 2);
+        assert_eq!(a.epoch(0), EpochShuffler::new(1000, 1).epoch(0));
+        assert!(!a.epoch(0).iter().eq(b.epoch(0).iter()));
+        // epochs of one seed aren't shifted epochs of another
+        assert!(!a.epoch(1).iter().eq(b.epoch(0).iter()));
+    }
+
+    #[test]
+    #[cfg(any(feature = "zeroize", not(feature = "expose_seed")))]
+    fn debug_hides_seed() {
+        let debug = format!("{:?}", EpochShuffler::new(1000, 123456789));
+        assert!(!debug.contains("123456789"), "{debug}");
+        assert!(debug.contains("base_seed: <redacted>"), "{debug}");
+    }
+
+    #[test]
+    #[should_panic(expected = "must not be empty")]
+    fn empty() {
+        let _ = EpochShuffler::new(0, 1);
+    }
+}
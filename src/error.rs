@@ -0,0 +1,104 @@
+use std::fmt;
+
+use crate::MAX_ROUNDS;
+
+/// An error returned when a [`PerfectRng`](crate::PerfectRng) can't be
+/// created with the given configuration.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum PerfectRngError {
+    /// [`PerfectRngBuilder::range`](crate::PerfectRngBuilder::range) was never
+    /// called.
+    MissingRange,
+    /// The range was 0, so there's nothing to shuffle.
+    ZeroRange,
+    /// The number of rounds was 0, which would make the permutation the
+    /// identity.
+    ZeroRounds,
+    /// The number of rounds was more than [`MAX_ROUNDS`].
+    TooManyRounds(usize),
+    /// The number of SipHash rounds in each Feistel round was 0, which would
+    /// make the round function ignore its input.
+    ZeroSiprounds,
+    /// A [`Checkpoint`](crate::Checkpoint) was made with a differently
+    /// configured `PerfectRng` than the one it's being resumed with.
+    CheckpointMismatch,
+    /// A [`Checkpoint`](crate::Checkpoint) couldn't be decoded, or its index
+    /// is past the end of the range.
+    InvalidCheckpoint,
+    /// The ranges given to a [`DomainSet`](crate::DomainSet) overlap, so some
+    /// values would be shuffled more than once.
+    OverlappingRanges,
+    /// The domain has more values than fit in the integer type, or than the
+    /// [`Backend`](crate::Backend) supports.
+    DomainTooLarge,
+    /// A [`Backend`](crate::Backend) was parsed from a name that isn't one of
+    /// the backends, or that needs a feature that isn't enabled.
+    UnknownBackend,
+    /// Constant-time mode was used with a [`Backend`](crate::Backend) that
+    /// isn't constant-time. See
+    /// [`PerfectRng::with_constant_time`](crate::PerfectRng::with_constant_time).
+    NotConstantTime,
+    /// The alphabet given to an [`AlphabetRng`](crate::AlphabetRng) has fewer
+    /// than two characters, or has the same character more than once.
+    InvalidAlphabet,
+    /// A [`TargetSet`](crate::TargetSet) couldn't be parsed because one of
+    /// the targets isn't an IPv4 address, CIDR block, or range.
+    InvalidTarget,
+    /// A [`PortSet`](crate::PortSet) couldn't be parsed because one of the
+    /// ports isn't a port number or range with an optional protocol prefix.
+    InvalidPortSpec,
+    /// A [`ScanState`](crate::ScanState) couldn't be saved, because the key
+    /// is secret or the [`Backend`](crate::Backend) is custom.
+    NotSerializable,
+    /// A [`ScanState`](crate::ScanState) couldn't be decoded, or it was saved
+    /// with a different integer type or version of the format.
+    InvalidState,
+    /// The table given to a [`TablePermutation`](crate::TablePermutation)
+    /// doesn't have every index from 0 to its length exactly once.
+    NotAPermutation,
+}
+
+/// The error returned by
+/// [`PerfectRngBuilder::build`](crate::PerfectRngBuilder::build).
+pub type BuildError = PerfectRngError;
+
+impl fmt::Display for PerfectRngError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PerfectRngError::MissingRange => write!(f, "no range was given"),
+            PerfectRngError::ZeroRange => write!(f, "the range must not be 0"),
+            PerfectRngError::ZeroRounds => write!(f, "the number of rounds must not be 0"),
+            PerfectRngError::TooManyRounds(rounds) => write!(
+                f,
+                "{rounds} rounds is more than the maximum of {MAX_ROUNDS}"
+            ),
+            PerfectRngError::ZeroSiprounds => {
+                write!(f, "the number of siprounds must not be 0")
+            }
+            PerfectRngError::CheckpointMismatch => {
+                write!(f, "the checkpoint was made with a different configuration")
+            }
+            PerfectRngError::InvalidCheckpoint => write!(f, "the checkpoint is invalid"),
+            PerfectRngError::OverlappingRanges => write!(f, "the ranges must not overlap"),
+            PerfectRngError::DomainTooLarge => {
+                write!(f, "the domain has too many values for the integer type")
+            }
+            PerfectRngError::UnknownBackend => write!(f, "unknown backend"),
+            PerfectRngError::NotConstantTime => write!(f, "the backend isn't constant-time"),
+            PerfectRngError::InvalidAlphabet => write!(
+                f,
+                "the alphabet must have at least two characters and no duplicates"
+            ),
+            PerfectRngError::InvalidTarget => write!(f, "invalid target"),
+            PerfectRngError::InvalidPortSpec => write!(f, "invalid port specification"),
+            PerfectRngError::NotSerializable => {
+                write!(f, "the key is secret or the backend is custom")
+            }
+            PerfectRngError::InvalidState => write!(f, "the saved state is invalid"),
+            PerfectRngError::NotAPermutation => write!(f, "the table isn't a permutation"),
+        }
+    }
+}
+
+impl std::error::Error for PerfectRngError {}
@@ -0,0 +1,114 @@
+//! An Even-Mansour construction, for
+//! [`Backend::EvenMansour`](crate::Backend::EvenMansour).
+//!
+//! The input is xored with one key, put through a fixed public permutation,
+//! and xored with another key. The public permutation is made of rounds that
+//! multiply by an odd constant, xorshift, and add a round constant, all modulo
+//! `2^bits`. Every step is invertible on its own, so unlike a Feistel network
+//! the domain doesn't have to be split in half.
+
+use crate::{PerfectRng, UnsignedInt};
+
+/// The multiplier for the public permutation, which is the 128-bit golden
+/// ratio rounded to be odd so it's invertible modulo any power of two.
+const MULTIPLIER: u128 = 0x9e3779b97f4a7c15f39cc0605cedc835;
+
+/// The inverse of `n` modulo 2^128, with Newton's method. Every iteration
+/// doubles the number of correct bits, and an odd `n` is already its own
+/// inverse modulo 8.
+const fn inverse(n: u128) -> u128 {
+    let mut inverse = n;
+    let mut i = 0;
+    while i < 6 {
+        inverse = inverse.wrapping_mul(2u128.wrapping_sub(n.wrapping_mul(inverse)));
+        i += 1;
+    }
+    inverse
+}
+
+const MULTIPLIER_INVERSE: u128 = inverse(MULTIPLIER);
+
+impl<T: UnsignedInt> PerfectRng<T> {
+    /// The mask for the domain the permutation is over, `0..2^bits`.
+    #[inline]
+    fn even_mansour_mask(&self) -> u128 {
+        // a range of 1 has no bits, and shifting by 128 would overflow
+        u128::MAX
+            .checked_shr(128 - self.max.count_bits())
+            .unwrap_or(0)
+    }
+
+    #[inline]
+    fn even_mansour_shift(&self) -> u32 {
+        // at least half of the bits so one xorshift undoes itself
+        self.max.count_bits().div_ceil(2).max(1)
+    }
+
+    #[inline]
+    fn even_mansour_constant(j: usize) -> u128 {
+        MULTIPLIER.wrapping_mul(j as u128 + 1)
+    }
+
+    #[inline]
+    pub(crate) fn even_mansour_encrypt(&self, m: T) -> T {
+        let mask = self.even_mansour_mask();
+        let shift = self.even_mansour_shift();
+        let [k1, k2] = self.round_keys[0].map(|k| k.as_u128() & mask);
+
+        let mut x = m.as_u128() ^ k1;
+        for j in 0..self.rounds {
+            x = x.wrapping_mul(MULTIPLIER) & mask;
+            x ^= x >> shift;
+            x = x.wrapping_add(Self::even_mansour_constant(j) ^ self.tweak.as_u128()) & mask;
+        }
+        T::from_u128(x ^ k2)
+    }
+
+    #[inline]
+    pub(crate) fn even_mansour_decrypt(&self, c: T) -> T {
+        let mask = self.even_mansour_mask();
+        let shift = self.even_mansour_shift();
+        let [k1, k2] = self.round_keys[0].map(|k| k.as_u128() & mask);
+
+        let mut x = c.as_u128() ^ k2;
+        for j in (0..self.rounds).rev() {
+            x = x.wrapping_sub(Self::even_mansour_constant(j) ^ self.tweak.as_u128()) & mask;
+            x ^= x >> shift;
+            x = x.wrapping_mul(MULTIPLIER_INVERSE) & mask;
+        }
+        T::from_u128(x ^ k1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{MULTIPLIER, MULTIPLIER_INVERSE};
+    use crate::{Backend, PerfectRng};
+
+    #[test]
+    fn inverse() {
+        assert_eq!(MULTIPLIER.wrapping_mul(MULTIPLIER_INVERSE), 1);
+    }
+
+    #[test]
+    fn is_a_permutation() {
+        for range in [1u64, 2, 3, 10, 1000, 1024] {
+            let randomizer =
+                PerfectRng::with_key(range, [1, 2], 4).with_backend(Backend::EvenMansour);
+            let mut seen = vec![false; range as usize];
+            for i in 0..range {
+                let x = randomizer.shuffle(i);
+                assert_eq!(randomizer.unshuffle(x), i);
+                assert!(!seen[x as usize]);
+                seen[x as usize] = true;
+            }
+        }
+
+        let randomizer = PerfectRng::<u128>::with_key_inclusive(0..=u128::MAX, [1, 2], 4)
+            .with_tweak(b"tweak")
+            .with_backend(Backend::EvenMansour);
+        for i in [0, 1, u128::MAX / 2, u128::MAX] {
+            assert_eq!(randomizer.unshuffle(randomizer.shuffle(i)), i);
+        }
+    }
+}
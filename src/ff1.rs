@@ -0,0 +1,243 @@
+//! FF1 format-preserving encryption from
+//! [NIST SP 800-38G](https://doi.org/10.6028/NIST.SP.800-38G), for
+//! [`Backend::Ff1`](crate::Backend::Ff1).
+//!
+//! Numeral strings are represented by the number they encode, so
+//! `NUM_radix(X)` is just `x` and every numeral string of length `n` is a
+//! number below `radix^n`.
+
+use crate::{aes::Aes128, PerfectRng, UnsignedInt};
+
+/// The number of Feistel rounds FF1 always does.
+const ROUNDS: u8 = 10;
+
+/// Encrypt `x`, a numeral string of length `n` in the given radix.
+///
+/// `radix^ceil(n / 2)` must be less than 2^120, so the arithmetic fits in a
+/// `u128`.
+pub(crate) fn encrypt(aes: &Aes128, tweak: &[u8], radix: u32, n: u32, x: u128) -> u128 {
+    let ff1 = Ff1::new(aes, tweak, radix, n);
+    let (mut a, mut b) = (x / ff1.v_modulus, x % ff1.v_modulus);
+    for i in 0..ROUNDS {
+        let modulus = ff1.modulus(i);
+        let y = ff1.round(i, b, modulus);
+        let c = (a % modulus + y) % modulus;
+        a = b;
+        b = c;
+    }
+    a * ff1.v_modulus + b
+}
+
+/// Reverse [`encrypt`].
+pub(crate) fn decrypt(aes: &Aes128, tweak: &[u8], radix: u32, n: u32, x: u128) -> u128 {
+    let ff1 = Ff1::new(aes, tweak, radix, n);
+    let (mut a, mut b) = (x / ff1.v_modulus, x % ff1.v_modulus);
+    for i in (0..ROUNDS).rev() {
+        let modulus = ff1.modulus(i);
+        let y = ff1.round(i, a, modulus);
+        let c = (b % modulus + modulus - y) % modulus;
+        b = a;
+        a = c;
+    }
+    a * ff1.v_modulus + b
+}
+
+struct Ff1<'a> {
+    aes: &'a Aes128,
+    tweak: &'a [u8],
+    /// `radix^u`, where `u` is the length of the left half.
+    u_modulus: u128,
+    /// `radix^v`, where `v` is the length of the right half.
+    v_modulus: u128,
+    /// The number of bytes that the right half is encoded as.
+    b: usize,
+    /// The number of bytes of pseudorandom output that's used in each round.
+    d: usize,
+    /// The output of the PRF on the first block, `P`, which is the same for
+    /// every round.
+    p_mac: [u8; 16],
+}
+
+impl<'a> Ff1<'a> {
+    fn new(aes: &'a Aes128, tweak: &'a [u8], radix: u32, n: u32) -> Self {
+        assert!(radix >= 2 && n >= 2);
+        let u = n / 2;
+        let v = n - u;
+        let u_modulus = (radix as u128).pow(u);
+        let v_modulus = (radix as u128)
+            .checked_pow(v)
+            .filter(|&m| m < 1 << 120)
+            .expect("the domain is too big for FF1");
+
+        // ceil(v * log2(radix)) is the number of bits in radix^v - 1
+        let b = (128 - (v_modulus - 1).leading_zeros()).div_ceil(8) as usize;
+        let d = 4 * b.div_ceil(4) + 4;
+
+        let mut p = [0; 16];
+        p[..3].copy_from_slice(&[1, 2, 1]);
+        p[3..6].copy_from_slice(&radix.to_be_bytes()[1..]);
+        p[6] = ROUNDS;
+        p[7] = (u % 256) as u8;
+        p[8..12].copy_from_slice(&n.to_be_bytes());
+        p[12..].copy_from_slice(&(tweak.len() as u32).to_be_bytes());
+
+        Ff1 {
+            aes,
+            tweak,
+            u_modulus,
+            v_modulus,
+            b,
+            d,
+            p_mac: aes.encrypt(p),
+        }
+    }
+
+    /// `radix^m`, where `m` is the length of the half that's replaced in round
+    /// `i`.
+    fn modulus(&self, i: u8) -> u128 {
+        if i.is_multiple_of(2) {
+            self.u_modulus
+        } else {
+            self.v_modulus
+        }
+    }
+
+    /// Compute `y mod radix^m` for round `i`, where `half` is the numeral
+    /// string that's being fed into the round function.
+    fn round(&self, i: u8, half: u128, modulus: u128) -> u128 {
+        // Q = T || [0]^((-t-b-1) mod 16) || [i] || [NUM(B)]^b
+        let padding = (16 - (self.tweak.len() + self.b + 1) % 16) % 16;
+        let mut q = Vec::with_capacity(self.tweak.len() + padding + 1 + self.b);
+        q.extend_from_slice(self.tweak);
+        q.resize(q.len() + padding, 0);
+        q.push(i);
+        q.extend_from_slice(&half.to_be_bytes()[16 - self.b..]);
+
+        // R = PRF(P || Q), which is CBC-MAC continuing from P
+        let mut r = self.p_mac;
+        for block in q.chunks_exact(16) {
+            for (r, q) in r.iter_mut().zip(block) {
+                *r ^= q;
+            }
+            r = self.aes.encrypt(r);
+        }
+
+        // S = R || CIPH(R ^ [1]^16) || CIPH(R ^ [2]^16) ..., and y = NUM(S)
+        // mod radix^m is computed a byte at a time so it never overflows
+        let mut y = 0;
+        for j in 0..self.d.div_ceil(16) {
+            let block = if j == 0 {
+                r
+            } else {
+                self.aes
+                    .encrypt((u128::from_be_bytes(r) ^ j as u128).to_be_bytes())
+            };
+            let len = (self.d - j * 16).min(16);
+            for &byte in &block[..len] {
+                y = ((y << 8) | byte as u128) % modulus;
+            }
+        }
+        y
+    }
+}
+
+impl<T: UnsignedInt> PerfectRng<T> {
+    /// The AES key for the FPE backends. For `PerfectRng<u64>` these are the
+    /// same bytes that were passed to [`PerfectRng::with_key_bytes`].
+    pub(crate) fn aes_key(&self) -> [u8; 16] {
+        let mut key = [0; 16];
+        for (bytes, k) in key.chunks_exact_mut(8).zip(self.key) {
+            let k = k.as_u128();
+            bytes.copy_from_slice(&((k ^ (k >> 64)) as u64).to_le_bytes());
+        }
+        key
+    }
+
+    /// The FPE tweak, which is empty if no tweak was set.
+    pub(crate) fn fpe_tweak(&self) -> Vec<u8> {
+        if self.tweak == T::ZERO {
+            return Vec::new();
+        }
+        self.tweak.as_u128().to_le_bytes()[..T::BITS as usize / 8].to_vec()
+    }
+
+    /// The number of bits that the FPE backends encrypt, which covers the
+    /// whole domain. FF1 needs at least two numerals.
+    pub(crate) fn fpe_bits(&self) -> u32 {
+        self.max.count_bits().max(2)
+    }
+
+    pub(crate) fn ff1_encrypt(&self, m: T) -> T {
+        let aes = Aes128::new(self.aes_key());
+        let c = encrypt(&aes, &self.fpe_tweak(), 2, self.fpe_bits(), m.as_u128());
+        T::from_u128(c)
+    }
+
+    pub(crate) fn ff1_decrypt(&self, c: T) -> T {
+        let aes = Aes128::new(self.aes_key());
+        let m = decrypt(&aes, &self.fpe_tweak(), 2, self.fpe_bits(), c.as_u128());
+        T::from_u128(m)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{decrypt, encrypt};
+    use crate::{aes::Aes128, Backend, PerfectRng};
+
+    fn num(digits: &str, radix: u32) -> u128 {
+        u128::from_str_radix(digits, radix).unwrap()
+    }
+
+    fn hex(s: &str) -> Vec<u8> {
+        (0..s.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&s[i..i + 2], 16).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn nist_vectors() {
+        // the AES-128 samples from NIST's FF1 examples
+        let aes = Aes128::new(0x2b7e151628aed2a6abf7158809cf4f3c_u128.to_be_bytes());
+        let samples = [
+            ("", 10, "0123456789", "2433477484"),
+            ("39383736353433323130", 10, "0123456789", "6124200773"),
+            (
+                "3737373770717273373737",
+                36,
+                "0123456789abcdefghi",
+                "a9tv40mll9kdu509eum",
+            ),
+        ];
+        for (tweak, radix, plaintext, ciphertext) in samples {
+            let tweak = hex(tweak);
+            let n = plaintext.len() as u32;
+            let x = num(plaintext, radix);
+            let c = encrypt(&aes, &tweak, radix, n, x);
+            assert_eq!(c, num(ciphertext, radix), "{plaintext}");
+            assert_eq!(decrypt(&aes, &tweak, radix, n, c), x);
+        }
+    }
+
+    #[test]
+    fn is_a_permutation() {
+        for range in [1u64, 2, 3, 1000, 4096] {
+            let randomizer = PerfectRng::with_key(range, [1, 2], 4).with_backend(Backend::Ff1);
+            let mut seen = vec![false; range as usize];
+            for i in 0..range {
+                let x = randomizer.shuffle(i);
+                assert_eq!(randomizer.unshuffle(x), i);
+                assert!(!seen[x as usize]);
+                seen[x as usize] = true;
+            }
+        }
+
+        let randomizer = PerfectRng::<u128>::from_range_inclusive(0..=u128::MAX)
+            .with_tweak(b"tweak")
+            .with_backend(Backend::Ff1);
+        for i in [0, 1, u128::MAX / 2, u128::MAX] {
+            assert_eq!(randomizer.unshuffle(randomizer.shuffle(i)), i);
+        }
+    }
+}
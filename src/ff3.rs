@@ -0,0 +1,242 @@
+//! FF3-1 format-preserving encryption from
+//! [NIST SP 800-38G Rev. 1](https://doi.org/10.6028/NIST.SP.800-38Gr1-draft),
+//! for [`Backend::Ff3_1`](crate::Backend::Ff3_1).
+//!
+//! Like in the FF1 module, numeral strings are represented by the number they
+//! encode, with the first numeral being the most significant.
+
+use crate::{aes::Aes128, PerfectRng, UnsignedInt};
+
+/// The number of Feistel rounds FF3-1 always does.
+const ROUNDS: u8 = 8;
+
+/// Encrypt `x`, a numeral string of length `n` in the given radix, with the
+/// tweak already split into its left and right halves.
+///
+/// `radix^ceil(n / 2)` must be less than 2^96.
+pub(crate) fn encrypt(aes: &Aes128, tweak: [[u8; 4]; 2], radix: u32, n: u32, x: u128) -> u128 {
+    let ff3 = Ff3::new(aes, tweak, radix, n);
+    let (mut a, mut b) = (x / ff3.v_modulus, x % ff3.v_modulus);
+    for i in 0..ROUNDS {
+        let (m, modulus) = ff3.half(i);
+        let y = ff3.round(i, b);
+        let c = (rev(a, radix, m) + y) % modulus;
+        a = b;
+        b = rev(c, radix, m);
+    }
+    a * ff3.v_modulus + b
+}
+
+/// Reverse [`encrypt`].
+pub(crate) fn decrypt(aes: &Aes128, tweak: [[u8; 4]; 2], radix: u32, n: u32, x: u128) -> u128 {
+    let ff3 = Ff3::new(aes, tweak, radix, n);
+    let (mut a, mut b) = (x / ff3.v_modulus, x % ff3.v_modulus);
+    for i in (0..ROUNDS).rev() {
+        let (m, modulus) = ff3.half(i);
+        let y = ff3.round(i, a);
+        let c = (rev(b, radix, m) + modulus - y) % modulus;
+        b = a;
+        a = rev(c, radix, m);
+    }
+    a * ff3.v_modulus + b
+}
+
+/// Split a 56-bit FF3-1 tweak into the 32-bit halves used by the rounds.
+pub(crate) fn split_tweak(tweak: [u8; 7]) -> [[u8; 4]; 2] {
+    [
+        [tweak[0], tweak[1], tweak[2], tweak[3] & 0xf0],
+        [tweak[4], tweak[5], tweak[6], tweak[3] << 4],
+    ]
+}
+
+/// Reverse the order of the `len` numerals of `x`.
+fn rev(mut x: u128, radix: u32, len: u32) -> u128 {
+    let radix = radix as u128;
+    let mut reversed = 0;
+    for _ in 0..len {
+        reversed = reversed * radix + x % radix;
+        x /= radix;
+    }
+    reversed
+}
+
+struct Ff3<'a> {
+    aes: &'a Aes128,
+    tweak: [[u8; 4]; 2],
+    radix: u32,
+    u: u32,
+    v: u32,
+    /// `radix^u`, where `u` is the length of the left half.
+    u_modulus: u128,
+    /// `radix^v`, where `v` is the length of the right half.
+    v_modulus: u128,
+}
+
+impl<'a> Ff3<'a> {
+    fn new(aes: &'a Aes128, tweak: [[u8; 4]; 2], radix: u32, n: u32) -> Self {
+        assert!(radix >= 2 && n >= 2);
+        let u = n.div_ceil(2);
+        let v = n - u;
+        let u_modulus = (radix as u128)
+            .checked_pow(u)
+            .filter(|&m| m <= 1 << 96)
+            .expect("the domain is too big for FF3-1");
+        Ff3 {
+            aes,
+            tweak,
+            radix,
+            u,
+            v,
+            u_modulus,
+            v_modulus: (radix as u128).pow(v),
+        }
+    }
+
+    /// The length of the half that's replaced in round `i`, and `radix` to
+    /// the power of that.
+    fn half(&self, i: u8) -> (u32, u128) {
+        if i.is_multiple_of(2) {
+            (self.u, self.u_modulus)
+        } else {
+            (self.v, self.v_modulus)
+        }
+    }
+
+    /// Compute `y mod radix^m` for round `i`, where `half` is the numeral
+    /// string that's being fed into the round function.
+    fn round(&self, i: u8, half: u128) -> u128 {
+        let (m, modulus) = self.half(i);
+        // the right half of the tweak is used in even rounds
+        let mut w = self.tweak[if i.is_multiple_of(2) { 1 } else { 0 }];
+        w[3] ^= i;
+
+        // P = W ^ [i]^4 || [NUM(REV(B))]^12, where B has the other length
+        let other = if m == self.u { self.v } else { self.u };
+        let mut p = [0; 16];
+        p[..4].copy_from_slice(&w);
+        p[4..].copy_from_slice(&rev(half, self.radix, other).to_be_bytes()[4..]);
+
+        // S = REVB(CIPH_REVB(K)(REVB(P))), and the key is already reversed
+        p.reverse();
+        let mut s = self.aes.encrypt(p);
+        s.reverse();
+        u128::from_be_bytes(s) % modulus
+    }
+}
+
+impl<T: UnsignedInt> PerfectRng<T> {
+    /// The AES key for FF3-1, which uses the key bytes in reverse.
+    fn ff3_aes(&self) -> Aes128 {
+        let mut key = self.aes_key();
+        key.reverse();
+        Aes128::new(key)
+    }
+
+    /// The first 56 bits of the tweak.
+    fn ff3_tweak(&self) -> [[u8; 4]; 2] {
+        let mut tweak = [0; 7];
+        for (t, byte) in tweak.iter_mut().zip(self.fpe_tweak()) {
+            *t = byte;
+        }
+        split_tweak(tweak)
+    }
+
+    pub(crate) fn ff3_encrypt(&self, m: T) -> T {
+        let c = encrypt(
+            &self.ff3_aes(),
+            self.ff3_tweak(),
+            2,
+            self.fpe_bits(),
+            m.as_u128(),
+        );
+        T::from_u128(c)
+    }
+
+    pub(crate) fn ff3_decrypt(&self, c: T) -> T {
+        let m = decrypt(
+            &self.ff3_aes(),
+            self.ff3_tweak(),
+            2,
+            self.fpe_bits(),
+            c.as_u128(),
+        );
+        T::from_u128(m)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{decrypt, encrypt, split_tweak};
+    use crate::{aes::Aes128, Backend, PerfectRng};
+
+    fn reversed_key(key: u128) -> Aes128 {
+        let mut key = key.to_be_bytes();
+        key.reverse();
+        Aes128::new(key)
+    }
+
+    #[test]
+    fn nist_ff3_vectors() {
+        // NIST's samples for the original FF3, which is the same as FF3-1
+        // except that the tweak is 64 bits and split down the middle
+        let aes = reversed_key(0xef4359d8d580aa4f7f036d6f04fc6a94);
+        let samples: [(u64, &str, &str); 3] = [
+            (
+                0xd8e7920afa330a73,
+                "890121234567890000",
+                "750918814058654607",
+            ),
+            (
+                0x9a768a92f60e12d8,
+                "890121234567890000",
+                "018989839189395384",
+            ),
+            (
+                0xd8e7920afa330a73,
+                "89012123456789000000789000000",
+                "48598367162252569629397416226",
+            ),
+        ];
+        for (tweak, plaintext, ciphertext) in samples {
+            let tweak = tweak.to_be_bytes();
+            let tweak = [
+                tweak[..4].try_into().unwrap(),
+                tweak[4..].try_into().unwrap(),
+            ];
+            let n = plaintext.len() as u32;
+            let x = plaintext.parse().unwrap();
+            let c = encrypt(&aes, tweak, 10, n, x);
+            assert_eq!(c, ciphertext.parse().unwrap(), "{plaintext}");
+            assert_eq!(decrypt(&aes, tweak, 10, n, c), x);
+        }
+    }
+
+    #[test]
+    fn split_tweak_56() {
+        assert_eq!(
+            split_tweak([0xd8, 0xe7, 0x92, 0x0a, 0xfa, 0x33, 0x0a]),
+            [[0xd8, 0xe7, 0x92, 0x00], [0xfa, 0x33, 0x0a, 0xa0]]
+        );
+    }
+
+    #[test]
+    fn is_a_permutation() {
+        for range in [1u64, 2, 3, 1000, 4096] {
+            let randomizer = PerfectRng::with_key(range, [1, 2], 4).with_backend(Backend::Ff3_1);
+            let mut seen = vec![false; range as usize];
+            for i in 0..range {
+                let x = randomizer.shuffle(i);
+                assert_eq!(randomizer.unshuffle(x), i);
+                assert!(!seen[x as usize]);
+                seen[x as usize] = true;
+            }
+        }
+
+        let randomizer = PerfectRng::<u128>::from_range_inclusive(0..=u128::MAX)
+            .with_tweak(b"tweak")
+            .with_backend(Backend::Ff3_1);
+        for i in [0, 1, u128::MAX / 2, u128::MAX] {
+            assert_eq!(randomizer.unshuffle(randomizer.shuffle(i)), i);
+        }
+    }
+}
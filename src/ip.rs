@@ -0,0 +1,277 @@
+use std::{
+    iter::FusedIterator,
+    net::{Ipv4Addr, Ipv6Addr},
+    ops::RangeInclusive,
+};
+
+use crate::{PerfectRng, ShuffleCursor};
+
+/// An iterator over IPv4 addresses in shuffled order, which is the most
+/// common thing to use a [`PerfectRng`] for. See [`ShuffledIpv6`] for IPv6.
+///
+/// This is created by [`PerfectRng::shuffled_ipv4`],
+/// [`PerfectRng::shuffled_ipv4_range`], [`PerfectRng::shuffled_ipv4_net`],
+/// and [`PerfectRng::shuffled_ipv4_hosts`].
+#[derive(Debug)]
+pub struct ShuffledIpv4 {
+    cursor: ShuffleCursor<u32>,
+}
+
+impl PerfectRng<u32> {
+    /// Iterate over every IPv4 address in shuffled order, with a random key.
+    ///
+    /// ```
+    /// # use perfect_rand::PerfectRng;
+    /// for ip in PerfectRng::shuffled_ipv4().take(10) {
+    ///     println!("{ip}");
+    /// }
+    /// ```
+    #[must_use]
+    pub fn shuffled_ipv4() -> ShuffledIpv4 {
+        Self::shuffled_ipv4_range(Ipv4Addr::UNSPECIFIED..=Ipv4Addr::BROADCAST)
+    }
+
+    /// Iterate over the IPv4 addresses in a range in shuffled order, with a
+    /// random key.
+    ///
+    /// ```
+    /// # use std::net::Ipv4Addr;
+    /// # use perfect_rand::PerfectRng;
+    /// let start = Ipv4Addr::new(10, 0, 0, 0);
+    /// let end = Ipv4Addr::new(10, 0, 0, 255);
+    /// let mut ips = PerfectRng::shuffled_ipv4_range(start..=end).collect::<Vec<_>>();
+    /// assert_eq!(ips.len(), 256);
+    /// ips.sort();
+    /// assert_eq!(ips[0], start);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if the range is empty.
+    #[must_use]
+    pub fn shuffled_ipv4_range(range: RangeInclusive<Ipv4Addr>) -> ShuffledIpv4 {
+        let (start, end) = range.into_inner();
+        ShuffledIpv4::new(Self::from_range_inclusive(
+            u32::from(start)..=u32::from(end),
+        ))
+    }
+}
+
+impl PerfectRng<u32> {
+    /// Iterate over the addresses in an IPv4 prefix in shuffled order, with a
+    /// random key. The host bits of `network` are ignored.
+    ///
+    /// ```
+    /// # use std::net::Ipv4Addr;
+    /// # use perfect_rand::PerfectRng;
+    /// let ips = PerfectRng::shuffled_ipv4_net(Ipv4Addr::new(192, 168, 1, 0), 24);
+    /// assert_eq!(ips.count(), 256);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if `prefix_len` is more than 32.
+    #[must_use]
+    pub fn shuffled_ipv4_net(network: Ipv4Addr, prefix_len: u8) -> ShuffledIpv4 {
+        let (start, end) = ipv4_net_bounds(network, prefix_len);
+        ShuffledIpv4::new(Self::from_range_inclusive(start..=end))
+    }
+
+    /// Like [`PerfectRng::shuffled_ipv4_net`], but skips the network and
+    /// broadcast addresses. Prefixes of length 31 and 32 have neither, so all
+    /// of their addresses are included, as in RFC 3021.
+    ///
+    /// ```
+    /// # use std::net::Ipv4Addr;
+    /// # use perfect_rand::PerfectRng;
+    /// let network = Ipv4Addr::new(192, 168, 1, 0);
+    /// let mut ips = PerfectRng::shuffled_ipv4_hosts(network, 24).collect::<Vec<_>>();
+    /// ips.sort();
+    /// assert_eq!(ips.len(), 254);
+    /// assert_eq!(ips[0], Ipv4Addr::new(192, 168, 1, 1));
+    /// assert_eq!(ips[253], Ipv4Addr::new(192, 168, 1, 254));
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if `prefix_len` is more than 32.
+    #[must_use]
+    pub fn shuffled_ipv4_hosts(network: Ipv4Addr, prefix_len: u8) -> ShuffledIpv4 {
+        let (start, end) = ipv4_net_bounds(network, prefix_len);
+        if prefix_len >= 31 {
+            return ShuffledIpv4::new(Self::from_range_inclusive(start..=end));
+        }
+        ShuffledIpv4::new(Self::from_range_inclusive(start + 1..=end - 1))
+    }
+}
+
+/// The first and last address in an IPv4 prefix.
+fn ipv4_net_bounds(network: Ipv4Addr, prefix_len: u8) -> (u32, u32) {
+    assert!(prefix_len <= 32, "the prefix length must be at most 32");
+    let host_mask = u32::MAX.checked_shr(u32::from(prefix_len)).unwrap_or(0);
+    let start = u32::from(network) & !host_mask;
+    (start, start | host_mask)
+}
+
+impl ShuffledIpv4 {
+    /// Iterate over the addresses in the range of a [`PerfectRng`], where
+    /// every `u32` is an address.
+    #[must_use]
+    pub fn new(rng: PerfectRng<u32>) -> Self {
+        ShuffledIpv4 {
+            cursor: ShuffleCursor::new(rng),
+        }
+    }
+
+    /// The [`PerfectRng`] that the addresses come from.
+    #[must_use]
+    pub fn rng(&self) -> &PerfectRng<u32> {
+        self.cursor.rng()
+    }
+}
+
+impl Iterator for ShuffledIpv4 {
+    type Item = Ipv4Addr;
+
+    #[inline]
+    fn next(&mut self) -> Option<Ipv4Addr> {
+        self.cursor.next().map(Ipv4Addr::from)
+    }
+}
+
+impl FusedIterator for ShuffledIpv4 {}
+
+/// An iterator over the addresses in an IPv6 prefix in shuffled order.
+///
+/// This is created by [`PerfectRng::shuffled_ipv6`].
+#[derive(Debug)]
+pub struct ShuffledIpv6 {
+    cursor: ShuffleCursor<u128>,
+}
+
+impl PerfectRng<u128> {
+    /// Iterate over every address in an IPv6 prefix in shuffled order, with a
+    /// random key. Only the host bits (the last `128 - prefix_len` bits) are
+    /// shuffled, and the host bits of `network` are ignored.
+    ///
+    /// Most prefixes have far too many addresses to iterate over all of
+    /// them, so this is meant for taking samples or for long prefixes.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `prefix_len` is more than 128.
+    ///
+    /// ```
+    /// # use std::net::Ipv6Addr;
+    /// # use perfect_rand::PerfectRng;
+    /// let network = "2001:db8::".parse::<Ipv6Addr>().unwrap();
+    /// for ip in PerfectRng::shuffled_ipv6(network, 64).take(10) {
+    ///     assert_eq!(ip.segments()[..4], network.segments()[..4]);
+    /// }
+    /// ```
+    #[must_use]
+    pub fn shuffled_ipv6(network: Ipv6Addr, prefix_len: u8) -> ShuffledIpv6 {
+        assert!(prefix_len <= 128, "the prefix length must be at most 128");
+        let host_mask = u128::MAX.checked_shr(u32::from(prefix_len)).unwrap_or(0);
+        let start = u128::from(network) & !host_mask;
+        ShuffledIpv6::new(Self::from_range_inclusive(start..=start | host_mask))
+    }
+}
+
+impl ShuffledIpv6 {
+    /// Iterate over the addresses in the range of a [`PerfectRng`], where
+    /// every `u128` is an address.
+    #[must_use]
+    pub fn new(rng: PerfectRng<u128>) -> Self {
+        ShuffledIpv6 {
+            cursor: ShuffleCursor::new(rng),
+        }
+    }
+
+    /// The [`PerfectRng`] that the addresses come from.
+    #[must_use]
+    pub fn rng(&self) -> &PerfectRng<u128> {
+        self.cursor.rng()
+    }
+}
+
+impl Iterator for ShuffledIpv6 {
+    type Item = Ipv6Addr;
+
+    #[inline]
+    fn next(&mut self) -> Option<Ipv6Addr> {
+        self.cursor.next().map(Ipv6Addr::from)
+    }
+}
+
+impl FusedIterator for ShuffledIpv6 {}
+
+#[cfg(test)]
+mod tests {
+    use std::net::{Ipv4Addr, Ipv6Addr};
+
+    use crate::PerfectRng;
+
+    #[test]
+    fn range() {
+        let start = Ipv4Addr::new(192, 168, 0, 0);
+        let end = Ipv4Addr::new(192, 168, 3, 255);
+        let mut ips = PerfectRng::shuffled_ipv4_range(start..=end).collect::<Vec<_>>();
+        ips.sort();
+        assert!(ips
+            .into_iter()
+            .eq((u32::from(start)..=u32::from(end)).map(Ipv4Addr::from)));
+    }
+
+    #[test]
+    fn full() {
+        let mut ips = PerfectRng::shuffled_ipv4();
+        let ip = ips.next().unwrap();
+        assert_eq!(ips.rng().unshuffle(u32::from(ip)), 0);
+    }
+
+    #[test]
+    fn net() {
+        let network = Ipv4Addr::new(10, 1, 2, 3);
+        let mut ips = PerfectRng::shuffled_ipv4_net(network, 28).collect::<Vec<_>>();
+        ips.sort();
+        let start = u32::from(Ipv4Addr::new(10, 1, 2, 0));
+        assert!(ips.into_iter().eq((start..start + 16).map(Ipv4Addr::from)));
+
+        let mut hosts = PerfectRng::shuffled_ipv4_hosts(network, 28).collect::<Vec<_>>();
+        hosts.sort();
+        assert!(hosts
+            .into_iter()
+            .eq((start + 1..start + 15).map(Ipv4Addr::from)));
+
+        assert_eq!(PerfectRng::shuffled_ipv4_hosts(network, 31).count(), 2);
+        assert!(PerfectRng::shuffled_ipv4_hosts(network, 32).eq([network]));
+        let mut all = PerfectRng::shuffled_ipv4_hosts(network, 0);
+        let ip = all.next().unwrap();
+        assert!(ip != Ipv4Addr::UNSPECIFIED && ip != Ipv4Addr::BROADCAST);
+        assert_eq!(all.rng().unshuffle(u32::from(ip)), 1);
+    }
+
+    #[test]
+    fn ipv6() {
+        let network = "2001:db8::1234".parse::<Ipv6Addr>().unwrap();
+        let mut ips = PerfectRng::shuffled_ipv6(network, 120).collect::<Vec<_>>();
+        assert_eq!(ips.len(), 256);
+        ips.sort();
+        let start = u128::from("2001:db8::1200".parse::<Ipv6Addr>().unwrap());
+        assert!(ips.into_iter().eq((start..start + 256).map(Ipv6Addr::from)));
+
+        let host = PerfectRng::shuffled_ipv6(network, 128);
+        assert!(host.eq([network]));
+
+        let mut all = PerfectRng::shuffled_ipv6(network, 0);
+        let ip = all.next().unwrap();
+        assert_eq!(all.rng().unshuffle(u128::from(ip)), 0);
+    }
+
+    #[test]
+    fn single() {
+        let ip = Ipv4Addr::BROADCAST;
+        assert!(PerfectRng::shuffled_ipv4_range(ip..=ip).eq([ip]));
+    }
+}
@@ -0,0 +1,330 @@
+use std::iter::FusedIterator;
+
+use crate::{PerfectRng, UnsignedInt};
+
+/// An iterator over every value in the range of a [`PerfectRng`], in
+/// shuffled order.
+///
+/// Positions in the iterator start at 0, even if the `PerfectRng` has an
+/// offset.
+///
+/// It's only an [`ExactSizeIterator`] for `u16`, and `u32` on 64-bit
+/// targets, since wider ranges can have more values than fit in a `usize`.
+/// Use [`Iterator::size_hint`] for the others.
+///
+/// This is created by [`PerfectRng::iter`].
+#[derive(Debug, Clone)]
+pub struct ShuffledIter<'a, T: UnsignedInt = u64> {
+    rng: &'a PerfectRng<T>,
+    /// The next position that'll be shuffled from the front.
+    front: T,
+    /// The next position that'll be shuffled from the back. This is
+    /// inclusive so the iterator can cover the full domain of `T`.
+    back: T,
+    /// Whether `front` has passed `back`.
+    empty: bool,
+}
+
+impl<'a, T: UnsignedInt> ShuffledIter<'a, T> {
+    pub(crate) fn new(rng: &'a PerfectRng<T>) -> Self {
+        ShuffledIter {
+            rng,
+            front: T::ZERO,
+            back: rng.max,
+            empty: false,
+        }
+    }
+}
+
+impl<T: UnsignedInt> ShuffledIter<'_, T> {
+    /// Jump to an index in the permutation, so the next call to
+    /// [`Iterator::next`] returns `rng.shuffle(index)` (plus the offset, if
+    /// there is one). This runs in constant time.
+    ///
+    /// Seeking past the back of the iterator leaves it empty.
+    ///
+    /// ```
+    /// # use perfect_rand::PerfectRng;
+    /// let randomizer = PerfectRng::new(100u64, 0, 4);
+    /// let mut iter = randomizer.iter();
+    /// iter.seek(50);
+    /// assert_eq!(iter.next(), Some(randomizer.shuffle(50)));
+    /// assert_eq!(iter.size_hint(), (49, Some(49)));
+    /// ```
+    pub fn seek(&mut self, index: T) {
+        self.front = index;
+        self.empty = index > self.back;
+    }
+
+    /// The index in the permutation that the next call to
+    /// [`Iterator::next`] will shuffle.
+    #[must_use]
+    pub fn position(&self) -> T {
+        self.front
+    }
+
+    /// The last index that hasn't been iterated over yet, or `None` if the
+    /// iterator is empty.
+    #[cfg(feature = "rayon")]
+    pub(crate) fn back(&self) -> Option<T> {
+        (!self.empty).then_some(self.back)
+    }
+
+    /// Stop the iterator after the given index.
+    #[cfg(feature = "rayon")]
+    pub(crate) fn truncate(&mut self, back: T) {
+        if back < self.front {
+            self.empty = true;
+        } else if back < self.back {
+            self.back = back;
+        }
+    }
+
+    /// The number of remaining items minus one, or `None` if there are none
+    /// left. The number of remaining items itself doesn't fit in a `u128` if
+    /// this is iterating over every `u128`.
+    #[inline]
+    fn remaining_minus_one(&self) -> Option<u128> {
+        (!self.empty).then(|| (self.back - self.front).as_u128())
+    }
+}
+
+impl<T: UnsignedInt> Iterator for ShuffledIter<'_, T> {
+    type Item = T;
+
+    #[inline]
+    fn next(&mut self) -> Option<T> {
+        if self.empty {
+            return None;
+        }
+        let shuffled = self.rng.shuffle_nth(self.front);
+        if self.front == self.back {
+            self.empty = true;
+        } else {
+            self.front = self.front + T::ONE;
+        }
+        Some(shuffled)
+    }
+
+    /// If the number of remaining items doesn't fit in a `usize` (which can
+    /// only happen with huge ranges), the lower bound is `usize::MAX` and
+    /// there's no upper bound.
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let Some(remaining_minus_one) = self.remaining_minus_one() else {
+            return (0, Some(0));
+        };
+        match usize::try_from(remaining_minus_one)
+            .ok()
+            .and_then(|n| n.checked_add(1))
+        {
+            Some(remaining) => (remaining, Some(remaining)),
+            None => (usize::MAX, None),
+        }
+    }
+
+    #[inline]
+    fn nth(&mut self, n: usize) -> Option<T> {
+        match self.remaining_minus_one() {
+            Some(remaining_minus_one) if n as u128 <= remaining_minus_one => {
+                self.front = self.front + T::from_usize(n);
+                self.next()
+            }
+            _ => {
+                self.empty = true;
+                None
+            }
+        }
+    }
+
+    #[inline]
+    fn last(mut self) -> Option<T> {
+        self.next_back()
+    }
+}
+
+impl<T: UnsignedInt> DoubleEndedIterator for ShuffledIter<'_, T> {
+    #[inline]
+    fn next_back(&mut self) -> Option<T> {
+        if self.empty {
+            return None;
+        }
+        let shuffled = self.rng.shuffle_nth(self.back);
+        if self.front == self.back {
+            self.empty = true;
+        } else {
+            self.back = self.back - T::ONE;
+        }
+        Some(shuffled)
+    }
+
+    #[inline]
+    fn nth_back(&mut self, n: usize) -> Option<T> {
+        match self.remaining_minus_one() {
+            Some(remaining_minus_one) if n as u128 <= remaining_minus_one => {
+                self.back = self.back - T::from_usize(n);
+                self.next_back()
+            }
+            _ => {
+                self.empty = true;
+                None
+            }
+        }
+    }
+}
+
+// the length has to fit in a usize, which it doesn't for the full range of
+// wider types
+impl ExactSizeIterator for ShuffledIter<'_, u16> {}
+#[cfg(target_pointer_width = "64")]
+impl ExactSizeIterator for ShuffledIter<'_, u32> {}
+
+impl<T: UnsignedInt> FusedIterator for ShuffledIter<'_, T> {}
+
+impl<T: UnsignedInt> PerfectRng<T> {
+    /// Iterate over every value in `0..range` in shuffled order.
+    ///
+    /// This is the same as calling [`PerfectRng::shuffle`] on every number in
+    /// the range, but it's done lazily so nothing is kept in memory.
+    ///
+    /// ```
+    /// # use perfect_rand::PerfectRng;
+    /// let randomizer = PerfectRng::from_range(100u64);
+    ///
+    /// let mut shuffled = randomizer.iter().collect::<Vec<_>>();
+    /// assert_eq!(shuffled.len(), 100);
+    /// shuffled.sort();
+    /// assert_eq!(shuffled, (0..100).collect::<Vec<_>>());
+    /// ```
+    #[must_use]
+    pub fn iter(&self) -> ShuffledIter<'_, T> {
+        ShuffledIter::new(self)
+    }
+
+    /// Iterate over the first `k` values of the permutation, which is a
+    /// sample of `k` values from the range without replacement.
+    ///
+    /// Unlike `rand::seq::index::sample`, this uses the same amount of memory
+    /// no matter how big the range or the sample is.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `k` is more than the range.
+    ///
+    /// ```
+    /// # use perfect_rand::PerfectRng;
+    /// let randomizer = PerfectRng::from_range(1_000_000_000u64);
+    /// let sample = randomizer.sample(10).collect::<Vec<_>>();
+    /// assert_eq!(sample.len(), 10);
+    /// assert!(sample.iter().all(|&i| i < 1_000_000_000));
+    /// ```
+    #[must_use]
+    pub fn sample(&self, k: T) -> ShuffledIter<'_, T> {
+        let mut iter = ShuffledIter::new(self);
+        if k == T::ZERO {
+            iter.empty = true;
+        } else {
+            assert!(
+                k - T::ONE <= self.max,
+                "the sample is bigger than the range"
+            );
+            iter.back = k - T::ONE;
+        }
+        iter
+    }
+}
+
+impl<'a, T: UnsignedInt> IntoIterator for &'a PerfectRng<T> {
+    type Item = T;
+    type IntoIter = ShuffledIter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{PerfectRng, PerfectRng128};
+
+    #[test]
+    fn iter_matches_shuffle() {
+        let randomizer = PerfectRng::new(1000u16, 0, 4);
+        let mut iter = randomizer.iter();
+        assert_eq!(iter.len(), 1000);
+
+        for i in 0..1000 {
+            assert_eq!(iter.next(), Some(randomizer.shuffle(i)));
+            assert_eq!(iter.len(), 999 - i as usize);
+        }
+        assert_eq!(iter.next(), None);
+    }
+
+    #[test]
+    fn double_ended() {
+        let randomizer = PerfectRng::new(100u16, 0, 4);
+        let forwards = randomizer.iter().collect::<Vec<_>>();
+        let mut backwards = randomizer.iter().rev().collect::<Vec<_>>();
+        backwards.reverse();
+        assert_eq!(forwards, backwards);
+
+        let mut iter = randomizer.iter();
+        assert_eq!(iter.next_back(), Some(forwards[99]));
+        assert_eq!(iter.nth(10), Some(forwards[10]));
+        assert_eq!(iter.nth_back(10), Some(forwards[88]));
+        assert_eq!(iter.len(), 77);
+        assert_eq!(iter.nth(1000), None);
+        assert_eq!(iter.next_back(), None);
+    }
+
+    #[test]
+    fn seek() {
+        let randomizer = PerfectRng128::new(u128::MAX, 0, 4);
+        let mut iter = randomizer.iter();
+        iter.seek(u128::MAX - 2);
+        assert_eq!(iter.position(), u128::MAX - 2);
+        assert_eq!(iter.next(), Some(randomizer.shuffle(u128::MAX - 2)));
+        assert_eq!(iter.next(), Some(randomizer.shuffle(u128::MAX - 1)));
+        assert_eq!(iter.next(), None);
+    }
+
+    #[test]
+    fn full_domain() {
+        let randomizer = PerfectRng::<u16>::from_range_inclusive(0..=u16::MAX);
+        assert_eq!(randomizer.iter().len(), 65536);
+        let mut seen = vec![false; 65536];
+        for shuffled in randomizer.iter() {
+            assert!(!seen[shuffled as usize]);
+            seen[shuffled as usize] = true;
+        }
+
+        let mut iter = randomizer.iter();
+        iter.seek(u16::MAX);
+        assert_eq!(iter.next(), Some(randomizer.shuffle(u16::MAX)));
+        assert_eq!(iter.next(), None);
+        assert_eq!(iter.len(), 0);
+    }
+
+    #[test]
+    fn sample() {
+        let randomizer = PerfectRng::new(1000u64, 0, 4);
+        assert!(randomizer.sample(10).eq(randomizer.iter().take(10)));
+        assert_eq!(randomizer.sample(0).next(), None);
+        assert!(randomizer.sample(1000).eq(randomizer.iter()));
+
+        let full = PerfectRng::<u16>::from_range_inclusive(0..=u16::MAX);
+        assert_eq!(full.sample(u16::MAX).len(), 65535);
+    }
+
+    #[test]
+    #[should_panic]
+    fn sample_too_big() {
+        let _ = PerfectRng::new(1000u64, 0, 4).sample(1001);
+    }
+
+    #[test]
+    fn huge_size_hint() {
+        let randomizer = PerfectRng128::new(u128::MAX, 0, 4);
+        assert_eq!(randomizer.iter().size_hint(), (usize::MAX, None));
+    }
+}
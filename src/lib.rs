@@ -12,6 +12,16 @@
 //! ```
 //! //! Print 10 random IPv4 addresses.
 //!
+//! # use perfect_rand::PerfectRng;
+//!
+//! for randomized_ip in PerfectRng::shuffled_ipv4().take(10) {
+//!     println!("{randomized_ip:?}");
+//! }
+//! ```
+//!
+//! Or with the [`PerfectRng`] directly, for any range of integers:
+//!
+//! ```
 //! # use std::net::Ipv4Addr;
 //! # use perfect_rand::PerfectRng;
 //!
@@ -21,70 +31,445 @@
 //!     println!("{randomized_ip:?}");
 //! }
 //! ```
+//!
+//! # Portability
+//!
+//! The permutation only depends on the range, key, and configuration, never
+//! on the platform. It's all integer math on fixed-width types, hardware
+//! acceleration like AES-NI gives the same results as the portable code,
+//! and [`ScanState`] is little-endian everywhere, so shuffled values that
+//! were stored on one machine can be unshuffled on any other, including
+//! 32-bit, big-endian, and wasm targets.
+//!
+//! # Features
+//!
+//! - `bench_support`: Adds the [`bench_support`] module, with the
+//!   deterministic fixtures that the `suites` benchmark uses.
+//! - `derive`: Adds a derive macro for [`Shuffleable`], for field-less enums
+//!   and structs of `Shuffleable` fields.
+//! - `expose_seed`: Adds [`PerfectRng::seed`] for getting the key back, like
+//!   for persisting the configuration, and shows the key in `Debug` output.
+//!   Otherwise, the key is always `<redacted>` there, since anyone who sees
+//!   it can unshuffle every value.
+//! - `fpe`: Adds the format-preserving encryption backends from NIST
+//!   SP 800-38G: [`Backend::Ff1`] and [`Backend::Ff3_1`].
+//! - `quality`: Adds the [`quality`] module, with statistical tests for
+//!   comparing how random different configurations look.
+//! - `rayon`: Adds [`PerfectRng::par_iter`] for iterating in parallel.
+//! - `serde`: Implements `Serialize` and `Deserialize` for [`PerfectRng`], so
+//!   the same permutation can be restored later.
+//! - `stats`: Adds [`PerfectRng::stats`] for counting how much cycle
+//!   walking is done.
+//! - `test_vectors`: Adds the [`test_vectors`] module, with known-answer
+//!   vectors for checking that the permutation hasn't changed.
+//! - `zeroize`: Wipes the key from memory when a [`PerfectRng`] or
+//!   [`SecretKey`] is dropped.
+
+use std::{
+    fmt,
+    hash::{Hash, Hasher},
+    ops::{Range, RangeInclusive},
+};
 
-#[derive(Default, Debug)]
-pub struct PerfectRng {
-    range: u64,
-    seed: u64,
+use rand::RngCore;
+
+mod aes;
+mod algorithm;
+mod alphabet;
+mod backend;
+mod batch;
+#[cfg(feature = "bench_support")]
+pub mod bench_support;
+mod blackrock2;
+mod bounded;
+mod builder;
+mod const_fn;
+mod const_rounds;
+mod constant_time;
+mod cursor;
+mod digits;
+mod dns;
+mod domain;
+mod epoch;
+mod error;
+mod even_mansour;
+#[cfg(feature = "fpe")]
+mod ff1;
+#[cfg(feature = "fpe")]
+mod ff3;
+mod ip;
+mod iter;
+mod mac;
+mod nd;
+#[cfg(feature = "rayon")]
+mod par;
+mod permutation;
+mod ports;
+mod producer;
+mod product;
+#[cfg(feature = "quality")]
+pub mod quality;
+mod secret;
+mod security;
+mod sequential;
+#[cfg(feature = "serde")]
+mod serde_impl;
+mod shard;
+mod shuffle_ext;
+mod siphash;
+mod slice;
+mod split;
+mod state;
+mod stats;
+mod striped;
+mod swap_or_not;
+mod table;
+mod targets;
+#[cfg(feature = "test_vectors")]
+pub mod test_vectors;
+mod thorp;
+mod throttle;
+mod tight;
+mod typed;
+mod uint;
+mod unique;
+mod uuid;
+mod verify;
+
+pub use algorithm::Algorithm;
+pub use alphabet::AlphabetRng;
+pub use backend::{Backend, RoundFunction};
+pub use builder::PerfectRngBuilder;
+pub use const_rounds::PerfectRngConst;
+pub use cursor::{Checkpoint, ShuffleCursor};
+pub use digits::DigitsRng;
+pub use dns::{SubdomainIter, Subdomains};
+pub use domain::{DomainSet, DomainSetIter};
+pub use epoch::EpochShuffler;
+pub use error::{BuildError, PerfectRngError};
+pub use ip::{ShuffledIpv4, ShuffledIpv6};
+pub use iter::ShuffledIter;
+pub use mac::ShuffledMacs;
+pub use nd::{PerfectRngNd, PerfectRngNdIter};
+#[cfg(feature = "rayon")]
+pub use par::ParShuffledIter;
+#[cfg(feature = "derive")]
+pub use perfect_rand_derive::Shuffleable;
+pub use permutation::{
+    Composed, IdentityPermutation, Inverted, Permutation, ReversePermutation, TablePermutation,
+};
+pub use ports::{Port, PortSet, Protocol, ShuffledPorts};
+pub use producer::{Producer, ShuffledChunk};
+pub use product::{ProductDomain, ProductDomainIter};
+pub use secret::SecretKey;
+pub use security::SecurityLevel;
+pub use sequential::SequentialIter;
+pub use shard::ShardIter;
+pub use shuffle_ext::ShuffleExt;
+pub use slice::{PermutedSlice, PermutedSliceIter};
+pub use split::{DatasetSplit, Split};
+pub use state::ScanState;
+#[cfg(feature = "stats")]
+pub use stats::WalkStats;
+pub use striped::{StripedIter, StripedRng};
+pub use table::{TableRng, DEFAULT_TABLE_THRESHOLD};
+pub use targets::{ShuffledTargetPorts, ShuffledTargets, TargetSet};
+pub use throttle::Throttled;
+pub use typed::Shuffleable;
+pub use uint::UnsignedInt;
+pub use unique::UniqueRng;
+pub use uuid::UuidRng;
+pub use verify::{BijectionFailure, BijectionReport};
+
+/// The highest number of rounds a [`PerfectRng`] can have. More rounds than
+/// this don't make the permutation any more random, so it's almost certainly
+/// a mistake.
+pub const MAX_ROUNDS: usize = 32;
+
+/// The number of SipHash rounds in every Feistel round, unless it's changed
+/// with [`PerfectRng::with_siprounds`].
+pub(crate) const DEFAULT_SIPROUNDS: usize = 4;
+
+/// Whether keys and seeds are hidden in `Debug` output. Anyone with the key
+/// can unshuffle everything, so it's only shown when that's asked for with
+/// `expose_seed`, and never with `zeroize`.
+pub(crate) const REDACT_KEYS: bool = cfg!(feature = "zeroize") || !cfg!(feature = "expose_seed");
+
+/// A perfect cipher over `0..range`, generic over the width of the integers
+/// it shuffles.
+///
+/// Use the [`PerfectRng16`], [`PerfectRng32`], and [`PerfectRng128`] aliases
+/// for domains of other sizes.
+#[derive(Default)]
+pub struct PerfectRng<T: UnsignedInt = u64> {
+    /// The highest index in the domain, which is `range - 1`. This is stored
+    /// instead of the range so the full domain of `T` can be represented.
+    max: T,
+    /// The lowest value in the domain, so the domain is
+    /// `offset..=offset + max`.
+    offset: T,
+    key: [T; 2],
     rounds: usize,
+    /// The number of SipHash rounds in each Feistel round, for
+    /// [`Backend::SipHash`] and the card shuffles.
+    siprounds: usize,
+    tweak: T,
+    backend: Backend,
+    /// Whether cycle walking always does the same number of encryptions.
+    constant_time: bool,
+    /// The version of the algorithm, which is always [`Algorithm::V1`] for
+    /// now.
+    algorithm: Algorithm,
+    /// Whether the cipher is a permutation of exactly `0..=max`, like when
+    /// the range is a power of two, so nothing ever has to be cycle walked.
+    exact: bool,
+    /// Whether the key was given as a [`SecretKey`], so it shouldn't be shown
+    /// or serialized.
+    secret: bool,
     a_bits: u32,
-    a_mask: u64,
-    b_mask: u64,
+    a_mask: T,
+    b_mask: T,
+    /// Whether [`PerfectRng::with_tight_domain`] was used.
+    tight: bool,
+    /// The sizes of the two halves when the domain is split with division
+    /// instead of bits, like for [`Backend::Blackrock2`] or a tight domain.
+    /// This is zero otherwise.
+    split: [T; 2],
+    /// Keys derived from `key` for each round, so they don't have to be
+    /// derived every time the round function is called.
+    round_keys: [[T; 2]; MAX_ROUNDS],
+    /// The round number xored with the tweak for each round, which is what
+    /// the round function takes as `j`.
+    round_tweaks: [T; MAX_ROUNDS],
+    /// How much cycle walking has been done, which is only counted with the
+    /// `stats` feature.
+    counters: stats::Counters,
 }
 
-#[derive(Default, Debug)]
-pub struct PerfectRng32 {
-    range: u32,
-    seed: u32,
-    rounds: usize,
-    a_bits: u32,
-    a_mask: u32,
-    b_mask: u32,
+impl<T: UnsignedInt> fmt::Debug for PerfectRng<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let redact = self.secret || REDACT_KEYS;
+
+        let mut debug = f.debug_struct("PerfectRng");
+        debug.field("max", &self.max).field("offset", &self.offset);
+        if redact {
+            debug.field("key", &format_args!("<redacted>"));
+        } else {
+            debug.field("key", &self.key);
+        }
+        debug
+            .field("rounds", &self.rounds)
+            .field("siprounds", &self.siprounds)
+            .field("backend", &self.backend)
+            .field("constant_time", &self.constant_time)
+            .field("algorithm", &self.algorithm)
+            .field("exact", &self.exact)
+            .field("a_bits", &self.a_bits)
+            .field("a_mask", &self.a_mask)
+            .field("b_mask", &self.b_mask)
+            .field("tight", &self.tight)
+            .field("split", &self.split);
+        if redact {
+            return debug.finish_non_exhaustive();
+        }
+        debug
+            .field("tweak", &self.tweak)
+            .field("round_keys", &&self.round_keys[..self.rounds])
+            .finish()
+    }
 }
 
-fn count_bits(num: u64) -> u32 {
-    let mut bits = 0;
-    while (num >> bits) != 0 {
-        bits += 1;
+/// Two `PerfectRng`s are equal if they're configured to give the same
+/// permutation, so this compares the range, key, rounds, backend, and
+/// everything else that changes the order. Settings that only change how it's
+/// computed, like [`PerfectRng::with_constant_time`], are ignored.
+///
+/// ```
+/// # use perfect_rand::PerfectRng;
+/// let a = PerfectRng::with_key(1000u64, [1, 2], 4);
+/// assert_eq!(a, PerfectRng::with_key(1000u64, [1, 2], 4).with_constant_time());
+/// assert_ne!(a, PerfectRng::with_key(1000u64, [1, 2], 5));
+/// ```
+impl<T: UnsignedInt> PartialEq for PerfectRng<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.max == other.max
+            && self.offset == other.offset
+            && self.key == other.key
+            && self.rounds == other.rounds
+            && self.siprounds == other.siprounds
+            && self.tweak == other.tweak
+            && self.backend == other.backend
+            && self.algorithm == other.algorithm
+            && self.tight == other.tight
     }
-    bits
 }
 
-#[inline]
-fn sipround(mut v0: u64, mut v1: u64, mut v2: u64, mut v3: u64) -> (u64, u64, u64, u64) {
-    v0 = v0.wrapping_add(v1);
-    v2 = v2.wrapping_add(v3);
-    v1 = v1.rotate_left(13) ^ v0;
-    v3 = v3.rotate_left(16) ^ v2;
-    v0 = v0.rotate_left(32);
-
-    v2 = v2.wrapping_add(v1);
-    v0 = v0.wrapping_add(v3);
-    v1 = v1.rotate_left(17) ^ v2;
-    v3 = v3.rotate_left(21) ^ v0;
-    v2 = v2.rotate_left(32);
-
-    (v0, v1, v2, v3)
+impl<T: UnsignedInt> Eq for PerfectRng<T> {}
+
+impl<T: UnsignedInt> Hash for PerfectRng<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.max.hash(state);
+        self.offset.hash(state);
+        self.key.hash(state);
+        self.rounds.hash(state);
+        self.siprounds.hash(state);
+        self.tweak.hash(state);
+        self.backend.hash(state);
+        self.algorithm.hash(state);
+        self.tight.hash(state);
+    }
 }
 
-#[inline]
-fn sipround32(mut v0: u32, mut v1: u32, mut v2: u32, mut v3: u32) -> (u32, u32, u32, u32) {
-    v0 = v0.wrapping_add(v1);
-    v2 = v2.wrapping_add(v3);
-    v1 = v1.rotate_left(5) ^ v0;
-    v3 = v3.rotate_left(8) ^ v2;
-    v0 = v0.rotate_left(16);
-
-    v2 = v2.wrapping_add(v1);
-    v0 = v0.wrapping_add(v3);
-    v1 = v1.rotate_left(13) ^ v2;
-    v3 = v3.rotate_left(17) ^ v0;
-    v2 = v2.rotate_left(16);
-
-    (v0, v1, v2, v3)
+/// The same as [`PerfectRng::from_range_bounds`], so a range can be used
+/// wherever an `impl Into<PerfectRng<T>>` is expected.
+///
+/// ```
+/// # use perfect_rand::PerfectRng;
+/// fn count(randomizer: impl Into<PerfectRng<u64>>) -> usize {
+///     randomizer.into().iter().count()
+/// }
+/// assert_eq!(count(10..20), 10);
+/// ```
+///
+/// # Panics
+///
+/// Panics if the range is empty.
+impl<T: UnsignedInt> From<Range<T>> for PerfectRng<T> {
+    fn from(range: Range<T>) -> Self {
+        Self::from_range_bounds(range)
+    }
+}
+
+/// The same as [`PerfectRng::from_range_inclusive`], but returns
+/// [`PerfectRngError::ZeroRange`] instead of panicking if the range is empty.
+///
+/// ```
+/// # use perfect_rand::{PerfectRng, PerfectRngError};
+/// let randomizer = PerfectRng::try_from(0..=u64::MAX).unwrap();
+/// assert_eq!(randomizer.range(), 0..=u64::MAX);
+/// #[allow(clippy::reversed_empty_ranges)]
+/// let empty = PerfectRng::try_from(5..=4u64);
+/// assert_eq!(empty.unwrap_err(), PerfectRngError::ZeroRange);
+/// ```
+impl<T: UnsignedInt> TryFrom<RangeInclusive<T>> for PerfectRng<T> {
+    type Error = PerfectRngError;
+
+    fn try_from(range: RangeInclusive<T>) -> Result<Self, Self::Error> {
+        if range.is_empty() {
+            return Err(PerfectRngError::ZeroRange);
+        }
+        Ok(Self::from_range_inclusive(range))
+    }
+}
+
+/// Wipe the key material when the `PerfectRng` is dropped.
+#[cfg(feature = "zeroize")]
+impl<T: UnsignedInt> Drop for PerfectRng<T> {
+    fn drop(&mut self) {
+        for k in self
+            .key
+            .iter_mut()
+            .chain(self.round_keys.iter_mut().flatten())
+            .chain(&mut self.round_tweaks)
+        {
+            k.zeroize();
+        }
+        self.tweak.zeroize();
+    }
 }
 
-impl PerfectRng {
+#[cfg(feature = "zeroize")]
+impl<T: UnsignedInt> zeroize::ZeroizeOnDrop for PerfectRng<T> {}
+
+impl PerfectRng<u64> {
+    /// Create a new perfect cipher from a 128-bit key, split into two
+    /// little-endian `u64`s. See [`PerfectRng::with_key`].
+    ///
+    /// ```
+    /// # use perfect_rand::PerfectRng;
+    /// let perfect_rng = PerfectRng::with_key_bytes(10, rand::random(), 4);
+    /// ```
+    #[must_use]
+    pub fn with_key_bytes(range: u64, key: [u8; 16], rounds: usize) -> Self {
+        let (k0, k1) = key.split_at(8);
+        let k0 = u64::from_le_bytes(k0.try_into().unwrap());
+        let k1 = u64::from_le_bytes(k1.try_into().unwrap());
+        Self::with_key(range, [k0, k1], rounds)
+    }
+
+    /// Create a perfect cipher with [`Backend::Blackrock2`], a port of
+    /// masscan's `blackrock2_shuffle`, with the given range and seed and
+    /// masscan's default of 14 rounds.
+    ///
+    /// The outputs haven't been checked against masscan's own yet, so don't
+    /// rely on getting the same order as a masscan scan with the same seed.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `range` is 0 or too big for Blackrock2.
+    ///
+    /// ```
+    /// # use perfect_rand::PerfectRng;
+    /// let randomizer = PerfectRng::masscan_compatible(1000, 1234);
+    /// assert_eq!(randomizer.unshuffle(randomizer.shuffle(10)), 10);
+    /// ```
+    #[doc(hidden)]
+    #[must_use]
+    pub fn masscan_compatible(range: u64, seed: u64) -> Self {
+        Self::new(range, seed, 14).with_backend(Backend::Blackrock2)
+    }
+}
+
+/// A [`PerfectRng`] over `u16`, for small domains like ports.
+///
+/// The rounds are done with 32-bit SipHash rounds, so shuffling never needs
+/// 64-bit arithmetic, which is slow on some embedded targets.
+///
+/// ```
+/// # use perfect_rand::PerfectRng16;
+/// let randomizer = PerfectRng16::with_key_inclusive(1..=u16::MAX, [1, 2], 4);
+/// for port in randomizer.iter().take(10) {
+///     assert_ne!(port, 0);
+/// }
+/// ```
+pub type PerfectRng16 = PerfectRng<u16>;
+
+/// A [`PerfectRng`] over `u32`, which is faster than the `u64` version if
+/// your range fits in it, like IPv4 addresses.
+///
+/// The rounds are done with 32-bit SipHash rounds, so the Feistel network
+/// doesn't need 64-bit arithmetic. It's a different permutation from the
+/// `u64` version with the same key.
+///
+/// ```
+/// # use std::net::Ipv4Addr;
+/// # use perfect_rand::PerfectRng32;
+/// let randomizer = PerfectRng32::from_range(u32::MAX);
+/// for i in 0..10 {
+///     println!("{}", Ipv4Addr::from(randomizer.shuffle(i)));
+/// }
+/// ```
+pub type PerfectRng32 = PerfectRng<u32>;
+
+/// A [`PerfectRng`] over `u64`, which is the same type as `PerfectRng` with
+/// its default width.
+///
+/// Integer literals passed to `PerfectRng::new` don't get a type from the
+/// default, so they need a suffix like `10u64`. With this alias they don't,
+/// like before `PerfectRng` was generic.
+///
+/// ```
+/// # use perfect_rand::PerfectRng64;
+/// let randomizer = PerfectRng64::new(10, 1, 3);
+/// assert_eq!(randomizer.unshuffle(randomizer.shuffle(5)), 5);
+/// ```
+pub type PerfectRng64 = PerfectRng<u64>;
+
+/// A [`PerfectRng`] over `u128`, for domains that don't fit in a `u64` like
+/// IPv6 addresses.
+pub type PerfectRng128 = PerfectRng<u128>;
+
+impl<T: UnsignedInt> PerfectRng<T> {
     /// Create a new perfect cipher with a specific range, seed, and rounds.
     /// Use [`PerfectRng::from_range`] to use the default seed and rounds.
     ///
@@ -94,208 +479,573 @@ impl PerfectRng {
     /// - `seed`: The seed used for randomization.
     /// - `rounds`: The amount of times the randomization is done, to make it
     ///   more random. Recommended value is either 3 or 4, depending on your
-    ///   performance/quality needs.
+    ///   performance/quality needs. This can't be more than [`MAX_ROUNDS`].
+    ///   See [`SecurityLevel`] for some presets, and
+    ///   [`PerfectRng::recommended_rounds`] for small domains.
+    ///
+    /// A range of 1 is allowed, and shuffling always gives back its only value
+    /// with every backend. A range of 0 has nothing to shuffle, so it panics;
+    /// use [`PerfectRng::try_new`] to get [`PerfectRngError::ZeroRange`]
+    /// instead.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `range` is 0 or `rounds` is more than [`MAX_ROUNDS`].
+    ///
+    /// ```
+    /// # use perfect_rand::PerfectRng;
+    /// let perfect_rng = PerfectRng::new(10u64, rand::random(), 4);
+    ///
+    /// let single = PerfectRng::new(1u64, rand::random(), 4);
+    /// assert_eq!(single.shuffle(0), 0);
+    /// ```
+    #[must_use]
+    #[inline]
+    pub fn new(range: T, seed: T, rounds: usize) -> Self {
+        Self::with_key(range, [seed, T::ZERO], rounds)
+    }
+
+    /// Create a new perfect cipher keyed with two integers, like SipHash's
+    /// 128-bit key. This makes it harder to recover the key from shuffled
+    /// outputs than with a single seed.
+    ///
+    /// [`PerfectRng::new`] is the same as this with the second half of the
+    /// key set to 0.
     ///
     /// ```
     /// # use perfect_rand::PerfectRng;
-    /// let perfect_rng = PerfectRng::new(10, rand::random(), 4);
+    /// let perfect_rng = PerfectRng::with_key(10u64, rand::random(), 4);
+    /// assert!(perfect_rng.shuffle(5) < 10);
     /// ```
     #[must_use]
     #[inline]
-    pub fn new(range: u64, seed: u64, rounds: usize) -> Self {
-        assert_ne!(range, 0);
+    pub fn with_key(range: T, key: [T; 2], rounds: usize) -> Self {
+        assert_ne!(range, T::ZERO, "the range must not be 0");
+        Self::with_key_max(range - T::ONE, key, rounds)
+    }
 
-        let bits = count_bits(range - 1);
+    /// Create a new perfect cipher over `0..=max`.
+    pub(crate) fn with_key_max(max: T, key: [T; 2], rounds: usize) -> Self {
+        assert!(rounds <= MAX_ROUNDS, "rounds must be at most {MAX_ROUNDS}");
+
+        let bits = max.count_bits();
         let b = bits / 2;
         // if an odd number of bits, a gets the leftover bit
         let a = bits - b;
 
+        let mut round_keys = [[T::ZERO; 2]; MAX_ROUNDS];
+        for (j, round_key) in round_keys.iter_mut().enumerate().take(rounds) {
+            // rounds start at 1
+            *round_key = T::round_key(key, j + 1);
+        }
+
         PerfectRng {
-            range,
-            seed,
+            max,
+            offset: T::ZERO,
+            key,
             rounds,
+            siprounds: DEFAULT_SIPROUNDS,
+            tweak: T::ZERO,
+            backend: Backend::SipHash,
+            constant_time: false,
+            algorithm: Algorithm::V1,
+            // the bits are only ever all used by a power of two
+            exact: max & max.wrapping_add(T::ONE) == T::ZERO,
+            secret: false,
             a_bits: a,
-            a_mask: (1 << a) - 1,
-            b_mask: (1 << b) - 1,
+            a_mask: (T::ONE << a) - T::ONE,
+            b_mask: (T::ONE << b) - T::ONE,
+            tight: false,
+            split: [T::ZERO; 2],
+            round_keys,
+            round_tweaks: Self::round_tweaks(T::ZERO),
+            counters: stats::Counters::new(),
+        }
+    }
+
+    /// Set the tweak that's already been hashed, and the round tweaks that
+    /// are derived from it.
+    pub(crate) fn set_tweak(&mut self, tweak: T) {
+        self.tweak = tweak;
+        self.round_tweaks = Self::round_tweaks(tweak);
+    }
+
+    fn round_tweaks(tweak: T) -> [T; MAX_ROUNDS] {
+        // rounds start at 1
+        let mut round_tweaks = [T::ZERO; MAX_ROUNDS];
+        for (j, round_tweak) in round_tweaks.iter_mut().enumerate() {
+            *round_tweak = T::from_usize(j + 1) ^ tweak;
         }
+        round_tweaks
     }
 
-    /// Create a new `PerfectRng` with a random seed and default rounds.
+    /// Like [`PerfectRng::new`], but returns an error instead of panicking or
+    /// creating a degenerate permutation.
+    ///
+    /// This rejects a `range` of 0, and `rounds` that are 0 or more than
+    /// [`MAX_ROUNDS`]. Every other range is valid, since the Feistel halves
+    /// always fit in the integer type together.
     ///
     /// ```
-    /// # use perfect_rand::PerfectRng;
-    /// let perfect_rng = PerfectRng::from_range(2u64.pow(32));
+    /// # use perfect_rand::{PerfectRng, PerfectRngError};
+    /// assert!(PerfectRng::try_new(10u64, 0, 4).is_ok());
+    /// assert_eq!(
+    ///     PerfectRng::try_new(0u64, 0, 4).unwrap_err(),
+    ///     PerfectRngError::ZeroRange
+    /// );
     /// ```
-    #[must_use]
-    pub fn from_range(range: u64) -> Self {
-        Self::new(range, rand::random(), 4)
+    pub fn try_new(range: T, seed: T, rounds: usize) -> Result<Self, PerfectRngError> {
+        Self::try_with_key(range, [seed, T::ZERO], rounds)
     }
 
-    #[inline]
-    fn round(&self, j: usize, right: u64) -> u64 {
-        let v0 = self.seed;
-        let v1 = j as u64;
-        let v2 = right;
-        // all zeroes will lead to an all-zero output,
-        // this adds some randomness for that case.
-        let v3: u64 = 0xf3016d19bc9ad940;
+    /// Like [`PerfectRng::with_key`], but returns an error instead of
+    /// panicking or creating a degenerate permutation. See
+    /// [`PerfectRng::try_new`].
+    pub fn try_with_key(range: T, key: [T; 2], rounds: usize) -> Result<Self, PerfectRngError> {
+        if range == T::ZERO {
+            return Err(PerfectRngError::ZeroRange);
+        }
+        if rounds == 0 {
+            return Err(PerfectRngError::ZeroRounds);
+        }
+        if rounds > MAX_ROUNDS {
+            return Err(PerfectRngError::TooManyRounds(rounds));
+        }
 
-        let (v0, v1, v2, v3) = sipround(v0, v1, v2, v3);
-        let (v0, v1, v2, v3) = sipround(v0, v1, v2, v3);
-        let (v0, v1, v2, v3) = sipround(v0, v1, v2, v3);
-        let (v0, _, _, _) = sipround(v0, v1, v2, v3);
+        Ok(Self::with_key(range, key, rounds))
+    }
 
-        v0
+    /// Mix a tweak into every round, so the same key gives an independent
+    /// permutation for every tweak. This is useful for having a different
+    /// order for each scan without having to store a new key.
+    ///
+    /// ```
+    /// # use perfect_rand::PerfectRng;
+    /// let key = rand::random();
+    /// let july = PerfectRng::with_key(1000u64, key, 4).with_tweak(b"scan-2024-07");
+    /// let august = PerfectRng::with_key(1000u64, key, 4).with_tweak(b"scan-2024-08");
+    /// assert!(!july.iter().eq(august.iter()));
+    /// ```
+    #[must_use]
+    pub fn with_tweak(mut self, tweak: &[u8]) -> Self {
+        // the tweak is hashed with the key so tweaks can't be chosen to cancel
+        // out the round number
+        let key = self.key.map(|k| {
+            let k = k.as_u128();
+            (k ^ (k >> 64)) as u64
+        });
+        self.set_tweak(T::from_u128(siphash::siphash128(key, tweak)));
+        self
     }
 
-    #[inline]
-    fn encrypt(&self, m: u64) -> u64 {
-        let mut left = m & self.a_mask;
-        let mut right = m >> self.a_bits;
+    /// Use a different round function. See [`Backend`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if the backend is [`Backend::Blackrock2`] and the range is too
+    /// big for it.
+    ///
+    /// ```
+    /// # use perfect_rand::{Backend, PerfectRng};
+    /// let randomizer = PerfectRng::from_range(1000u64).with_backend(Backend::Aes);
+    /// assert_eq!(randomizer.backend(), Backend::Aes);
+    /// ```
+    #[must_use]
+    pub fn with_backend(mut self, backend: Backend) -> Self {
+        self.set_backend(backend)
+            .expect("the range is too big for the backend");
+        self
+    }
 
-        let mut j = 1;
-        while j <= self.rounds {
-            if j % 2 != 0 {
-                let tmp = (left + self.round(j, right)) & self.a_mask;
-                left = right;
-                right = tmp;
-                j += 1;
-            } else {
-                let tmp = (left + self.round(j, right)) & self.b_mask;
-                left = right;
-                right = tmp;
-                j += 1;
-            }
+    /// Like [`PerfectRng::with_backend`], but returns an error instead of
+    /// panicking.
+    pub(crate) fn set_backend(&mut self, backend: Backend) -> Result<(), PerfectRngError> {
+        if self.constant_time && !backend.is_constant_time() {
+            return Err(PerfectRngError::NotConstantTime);
         }
-
-        if self.rounds % 2 != 0 {
-            (left << self.a_bits) + right
-        } else {
-            (right << self.a_bits) + left
+        let mut split = [T::ZERO; 2];
+        if backend == Backend::Blackrock2 {
+            split = u64::try_from(self.max.as_u128())
+                .ok()
+                .and_then(|max| blackrock2::split(max.checked_add(1)?))
+                // the halves are padded, so they can multiply to more than T
+                // fits even if the range fits
+                .filter(|[a, b]| (a * b - 1) as u128 <= T::MAX.as_u128())
+                .ok_or(PerfectRngError::DomainTooLarge)?
+                .map(|n| T::from_u128(n as u128));
+        } else if self.tight && backend.splits_bits() {
+            split = tight::split(self.max).unwrap_or(split);
         }
+        self.backend = backend;
+        self.split = split;
+        self.exact = self.raw_max() == self.max;
+        Ok(())
     }
 
-    /// Randomize your input.
+    /// Change the number of SipHash rounds that are done in each Feistel
+    /// round, like the `c` and `d` in SipHash-c-d. The default is 4. Fewer is
+    /// faster, and more makes each round diffuse its input better.
+    ///
+    /// This is only used by [`Backend::SipHash`], [`Backend::SwapOrNot`], and
+    /// [`Backend::Thorp`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `siprounds` is 0.
+    ///
+    /// ```
+    /// # use perfect_rand::PerfectRng;
+    /// let randomizer = PerfectRng::from_range(1000u64).with_siprounds(2);
+    /// assert_eq!(randomizer.unshuffle(randomizer.shuffle(10)), 10);
+    /// ```
+    #[must_use]
+    pub fn with_siprounds(mut self, siprounds: usize) -> Self {
+        assert_ne!(siprounds, 0, "siprounds must not be 0");
+        self.siprounds = siprounds;
+        self
+    }
+
+    /// The round function this uses. See [`PerfectRng::with_backend`].
+    #[must_use]
+    pub fn backend(&self) -> Backend {
+        self.backend
+    }
+
+    /// The values that are shuffled, including the offset.
     ///
     /// ```
     /// # use perfect_rand::PerfectRng;
+    /// let randomizer = PerfectRng::with_key_inclusive(100..=199u64, [1, 2], 4);
+    /// assert_eq!(randomizer.range(), 100..=199);
+    /// assert_eq!(randomizer.rounds(), 4);
+    /// ```
+    #[must_use]
+    pub fn range(&self) -> RangeInclusive<T> {
+        self.offset..=self.offset + self.max
+    }
+
+    /// The number of Feistel rounds. See [`PerfectRng::new`].
+    #[must_use]
+    pub fn rounds(&self) -> usize {
+        self.rounds
+    }
+
+    /// The number of SipHash rounds in each Feistel round. See
+    /// [`PerfectRng::with_siprounds`].
+    #[must_use]
+    pub fn siprounds(&self) -> usize {
+        self.siprounds
+    }
+
+    /// The key, with the `expose_seed` feature. For a seed passed to
+    /// [`PerfectRng::new`], it's `[seed, 0]`.
+    ///
+    /// Anyone with the key can unshuffle every value, so be careful where
+    /// it's logged. This is `None` if the key is secret, until
+    /// [`PerfectRng::expose_key`] is called.
     ///
-    /// let randomizer = PerfectRng::from_range(100);
-    /// for i in 0..100 {
-    ///     let shuffled_i = randomizer.shuffle(i);
-    ///     assert!(shuffled_i <= 100);
-    /// }
     /// ```
+    /// # use perfect_rand::{PerfectRng, SecretKey};
+    /// assert_eq!(PerfectRng::new(1000u64, 5, 4).seed(), Some([5, 0]));
+    ///
+    /// let randomizer = PerfectRng::with_secret_key(1000, &SecretKey::new([1u64, 2]), 4);
+    /// assert_eq!(randomizer.seed(), None);
+    /// assert_eq!(randomizer.expose_key().seed(), Some([1, 2]));
+    /// ```
+    #[cfg(feature = "expose_seed")]
     #[must_use]
-    #[inline]
-    pub fn shuffle(&self, m: u64) -> u64 {
-        assert!(m < self.range);
+    pub fn seed(&self) -> Option<[T; 2]> {
+        (!self.secret).then_some(self.key)
+    }
 
-        let mut c = self.encrypt(m);
-        while c >= self.range {
-            c = self.encrypt(c);
+    /// A hash of everything that affects the permutation, for checking that a
+    /// saved position is resumed with the same configuration.
+    pub(crate) fn config_hash(&self) -> u64 {
+        let mut bytes = Vec::with_capacity(16 * 6);
+        for n in [self.max, self.offset, self.key[0], self.key[1], self.tweak] {
+            bytes.extend_from_slice(&n.as_u128().to_le_bytes());
         }
-        c
+        bytes.extend_from_slice(&(self.rounds as u64).to_le_bytes());
+        bytes.extend_from_slice(&(self.siprounds as u64).to_le_bytes());
+        bytes.push(self.backend.id());
+        bytes.push(u8::from(self.tight));
+        bytes.push(self.algorithm.id());
+        if let Backend::Custom(round_function) = self.backend {
+            bytes.extend_from_slice(round_function.name().as_bytes());
+        }
+        siphash::siphash128([0, 0], &bytes) as u64
     }
-}
 
-impl PerfectRng32 {
-    /// Create a new perfect cipher with a specific range, seed, and rounds.
-    /// Use [`PerfectRng::from_range`] to use the default seed and rounds.
+    /// Move the domain so it starts at `offset` instead of 0. The permutation
+    /// is then over `offset..offset + range`, and both the inputs and outputs
+    /// of [`PerfectRng::shuffle`] are in that range.
     ///
-    /// - `range`: The number of possible values. In other words, the highest
-    ///   value you will try to shuffle. For example, this would be 2**32-1 for
-    ///   an IPv4 address.
-    /// - `seed`: The seed used for randomization.
-    /// - `rounds`: The amount of times the randomization is done, to make it
-    ///   more random. Recommended value is either 3 or 4, depending on your
-    ///   performance/quality needs.
+    /// # Panics
+    ///
+    /// Panics if `offset + range - 1` doesn't fit in `T`.
     ///
     /// ```
     /// # use perfect_rand::PerfectRng;
-    /// let perfect_rng = PerfectRng::new(10, rand::random(), 4);
+    /// let randomizer = PerfectRng::from_range(10_000u64).with_offset(10_000);
+    /// let shuffled = randomizer.shuffle(15_000);
+    /// assert!((10_000..20_000).contains(&shuffled));
     /// ```
     #[must_use]
-    #[inline]
-    pub fn new(range: u32, seed: u32, rounds: usize) -> Self {
-        assert_ne!(range, 0);
+    pub fn with_offset(mut self, offset: T) -> Self {
+        assert!(
+            T::MAX - offset >= self.max,
+            "offset + range must fit in the integer type"
+        );
+        self.offset = offset;
+        self
+    }
 
-        let bits = count_bits((range - 1) as u64);
-        let b = bits / 2;
-        // if an odd number of bits, a gets the leftover bit
-        let a = bits - b;
+    /// Create a new `PerfectRng` over the values in `range`, with a random key
+    /// and default rounds.
+    ///
+    /// ```
+    /// # use perfect_rand::PerfectRng;
+    /// let randomizer = PerfectRng::from_range_bounds(10_000u64..20_000);
+    /// for shuffled in randomizer.iter() {
+    ///     assert!((10_000..20_000).contains(&shuffled));
+    /// }
+    /// ```
+    #[must_use]
+    pub fn from_range_bounds(range: Range<T>) -> Self {
+        assert!(range.start < range.end, "the range must not be empty");
+        Self::from_range(range.end - range.start).with_offset(range.start)
+    }
 
-        PerfectRng32 {
-            range,
-            seed,
-            rounds,
-            a_bits: a,
-            a_mask: (1 << a) - 1,
-            b_mask: (1 << b) - 1,
-        }
+    /// Create a new `PerfectRng` over the values in an inclusive range, with a
+    /// random key and default rounds.
+    ///
+    /// Unlike the exclusive ranges taken by the other constructors, this can
+    /// cover every value of `T`.
+    ///
+    /// ```
+    /// # use perfect_rand::PerfectRng;
+    /// let randomizer = PerfectRng::from_range_inclusive(0..=u64::MAX);
+    /// let shuffled = randomizer.shuffle(u64::MAX);
+    /// assert_eq!(randomizer.unshuffle(shuffled), u64::MAX);
+    /// ```
+    #[must_use]
+    pub fn from_range_inclusive(range: RangeInclusive<T>) -> Self {
+        Self::with_key_inclusive(range, [T::random(), T::random()], 4)
+    }
+
+    /// Create a new perfect cipher over the values in an inclusive range,
+    /// keyed like [`PerfectRng::with_key`].
+    ///
+    /// ```
+    /// # use perfect_rand::PerfectRng;
+    /// let randomizer = PerfectRng::with_key_inclusive(0..=u32::MAX, rand::random(), 4);
+    /// assert_eq!(randomizer.range(), 0..=u32::MAX);
+    /// ```
+    #[must_use]
+    pub fn with_key_inclusive(range: RangeInclusive<T>, key: [T; 2], rounds: usize) -> Self {
+        let (start, end) = range.into_inner();
+        assert!(start <= end, "the range must not be empty");
+        Self::with_key_max(end - start, key, rounds).with_offset(start)
     }
 
-    /// Create a new `PerfectRng` with a random seed and default rounds.
+    /// Create a new `PerfectRng` with a random key and default rounds.
     ///
     /// ```
     /// # use perfect_rand::PerfectRng;
     /// let perfect_rng = PerfectRng::from_range(2u64.pow(32));
     /// ```
     #[must_use]
-    pub fn from_range(range: u32) -> Self {
-        Self::new(range, rand::random(), 4)
+    pub fn from_range(range: T) -> Self {
+        Self::with_key(range, [T::random(), T::random()], 4)
     }
 
-    #[inline]
-    fn round(&self, j: usize, right: u32) -> u32 {
-        let v0 = self.seed;
-        let v1 = j as u32;
-        let v2 = right;
-        // all zeroes will lead to an all-zero output,
-        // this adds some randomness for that case.
-        let v3: u32 = 0xbc9ad940;
+    /// Create a new `PerfectRng` with a key from the given random number
+    /// generator and default rounds. This is useful for making the
+    /// permutation reproducible with a seeded generator.
+    ///
+    /// ```
+    /// # use perfect_rand::PerfectRng;
+    /// use rand::{rngs::StdRng, SeedableRng};
+    ///
+    /// let a = PerfectRng::from_rng(1000u64, &mut StdRng::seed_from_u64(1));
+    /// let b = PerfectRng::from_rng(1000u64, &mut StdRng::seed_from_u64(1));
+    /// assert!(a.iter().eq(b.iter()));
+    /// ```
+    #[must_use]
+    pub fn from_rng<R: RngCore + ?Sized>(range: T, rng: &mut R) -> Self {
+        Self::with_key(range, [T::random_from(rng), T::random_from(rng)], 4)
+    }
 
-        let (v0, v1, v2, v3) = sipround32(v0, v1, v2, v3);
-        let (v0, v1, v2, v3) = sipround32(v0, v1, v2, v3);
-        let (v0, v1, v2, v3) = sipround32(v0, v1, v2, v3);
-        let (v0, _, _, _) = sipround32(v0, v1, v2, v3);
+    #[inline]
+    fn round(&self, j: usize, right: T) -> T {
+        self.backend.round(
+            self.round_keys[j - 1],
+            self.round_tweaks[j - 1],
+            right,
+            self.siprounds,
+        )
+    }
 
-        v0
+    /// The number of rounds for [`Backend::SwapOrNot`] and [`Backend::Thorp`],
+    /// which is the number of rounds for every bit of the domain.
+    #[inline]
+    fn card_shuffle_rounds(&self) -> usize {
+        self.rounds * self.max.count_bits().max(1) as usize
     }
 
     #[inline]
-    fn encrypt(&self, m: u32) -> u32 {
+    fn encrypt(&self, m: T) -> T {
+        match self.backend {
+            Backend::Blackrock2 => return self.blackrock2_encrypt(m),
+            #[cfg(feature = "fpe")]
+            Backend::Ff1 => return self.ff1_encrypt(m),
+            #[cfg(feature = "fpe")]
+            Backend::Ff3_1 => return self.ff3_encrypt(m),
+            Backend::SwapOrNot => return self.swap_or_not_encrypt(m),
+            Backend::Thorp => return self.thorp_encrypt(m),
+            Backend::EvenMansour => return self.even_mansour_encrypt(m),
+            _ => {}
+        }
+        if self.split[0] != T::ZERO {
+            return self.tight_encrypt(m);
+        }
+
         let mut left = m & self.a_mask;
         let mut right = m >> self.a_bits;
 
+        // two rounds at a time, so the mask for each one is known without
+        // checking whether it's odd or even
         let mut j = 1;
-        while j <= self.rounds {
-            if j % 2 != 0 {
-                let tmp = (left + self.round(j, right)) & self.a_mask;
-                left = right;
-                right = tmp;
-                j += 1;
-            } else {
-                let tmp = (left + self.round(j, right)) & self.b_mask;
-                left = right;
-                right = tmp;
-                j += 1;
-            }
+        while j < self.rounds {
+            let tmp = left.wrapping_add(self.round(j, right)) & self.a_mask;
+            left = right;
+            right = tmp;
+            let tmp = left.wrapping_add(self.round(j + 1, right)) & self.b_mask;
+            left = right;
+            right = tmp;
+            j += 2;
+        }
+        if j == self.rounds {
+            let tmp = left.wrapping_add(self.round(j, right)) & self.a_mask;
+            left = right;
+            right = tmp;
         }
 
-        if self.rounds % 2 != 0 {
+        if !self.rounds.is_multiple_of(2) {
             (left << self.a_bits) + right
         } else {
             (right << self.a_bits) + left
         }
     }
 
+    #[inline]
+    fn decrypt(&self, c: T) -> T {
+        match self.backend {
+            Backend::Blackrock2 => return self.blackrock2_decrypt(c),
+            #[cfg(feature = "fpe")]
+            Backend::Ff1 => return self.ff1_decrypt(c),
+            #[cfg(feature = "fpe")]
+            Backend::Ff3_1 => return self.ff3_decrypt(c),
+            Backend::SwapOrNot => return self.swap_or_not_decrypt(c),
+            Backend::Thorp => return self.thorp_decrypt(c),
+            Backend::EvenMansour => return self.even_mansour_decrypt(c),
+            _ => {}
+        }
+        if self.split[0] != T::ZERO {
+            return self.tight_decrypt(c);
+        }
+
+        let (mut left, mut right) = if !self.rounds.is_multiple_of(2) {
+            (c >> self.a_bits, c & self.a_mask)
+        } else {
+            (c & self.a_mask, c >> self.a_bits)
+        };
+
+        // the last round is odd if there's an odd number of them, and the
+        // rest are undone two at a time like in encrypt
+        let mut j = self.rounds;
+        if !j.is_multiple_of(2) {
+            let tmp = right.wrapping_sub(self.round(j, left)) & self.a_mask;
+            right = left;
+            left = tmp;
+            j -= 1;
+        }
+        while j > 0 {
+            let tmp = right.wrapping_sub(self.round(j, left)) & self.b_mask;
+            right = left;
+            left = tmp;
+            let tmp = right.wrapping_sub(self.round(j - 1, left)) & self.a_mask;
+            right = left;
+            left = tmp;
+            j -= 2;
+        }
+
+        (right << self.a_bits) + left
+    }
+
+    /// The highest value that [`PerfectRng::encrypt_raw`] and
+    /// [`PerfectRng::decrypt_raw`] accept and return. The raw cipher is a
+    /// permutation of `0..=raw_max()`, which is usually padded past the range
+    /// to a power of two (or to `a * b` for [`Backend::Blackrock2`]).
+    ///
+    /// ```
+    /// # use perfect_rand::PerfectRng;
+    /// assert_eq!(PerfectRng::from_range(1000u64).raw_max(), 1023);
+    /// ```
+    #[must_use]
+    pub fn raw_max(&self) -> T {
+        match self.backend {
+            _ if self.split[0] != T::ZERO => {
+                T::from_u128(self.split[0].as_u128() * self.split[1].as_u128() - 1)
+            }
+            #[cfg(feature = "fpe")]
+            Backend::Ff1 | Backend::Ff3_1 => T::MAX >> (T::BITS - self.fpe_bits()),
+            Backend::SwapOrNot => self.max,
+            Backend::Thorp => self.max | T::ONE,
+            _ => (self.b_mask << self.a_bits) | self.a_mask,
+        }
+    }
+
+    /// Encrypt a value with the underlying cipher, without cycle walking and
+    /// without the offset. This is a permutation of `0..=raw_max()`, for
+    /// building your own mapping from a domain onto the cipher.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `m` is more than [`PerfectRng::raw_max`].
+    ///
+    /// ```
+    /// # use perfect_rand::PerfectRng;
+    /// let randomizer = PerfectRng::from_range(1000u64);
+    /// let c = randomizer.encrypt_raw(5);
+    /// assert!(c <= randomizer.raw_max());
+    /// assert_eq!(randomizer.decrypt_raw(c), 5);
+    /// ```
+    #[must_use]
+    #[inline]
+    pub fn encrypt_raw(&self, m: T) -> T {
+        assert!(m <= self.raw_max());
+        self.encrypt(m)
+    }
+
+    /// Reverse [`PerfectRng::encrypt_raw`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `c` is more than [`PerfectRng::raw_max`].
+    #[must_use]
+    #[inline]
+    pub fn decrypt_raw(&self, c: T) -> T {
+        assert!(c <= self.raw_max());
+        self.decrypt(c)
+    }
+
     /// Randomize your input.
     ///
     /// ```
     /// # use perfect_rand::PerfectRng;
     ///
-    /// let randomizer = PerfectRng::from_range(100);
+    /// let randomizer = PerfectRng::from_range(100u64);
     /// for i in 0..100 {
     ///     let shuffled_i = randomizer.shuffle(i);
     ///     assert!(shuffled_i <= 100);
@@ -303,22 +1053,82 @@ impl PerfectRng32 {
     /// ```
     #[must_use]
     #[inline]
-    pub fn shuffle(&self, m: u32) -> u32 {
-        assert!(m < self.range);
+    pub fn shuffle(&self, m: T) -> T {
+        assert!(m >= self.offset && m - self.offset <= self.max);
+
+        self.shuffle_index(m - self.offset) + self.offset
+    }
+
+    /// Shuffle a number in `0..=max`, ignoring the offset.
+    #[inline]
+    pub(crate) fn shuffle_index(&self, m: T) -> T {
+        if self.exact {
+            self.counters.record(0);
+            return self.encrypt(m);
+        }
+        if self.constant_time {
+            return self.walk_constant_time(self.encrypt(m), |c| self.encrypt(c));
+        }
 
         let mut c = self.encrypt(m);
-        while c >= self.range {
+        let mut walks = 0;
+        while c > self.max {
             c = self.encrypt(c);
+            walks += 1;
         }
+        self.counters.record(walks);
         c
     }
+
+    /// Get the value at a position in the permutation. This is the same as
+    /// `shuffle(offset + index)`.
+    #[inline]
+    pub(crate) fn shuffle_nth(&self, index: T) -> T {
+        self.shuffle_index(index) + self.offset
+    }
+
+    /// Reverse [`PerfectRng::shuffle`], getting back the input that produced
+    /// the given output.
+    ///
+    /// ```
+    /// # use perfect_rand::PerfectRng;
+    ///
+    /// let randomizer = PerfectRng::from_range(100u64);
+    /// for i in 0..100 {
+    ///     let shuffled_i = randomizer.shuffle(i);
+    ///     assert_eq!(randomizer.unshuffle(shuffled_i), i);
+    /// }
+    /// ```
+    #[must_use]
+    #[inline]
+    pub fn unshuffle(&self, c: T) -> T {
+        assert!(c >= self.offset && c - self.offset <= self.max);
+
+        if self.exact {
+            self.counters.record(0);
+            return self.decrypt(c - self.offset) + self.offset;
+        }
+        if self.constant_time {
+            return self.walk_constant_time(self.decrypt(c - self.offset), |m| self.decrypt(m))
+                + self.offset;
+        }
+
+        let mut m = self.decrypt(c - self.offset);
+        let mut walks = 0;
+        while m > self.max {
+            m = self.decrypt(m);
+            walks += 1;
+        }
+        self.counters.record(walks);
+        m + self.offset
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use ntest::timeout;
 
-    use super::PerfectRng;
+    use super::{Backend, PerfectRng, PerfectRng128, PerfectRng16, PerfectRng32, PerfectRngError};
 
     fn verify(range: u64, seed: u64, rounds: usize) {
         let randomizer = PerfectRng::new(range, seed, rounds);
@@ -327,8 +1137,9 @@ mod tests {
         // make sure every number gets added exactly once
         let mut list = vec![0; range as usize];
         for i in 0..range {
-            let x = randomizer.shuffle(i) as usize;
-            list[x] += 1;
+            let x = randomizer.shuffle(i);
+            assert_eq!(randomizer.unshuffle(x), i, "range: {range:?}");
+            list[x as usize] += 1;
         }
 
         for (i, number) in list.into_iter().enumerate() {
@@ -355,7 +1166,7 @@ mod tests {
     #[test]
     #[timeout(100)]
     fn dont_get_stuck() {
-        for range in [10, 100] {
+        for range in [10u64, 100] {
             for seed in 0..100 {
                 let randomizer = PerfectRng::new(range, seed, 4);
 
@@ -368,7 +1179,7 @@ mod tests {
 
     #[test]
     fn sufficiently_random() {
-        let randomizer = PerfectRng::new(65536, 0, 4);
+        let randomizer = PerfectRng::new(65536u64, 0, 4);
         let mut inc = 0_u32;
         let mut dec = 0_u32;
         let mut prev = 0;
@@ -389,4 +1200,546 @@ mod tests {
             "insufficiently random (inc: {inc}, dec: {dec})"
         );
     }
+
+    #[test]
+    fn unshuffle_odd_rounds() {
+        for rounds in [1, 3, 5] {
+            let randomizer = PerfectRng::new(1000u64, 0, rounds);
+            for i in 0..1000 {
+                assert_eq!(randomizer.unshuffle(randomizer.shuffle(i)), i);
+            }
+        }
+    }
+
+    #[test]
+    fn unshuffle_32() {
+        let randomizer = PerfectRng32::new(12345, 0, 4);
+        for i in 0..12345 {
+            assert_eq!(randomizer.unshuffle(randomizer.shuffle(i)), i);
+        }
+    }
+
+    #[test]
+    fn power_of_two() {
+        assert!(PerfectRng::new(1024u64, 0, 4).exact);
+        assert!(PerfectRng::with_key_inclusive(0..=u64::MAX, [1, 2], 4).exact);
+        assert!(!PerfectRng::new(1000u64, 0, 4).exact);
+        // blackrock2 pads the range even if it's a power of two
+        assert!(!PerfectRng::masscan_compatible(1024, 0).exact);
+        assert!(
+            PerfectRng::new(1024u64, 0, 4)
+                .with_backend(Backend::SwapOrNot)
+                .exact
+        );
+
+        for randomizer in [
+            PerfectRng::new(1u64 << 12, 0, 4),
+            PerfectRng::new(1u64 << 12, 0, 3)
+                .with_offset(5)
+                .with_constant_time(),
+        ] {
+            let offset = randomizer.offset;
+            let mut list = vec![0; 1 << 12];
+            for i in offset..offset + (1 << 12) {
+                let x = randomizer.shuffle(i);
+                assert_eq!(randomizer.unshuffle(x), i);
+                list[(x - offset) as usize] += 1;
+            }
+            assert!(list.into_iter().all(|n| n == 1));
+        }
+    }
+
+    #[test]
+    fn verify_128() {
+        let randomizer = PerfectRng128::new(20000, 0, 4);
+        let mut list = vec![0; 20000];
+        for i in 0..20000 {
+            let x = randomizer.shuffle(i);
+            assert_eq!(randomizer.unshuffle(x), i);
+            list[x as usize] += 1;
+        }
+        assert!(list.into_iter().all(|n| n == 1));
+    }
+
+    #[test]
+    fn huge_128() {
+        let range = u128::MAX / 3;
+        let randomizer = PerfectRng128::new(range, 0, 4);
+        for i in [0, 1, 2, range / 2, range - 1] {
+            let x = randomizer.shuffle(i);
+            assert!(x < range);
+            assert_eq!(randomizer.unshuffle(x), i);
+        }
+    }
+
+    #[test]
+    fn near_max() {
+        // the halves are as big as they can be here, so adding the round
+        // function's output to them would overflow if it didn't wrap
+        fn check<T: crate::UnsignedInt + std::fmt::Debug>(range: T, randomizer: &PerfectRng<T>) {
+            for i in [
+                T::ZERO,
+                T::ONE,
+                range >> 1,
+                range - T::from_usize(2),
+                range - T::ONE,
+            ] {
+                let x = randomizer.shuffle(i);
+                assert!(x < range, "{x:?} isn't in 0..{range:?}");
+                assert_eq!(randomizer.unshuffle(x), i);
+            }
+        }
+
+        for rounds in 1..=5 {
+            for backend in [
+                Backend::SipHash,
+                Backend::Aes,
+                Backend::ChaCha,
+                Backend::Philox,
+            ] {
+                let range = u64::MAX - 5;
+                check(
+                    range,
+                    &PerfectRng::new(range, 1, rounds).with_backend(backend),
+                );
+                let range = u32::MAX - 5;
+                check(
+                    range,
+                    &PerfectRng32::new(range, 1, rounds).with_backend(backend),
+                );
+                let range = u128::MAX - 5;
+                check(
+                    range,
+                    &PerfectRng128::new(range, 1, rounds).with_backend(backend),
+                );
+            }
+            let range = u64::MAX - 5;
+            check(
+                range,
+                &PerfectRng::new(range, 1, rounds).with_tight_domain(),
+            );
+
+            // the round function's output is only 16 bits for u16, so the
+            // additions overflow often enough to be caught by going over
+            // every value
+            let randomizer = PerfectRng16::new(u16::MAX - 5, 1, rounds);
+            let mut seen = vec![false; usize::from(u16::MAX - 5)];
+            for i in 0..u16::MAX - 5 {
+                let x = randomizer.shuffle(i);
+                assert_eq!(randomizer.unshuffle(x), i);
+                assert!(!std::mem::replace(&mut seen[usize::from(x)], true));
+            }
+        }
+    }
+
+    #[test]
+    fn range_of_one() {
+        let backends = [
+            Backend::SipHash,
+            Backend::Aes,
+            Backend::ChaCha,
+            Backend::Philox,
+            Backend::Blackrock2,
+            Backend::SwapOrNot,
+            Backend::Thorp,
+            Backend::EvenMansour,
+        ];
+        #[cfg(feature = "fpe")]
+        let backends = backends.into_iter().chain([Backend::Ff1, Backend::Ff3_1]);
+
+        for backend in backends {
+            for rounds in [1, 4] {
+                let randomizer = PerfectRng::new(1u64, 5, rounds).with_backend(backend);
+                assert_eq!(randomizer.shuffle(0), 0, "{backend:?}");
+                assert_eq!(randomizer.unshuffle(0), 0, "{backend:?}");
+                assert!(randomizer.iter().eq([0]));
+            }
+        }
+
+        let randomizer = PerfectRng::with_key_inclusive(7..=7u32, [1, 2], 4);
+        assert_eq!(randomizer.shuffle(7), 7);
+        assert!(randomizer.iter().eq([7]));
+        assert_eq!(
+            PerfectRng::new(1u64, 5, 4).with_tight_domain().shuffle(0),
+            0
+        );
+        assert_eq!(
+            PerfectRng::new(1u64, 5, 4).with_constant_time().shuffle(0),
+            0
+        );
+        assert_eq!(PerfectRng128::new(1, 5, 4).unshuffle(0), 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "the range must not be 0")]
+    fn range_of_zero() {
+        let _ = PerfectRng::new(0u64, 5, 4);
+    }
+
+    #[test]
+    fn same_on_every_platform() {
+        // everything is integer math on fixed-width types, so these hashes
+        // have to match on 32-bit, big-endian, and wasm targets, and with or
+        // without AES-NI
+        fn hash<T: crate::UnsignedInt>(backend: Backend, range: T) -> Option<u64> {
+            let randomizer = PerfectRng::builder()
+                .range(range)
+                .with_key([T::from_usize(1), T::from_usize(2)])
+                .rounds(3)
+                .backend(backend)
+                .build()
+                .ok()?;
+            let inputs = (0..64)
+                .map(|i| T::from_u128(range.as_u128() / 64 * i))
+                .collect::<Vec<_>>();
+            let mut outputs = inputs.clone();
+            randomizer.shuffle_batch(&mut outputs);
+
+            // FNV-1a over the little-endian bytes of the outputs
+            let mut hash = 0xcbf29ce484222325_u64;
+            for (&input, &output) in inputs.iter().zip(&outputs) {
+                assert_eq!(randomizer.shuffle(input), output, "{backend:?}");
+                for byte in output.as_u128().to_le_bytes() {
+                    hash = (hash ^ u64::from(byte)).wrapping_mul(0x100000001b3);
+                }
+            }
+            Some(hash)
+        }
+
+        let expected = [
+            (
+                Backend::SipHash,
+                [
+                    Some(0x9f6e7fae1c3113a4),
+                    Some(0x6d45434ff6572130),
+                    Some(0xfb36469086e8a964),
+                    Some(0x333ca50691685582),
+                ],
+            ),
+            (
+                Backend::Aes,
+                [
+                    Some(0xdb4e2e5c475016fc),
+                    Some(0x9e921aea18d1cd11),
+                    Some(0xfcac303d11cf3a13),
+                    Some(0xb52f29ea1724d325),
+                ],
+            ),
+            (
+                Backend::ChaCha,
+                [
+                    Some(0xea0de16fa878b017),
+                    Some(0x2931f819e98d793f),
+                    Some(0xb564a22ba83177b8),
+                    Some(0x04282454805e9c18),
+                ],
+            ),
+            (
+                Backend::Philox,
+                [
+                    Some(0x62d000696b0a02fb),
+                    Some(0x5134dded7bd29a63),
+                    Some(0x9966b9a67f367f03),
+                    Some(0x648f41220c2f1854),
+                ],
+            ),
+            (
+                Backend::Blackrock2,
+                [
+                    Some(0xe19e10c532a22ed1),
+                    Some(0xb32904fdb89c7acd),
+                    Some(0xd43dac8d8b1337b6),
+                    // only ranges that fit in a u64 are supported
+                    None,
+                ],
+            ),
+            (
+                Backend::SwapOrNot,
+                [
+                    Some(0xf9323c9161099c20),
+                    Some(0x56ae6a6d380d7247),
+                    Some(0x6e10b3aadb304cb7),
+                    Some(0x247158803a28f81b),
+                ],
+            ),
+            (
+                Backend::Thorp,
+                [
+                    Some(0x23ad7189c159f502),
+                    Some(0x021c8f618171be7f),
+                    Some(0x3fb43cc6b4bcf093),
+                    Some(0x27e9b7e221be5f3d),
+                ],
+            ),
+            (
+                Backend::EvenMansour,
+                [
+                    Some(0x43a38eafef5bf2c6),
+                    Some(0xde44045d7c35ef90),
+                    Some(0x77a1eb0d76323141),
+                    Some(0x7f133218815aed8a),
+                ],
+            ),
+        ];
+        #[cfg(feature = "fpe")]
+        let expected = expected.into_iter().chain([
+            (
+                Backend::Ff1,
+                [
+                    Some(0xdff51e695b194e94),
+                    Some(0xcd29148287e860f9),
+                    Some(0x9da34d763d91d2b1),
+                    Some(0xd832e613926811e0),
+                ],
+            ),
+            (
+                Backend::Ff3_1,
+                [
+                    Some(0x09eb83c744b7c4ef),
+                    Some(0x5083363c2cd9a3c5),
+                    Some(0x18ac80d61f20d6da),
+                    Some(0xb28efc8a8e902ae1),
+                ],
+            ),
+        ]);
+
+        for (backend, expected) in expected {
+            let hashes = [
+                hash(backend, 40_000u16),
+                hash(backend, 3_000_000_000u32),
+                hash(backend, 1_000_000_000_000_000_003u64),
+                hash(backend, 10u128.pow(30)),
+            ];
+            assert_eq!(hashes, expected, "{backend:?}");
+        }
+    }
+
+    #[test]
+    fn try_new_errors() {
+        use crate::{PerfectRngError, MAX_ROUNDS};
+
+        assert_eq!(
+            PerfectRng::try_new(0u32, 0, 4).unwrap_err(),
+            PerfectRngError::ZeroRange
+        );
+        assert_eq!(
+            PerfectRng::try_new(10u32, 0, 0).unwrap_err(),
+            PerfectRngError::ZeroRounds
+        );
+        assert_eq!(
+            PerfectRng::try_new(10u32, 0, MAX_ROUNDS + 1).unwrap_err(),
+            PerfectRngError::TooManyRounds(MAX_ROUNDS + 1)
+        );
+        assert!(PerfectRng::try_new(u128::MAX, 0, MAX_ROUNDS).is_ok());
+    }
+
+    #[test]
+    fn key_halves_matter() {
+        let a = PerfectRng::with_key(1000u64, [1, 2], 4);
+        let b = PerfectRng::with_key(1000u64, [1, 3], 4);
+        let c = PerfectRng::new(1000u64, 1, 4);
+        assert!(!a.iter().eq(b.iter()));
+        assert!(!a.iter().eq(c.iter()));
+        assert!(PerfectRng::with_key(1000u64, [1, 0], 4).iter().eq(c.iter()));
+    }
+
+    #[test]
+    fn equality() {
+        use std::hash::{BuildHasher, RandomState};
+
+        let randomizers = [
+            PerfectRng::with_key(1000u64, [1, 2], 4),
+            PerfectRng::with_key(1000u64, [1, 2], 4).with_offset(1),
+            PerfectRng::with_key(1001u64, [1, 2], 4),
+            PerfectRng::with_key(1000u64, [1, 3], 4),
+            PerfectRng::with_key(1000u64, [1, 2], 5),
+            PerfectRng::with_key(1000u64, [1, 2], 4).with_siprounds(2),
+            PerfectRng::with_key(1000u64, [1, 2], 4).with_tweak(b"a"),
+            PerfectRng::with_key(1000u64, [1, 2], 4).with_backend(Backend::Aes),
+            PerfectRng::with_key(1000u64, [1, 2], 4).with_tight_domain(),
+        ];
+        let state = RandomState::new();
+        for (i, a) in randomizers.iter().enumerate() {
+            for (j, b) in randomizers.iter().enumerate() {
+                assert_eq!(a == b, i == j, "{i} {j}");
+                assert_eq!(state.hash_one(a) == state.hash_one(b), i == j, "{i} {j}");
+            }
+        }
+
+        let constant_time = PerfectRng::with_key(1000u64, [1, 2], 4).with_constant_time();
+        assert_eq!(randomizers[0], constant_time);
+        assert_eq!(
+            state.hash_one(&randomizers[0]),
+            state.hash_one(&constant_time)
+        );
+    }
+
+    #[test]
+    #[allow(clippy::reversed_empty_ranges)]
+    fn from_ranges() {
+        let randomizer = PerfectRng::from(100..200u32);
+        assert_eq!(randomizer.range(), 100..=199);
+        let mut shuffled = randomizer.iter().collect::<Vec<_>>();
+        shuffled.sort_unstable();
+        assert!(shuffled.into_iter().eq(100..200));
+
+        let randomizer = PerfectRng::try_from(0..=u128::MAX).unwrap();
+        assert_eq!(randomizer.range(), 0..=u128::MAX);
+        assert_eq!(
+            PerfectRng::try_from(7..=7u16)
+                .unwrap()
+                .iter()
+                .collect::<Vec<_>>(),
+            [7]
+        );
+        assert_eq!(
+            PerfectRng::try_from(8..=7u16).unwrap_err(),
+            PerfectRngError::ZeroRange
+        );
+    }
+
+    #[test]
+    fn getters() {
+        let randomizer = PerfectRng::with_key_inclusive(0..=u128::MAX, [1, 2], 3).with_siprounds(2);
+        assert_eq!(randomizer.range(), 0..=u128::MAX);
+        assert_eq!(randomizer.rounds(), 3);
+        assert_eq!(randomizer.siprounds(), 2);
+        assert_eq!(PerfectRng::new(10u32, 0, 4).range(), 0..=9);
+        #[cfg(feature = "expose_seed")]
+        assert_eq!(randomizer.seed(), Some([1, 2]));
+    }
+
+    #[test]
+    fn tweaks() {
+        let plain = PerfectRng::new(1000u64, 1, 4);
+        let a = PerfectRng::new(1000u64, 1, 4).with_tweak(b"a");
+        let b = PerfectRng::new(1000u64, 1, 4).with_tweak(b"b");
+        assert!(!plain.iter().eq(a.iter()));
+        assert!(!a.iter().eq(b.iter()));
+        assert!(a
+            .iter()
+            .eq(PerfectRng::new(1000u64, 1, 4).with_tweak(b"a").iter()));
+        for i in 0..1000 {
+            assert_eq!(a.unshuffle(a.shuffle(i)), i);
+        }
+    }
+
+    #[test]
+    #[cfg(any(feature = "zeroize", not(feature = "expose_seed")))]
+    fn debug_hides_key() {
+        let randomizer = PerfectRng::with_key(10u64, [123456789, 987654321], 4);
+        let debug = format!("{randomizer:?}");
+        assert!(!debug.contains("123456789"), "{debug}");
+        assert!(!debug.contains("987654321"), "{debug}");
+        assert!(debug.contains("key: <redacted>"), "{debug}");
+    }
+
+    #[test]
+    fn offset() {
+        let randomizer = PerfectRng::new(1000u64, 1, 4).with_offset(5000);
+        let plain = PerfectRng::new(1000u64, 1, 4);
+        for i in 0..1000 {
+            let shuffled = randomizer.shuffle(5000 + i);
+            assert_eq!(shuffled, plain.shuffle(i) + 5000);
+            assert_eq!(randomizer.unshuffle(shuffled), 5000 + i);
+        }
+        assert!(randomizer.iter().eq(plain.iter().map(|i| i + 5000)));
+
+        let top = PerfectRng::from_range_bounds(u32::MAX - 10..u32::MAX);
+        assert!(top.iter().all(|i| (u32::MAX - 10..u32::MAX).contains(&i)));
+    }
+
+    #[test]
+    #[should_panic]
+    fn offset_out_of_range() {
+        let randomizer = PerfectRng::from_range_bounds(10u64..20);
+        let _ = randomizer.shuffle(5);
+    }
+
+    #[test]
+    fn siprounds() {
+        let default = PerfectRng::new(1000u64, 1, 4);
+        assert!(default
+            .iter()
+            .eq(PerfectRng::new(1000u64, 1, 4).with_siprounds(4).iter()));
+        for siprounds in [1, 2, 8] {
+            let randomizer = PerfectRng::new(1000u64, 1, 4).with_siprounds(siprounds);
+            assert!(!randomizer.iter().eq(default.iter()));
+            assert_ne!(randomizer.config_hash(), default.config_hash());
+            for i in 0..1000 {
+                assert_eq!(randomizer.unshuffle(randomizer.shuffle(i)), i);
+            }
+        }
+    }
+
+    #[test]
+    fn raw() {
+        for backend in [
+            Backend::SipHash,
+            Backend::Blackrock2,
+            Backend::SwapOrNot,
+            Backend::Thorp,
+            Backend::EvenMansour,
+        ] {
+            let randomizer = PerfectRng::with_key(1000u32, [1, 2], 4).with_backend(backend);
+            let raw_max = randomizer.raw_max();
+            assert!(raw_max >= 999);
+            let mut seen = vec![false; raw_max as usize + 1];
+            for m in 0..=raw_max {
+                let c = randomizer.encrypt_raw(m);
+                assert_eq!(randomizer.decrypt_raw(c), m);
+                assert!(!seen[c as usize]);
+                seen[c as usize] = true;
+            }
+        }
+
+        let full = PerfectRng::<u16>::with_key_inclusive(0..=u16::MAX, [1, 2], 4);
+        assert_eq!(full.raw_max(), u16::MAX);
+
+        // masscan pads the halves, so they multiply to more than a u32 holds
+        assert!(PerfectRng::<u32>::new(u32::MAX, 1, 4)
+            .set_backend(Backend::Blackrock2)
+            .is_err());
+    }
+
+    #[test]
+    #[should_panic]
+    fn raw_too_big() {
+        let randomizer = PerfectRng::new(1000u64, 1, 4);
+        let _ = randomizer.encrypt_raw(1024);
+    }
+
+    #[test]
+    fn full_domain() {
+        let randomizer = PerfectRng::from_range_inclusive(0..=u64::MAX);
+        for i in [0, 1, u64::MAX / 2, u64::MAX - 1, u64::MAX] {
+            assert_eq!(randomizer.unshuffle(randomizer.shuffle(i)), i);
+        }
+
+        let randomizer = PerfectRng::with_key_inclusive(0..=u16::MAX, [1, 2], 4);
+        let mut list = vec![0; 65536];
+        for i in 0..=u16::MAX {
+            let x = randomizer.shuffle(i);
+            assert_eq!(randomizer.unshuffle(x), i);
+            list[x as usize] += 1;
+        }
+        assert!(list.into_iter().all(|n| n == 1));
+
+        // an inclusive range is the same permutation as the exclusive one
+        let exclusive = PerfectRng::with_key(1000u64, [1, 2], 4).with_offset(10);
+        let inclusive = PerfectRng::with_key_inclusive(10..=1009, [1, 2], 4);
+        assert!(exclusive.iter().eq(inclusive.iter()));
+    }
+
+    #[test]
+    fn verify_16() {
+        let randomizer = PerfectRng16::new(u16::MAX, 0, 4);
+        let mut list = vec![0; u16::MAX as usize];
+        for i in 0..u16::MAX {
+            let x = randomizer.shuffle(i);
+            assert_eq!(randomizer.unshuffle(x), i);
+            list[x as usize] += 1;
+        }
+        assert!(list.into_iter().all(|n| n == 1));
+    }
 }
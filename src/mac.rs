@@ -0,0 +1,121 @@
+use std::iter::FusedIterator;
+
+use crate::{PerfectRng, ShuffleCursor};
+
+/// The number of MAC addresses, which are 48 bits.
+const MAC_COUNT: u64 = 1 << 48;
+/// The number of MAC addresses with the same OUI, which is the first 24 bits.
+const OUI_COUNT: u64 = 1 << 24;
+
+/// An iterator over 48-bit MAC addresses in shuffled order, as bytes in
+/// transmission order.
+///
+/// This is created by [`PerfectRng::shuffled_macs`] and
+/// [`PerfectRng::shuffled_macs_in_oui`].
+#[derive(Debug)]
+pub struct ShuffledMacs {
+    cursor: ShuffleCursor<u64>,
+}
+
+impl PerfectRng<u64> {
+    /// Iterate over every MAC address in shuffled order, with a random key.
+    ///
+    /// ```
+    /// # use perfect_rand::PerfectRng;
+    /// for mac in PerfectRng::shuffled_macs().take(10) {
+    ///     println!("{mac:02x?}");
+    /// }
+    /// ```
+    #[must_use]
+    pub fn shuffled_macs() -> ShuffledMacs {
+        ShuffledMacs::new(Self::from_range(MAC_COUNT))
+    }
+
+    /// Iterate over every MAC address with the given OUI (the first three
+    /// bytes, which identify the vendor) in shuffled order, with a random
+    /// key. Only the last three bytes are shuffled.
+    ///
+    /// ```
+    /// # use perfect_rand::PerfectRng;
+    /// let oui = [0x00, 0x1b, 0x63];
+    /// for mac in PerfectRng::shuffled_macs_in_oui(oui).take(10) {
+    ///     assert_eq!(mac[..3], oui);
+    /// }
+    /// ```
+    #[must_use]
+    pub fn shuffled_macs_in_oui(oui: [u8; 3]) -> ShuffledMacs {
+        let start = u64::from(u32::from_be_bytes([0, oui[0], oui[1], oui[2]])) << 24;
+        ShuffledMacs::new(Self::from_range(OUI_COUNT).with_offset(start))
+    }
+}
+
+impl ShuffledMacs {
+    /// Iterate over the MAC addresses in the range of a [`PerfectRng`], where
+    /// the low 48 bits of every `u64` are an address.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the range has values that are more than 48 bits.
+    #[must_use]
+    pub fn new(rng: PerfectRng<u64>) -> Self {
+        assert!(
+            rng.offset + rng.max < MAC_COUNT,
+            "MAC addresses must fit in 48 bits"
+        );
+        ShuffledMacs {
+            cursor: ShuffleCursor::new(rng),
+        }
+    }
+
+    /// The [`PerfectRng`] that the addresses come from.
+    #[must_use]
+    pub fn rng(&self) -> &PerfectRng<u64> {
+        self.cursor.rng()
+    }
+}
+
+impl Iterator for ShuffledMacs {
+    type Item = [u8; 6];
+
+    #[inline]
+    fn next(&mut self) -> Option<[u8; 6]> {
+        let mac = self.cursor.next()?.to_be_bytes();
+        Some([mac[2], mac[3], mac[4], mac[5], mac[6], mac[7]])
+    }
+}
+
+impl FusedIterator for ShuffledMacs {}
+
+#[cfg(test)]
+mod tests {
+    use super::ShuffledMacs;
+    use crate::PerfectRng;
+
+    #[test]
+    fn oui() {
+        let oui = [0xfc, 0xff, 0xff];
+        let mut macs = PerfectRng::shuffled_macs_in_oui(oui)
+            .map(|mac| {
+                assert_eq!(mac[..3], oui);
+                u32::from_be_bytes([0, mac[3], mac[4], mac[5]])
+            })
+            .collect::<Vec<_>>();
+        macs.sort_unstable();
+        assert!(macs.into_iter().eq(0..1 << 24));
+    }
+
+    #[test]
+    fn all() {
+        let mut macs = PerfectRng::shuffled_macs();
+        let mac = macs.next().unwrap();
+        let mut bytes = [0; 8];
+        bytes[2..].copy_from_slice(&mac);
+        assert_eq!(macs.rng().unshuffle(u64::from_be_bytes(bytes)), 0);
+    }
+
+    #[test]
+    #[should_panic]
+    fn too_big() {
+        let _ = ShuffledMacs::new(PerfectRng::from_range(1 << 48).with_offset(1));
+    }
+}
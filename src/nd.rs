@@ -0,0 +1,212 @@
+use std::iter::FusedIterator;
+
+use crate::{PerfectRng, PerfectRngError, ShuffledIter};
+
+/// A permutation over every point in an N-dimensional grid, like chunk
+/// coordinates in a game world.
+///
+/// Points are encoded as a mixed-radix index with the first coordinate
+/// changing fastest, and that index is shuffled by a [`PerfectRng`].
+///
+/// ```
+/// # use perfect_rand::PerfectRngNd;
+/// let grid = PerfectRngNd::new([16, 16, 4]).unwrap();
+/// assert_eq!(grid.len(), 1024);
+///
+/// for [x, y, z] in grid.iter() {
+///     assert!(x < 16 && y < 16 && z < 4);
+/// }
+/// ```
+#[derive(Debug)]
+pub struct PerfectRngNd<const N: usize> {
+    dims: [u64; N],
+    rng: PerfectRng<u64>,
+}
+
+impl<const N: usize> PerfectRngNd<N> {
+    /// Create a permutation over the grid with the given size in each
+    /// dimension, with a random key and default rounds.
+    ///
+    /// This returns an error if any dimension is 0, or if the number of
+    /// points doesn't fit in a `u64`.
+    pub fn new(dims: [u64; N]) -> Result<Self, PerfectRngError> {
+        Self::with_key(dims, rand::random(), 4)
+    }
+
+    /// Create a permutation over the grid, keyed like
+    /// [`PerfectRng::with_key`]. See [`PerfectRngNd::new`].
+    pub fn with_key(dims: [u64; N], key: [u64; 2], rounds: usize) -> Result<Self, PerfectRngError> {
+        let len = dims
+            .iter()
+            .try_fold(1u64, |len, &dim| len.checked_mul(dim))
+            .ok_or(PerfectRngError::DomainTooLarge)?;
+        let rng = PerfectRng::try_with_key(len, key, rounds)?;
+        Ok(PerfectRngNd { dims, rng })
+    }
+
+    /// The size of the grid in each dimension.
+    #[must_use]
+    pub fn dims(&self) -> [u64; N] {
+        self.dims
+    }
+
+    /// The number of points in the grid.
+    #[must_use]
+    pub fn len(&self) -> u64 {
+        self.rng.max + 1
+    }
+
+    /// Always `false`, since a grid can't be created without any points.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        false
+    }
+
+    /// Get the point at a position in the shuffled grid.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` isn't less than [`PerfectRngNd::len`].
+    #[must_use]
+    pub fn shuffle(&self, index: u64) -> [u64; N] {
+        self.decode(self.rng.shuffle(index))
+    }
+
+    /// Reverse [`PerfectRngNd::shuffle`], getting the position of a point in
+    /// the shuffled grid. Returns `None` if the point is outside the grid.
+    ///
+    /// ```
+    /// # use perfect_rand::PerfectRngNd;
+    /// let grid = PerfectRngNd::new([10, 20, 30]).unwrap();
+    /// let index = grid.unshuffle([1, 2, 3]).unwrap();
+    /// assert_eq!(grid.shuffle(index), [1, 2, 3]);
+    /// assert_eq!(grid.unshuffle([10, 0, 0]), None);
+    /// ```
+    #[must_use]
+    pub fn unshuffle(&self, point: [u64; N]) -> Option<u64> {
+        let index = self.encode(point)?;
+        Some(self.rng.unshuffle(index))
+    }
+
+    /// Iterate over every point in shuffled order.
+    #[must_use]
+    pub fn iter(&self) -> PerfectRngNdIter<'_, N> {
+        PerfectRngNdIter {
+            grid: self,
+            iter: self.rng.iter(),
+        }
+    }
+
+    /// The [`PerfectRng`] that shuffles the index space.
+    #[must_use]
+    pub fn rng(&self) -> &PerfectRng<u64> {
+        &self.rng
+    }
+
+    fn encode(&self, point: [u64; N]) -> Option<u64> {
+        // every coordinate is in range, so this can't overflow
+        let mut index = 0;
+        for (&coordinate, &dim) in point.iter().zip(&self.dims).rev() {
+            if coordinate >= dim {
+                return None;
+            }
+            index = index * dim + coordinate;
+        }
+        Some(index)
+    }
+
+    fn decode(&self, mut index: u64) -> [u64; N] {
+        self.dims.map(|dim| {
+            let coordinate = index % dim;
+            index /= dim;
+            coordinate
+        })
+    }
+}
+
+impl<'a, const N: usize> IntoIterator for &'a PerfectRngNd<N> {
+    type Item = [u64; N];
+    type IntoIter = PerfectRngNdIter<'a, N>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// An iterator over every point in a [`PerfectRngNd`], in shuffled order.
+///
+/// This is created by [`PerfectRngNd::iter`].
+#[derive(Debug, Clone)]
+pub struct PerfectRngNdIter<'a, const N: usize> {
+    grid: &'a PerfectRngNd<N>,
+    iter: ShuffledIter<'a, u64>,
+}
+
+impl<const N: usize> PerfectRngNdIter<'_, N> {
+    /// Jump to a position in the shuffled grid. See [`ShuffledIter::seek`].
+    pub fn seek(&mut self, index: u64) {
+        self.iter.seek(index);
+    }
+}
+
+impl<const N: usize> Iterator for PerfectRngNdIter<'_, N> {
+    type Item = [u64; N];
+
+    #[inline]
+    fn next(&mut self) -> Option<[u64; N]> {
+        self.iter.next().map(|index| self.grid.decode(index))
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+
+    #[inline]
+    fn nth(&mut self, n: usize) -> Option<[u64; N]> {
+        self.iter.nth(n).map(|index| self.grid.decode(index))
+    }
+}
+
+impl<const N: usize> DoubleEndedIterator for PerfectRngNdIter<'_, N> {
+    #[inline]
+    fn next_back(&mut self) -> Option<[u64; N]> {
+        self.iter.next_back().map(|index| self.grid.decode(index))
+    }
+}
+
+impl<const N: usize> ExactSizeIterator for PerfectRngNdIter<'_, N> {}
+
+impl<const N: usize> FusedIterator for PerfectRngNdIter<'_, N> {}
+
+#[cfg(test)]
+mod tests {
+    use super::PerfectRngNd;
+    use crate::PerfectRngError;
+
+    #[test]
+    fn covers_every_point() {
+        let grid = PerfectRngNd::with_key([7, 5, 3], [1, 2], 4).unwrap();
+        let mut points = grid.iter().collect::<Vec<_>>();
+        for (i, &point) in points.iter().enumerate() {
+            assert_eq!(grid.shuffle(i as u64), point);
+            assert_eq!(grid.unshuffle(point), Some(i as u64));
+        }
+        points.sort_unstable();
+        points.dedup();
+        assert_eq!(points.len(), 105);
+        assert!(points.iter().all(|&[x, y, z]| x < 7 && y < 5 && z < 3));
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(
+            PerfectRngNd::new([10, 0]).unwrap_err(),
+            PerfectRngError::ZeroRange
+        );
+        assert_eq!(
+            PerfectRngNd::new([u64::MAX, 2]).unwrap_err(),
+            PerfectRngError::DomainTooLarge
+        );
+    }
+}
@@ -0,0 +1,102 @@
+use rayon::iter::{
+    plumbing::{bridge_unindexed, Folder, UnindexedConsumer, UnindexedProducer},
+    ParallelIterator,
+};
+
+use crate::{PerfectRng, ShuffledIter, UnsignedInt};
+
+/// A parallel iterator over every value in the range of a [`PerfectRng`], in
+/// shuffled order.
+///
+/// This is created by [`PerfectRng::par_iter`].
+#[derive(Debug, Clone)]
+pub struct ParShuffledIter<'a, T: UnsignedInt = u64> {
+    iter: ShuffledIter<'a, T>,
+}
+
+impl<T: UnsignedInt> PerfectRng<T> {
+    /// Iterate over every value in `0..range` in shuffled order, in parallel
+    /// with [Rayon](https://docs.rs/rayon).
+    ///
+    /// Order is kept if you collect the values, but the closures you pass to
+    /// the iterator will be called from multiple threads in any order.
+    ///
+    /// ```
+    /// # use perfect_rand::PerfectRng;
+    /// use rayon::prelude::*;
+    ///
+    /// let randomizer = PerfectRng::from_range(100_000u64);
+    /// let sum: u64 = randomizer.par_iter().sum();
+    /// assert_eq!(sum, (0..100_000).sum());
+    /// ```
+    #[must_use]
+    pub fn par_iter(&self) -> ParShuffledIter<'_, T> {
+        ParShuffledIter { iter: self.iter() }
+    }
+}
+
+impl<T: UnsignedInt> ParallelIterator for ParShuffledIter<'_, T> {
+    type Item = T;
+
+    fn drive_unindexed<C>(self, consumer: C) -> C::Result
+    where
+        C: UnindexedConsumer<Self::Item>,
+    {
+        bridge_unindexed(self, consumer)
+    }
+}
+
+impl<T: UnsignedInt> UnindexedProducer for ParShuffledIter<'_, T> {
+    type Item = T;
+
+    fn split(self) -> (Self, Option<Self>) {
+        let front = self.iter.position();
+        let Some(back) = self.iter.back() else {
+            return (self, None);
+        };
+        if front == back {
+            return (self, None);
+        }
+
+        // the left half gets front..=mid and the right half gets the rest
+        let mid = front + ((back - front) >> 1);
+        let mut left = self.iter.clone();
+        left.truncate(mid);
+        let mut right = self.iter;
+        right.seek(mid + T::ONE);
+
+        (
+            ParShuffledIter { iter: left },
+            Some(ParShuffledIter { iter: right }),
+        )
+    }
+
+    fn fold_with<F>(self, folder: F) -> F
+    where
+        F: Folder<Self::Item>,
+    {
+        folder.consume_iter(self.iter)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rayon::prelude::*;
+
+    use crate::{PerfectRng, PerfectRng128};
+
+    #[test]
+    fn par_iter_matches_iter() {
+        let randomizer = PerfectRng::new(10_000u64, 0, 4);
+        let sequential = randomizer.iter().collect::<Vec<_>>();
+        let parallel = randomizer.par_iter().collect::<Vec<_>>();
+        assert_eq!(sequential, parallel);
+    }
+
+    #[test]
+    fn par_iter_huge() {
+        let randomizer = PerfectRng128::new(u128::MAX, 0, 4);
+        let found = randomizer.par_iter().find_any(|&x| x < u128::MAX);
+        assert!(found.is_some());
+    }
+}
@@ -0,0 +1,451 @@
+use crate::{PerfectRng, PerfectRngError};
+
+/// A permutation of the indices `0..len`, that can be applied in both
+/// directions.
+///
+/// This is implemented by [`PerfectRng`] with every
+/// [`Backend`](crate::Backend), for the integer types that fit in a `u64`.
+/// Code that only needs to map indices back and forth can take this instead
+/// of a concrete type, so the permutation can be swapped out in tests with
+/// [`IdentityPermutation`], [`ReversePermutation`], or [`TablePermutation`],
+/// where the order is easy to predict.
+///
+/// ```
+/// use perfect_rand::{Permutation, ReversePermutation};
+///
+/// fn first_batch(permutation: &impl Permutation, size: u64) -> Vec<u64> {
+///     (0..size.min(permutation.len())).map(|i| permutation.forward(i)).collect()
+/// }
+///
+/// assert_eq!(first_batch(&ReversePermutation::new(10), 3), [9, 8, 7]);
+/// ```
+pub trait Permutation {
+    /// The number of indices that are permuted.
+    fn len(&self) -> u64;
+
+    /// Whether there are no indices to permute.
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Where `index` goes in the permutation. This has to return a different
+    /// index in `0..len` for every index in `0..len`.
+    ///
+    /// Implementations can panic if `index` isn't less than the length.
+    fn forward(&self, index: u64) -> u64;
+
+    /// The index that `forward` maps to `index`, so
+    /// `inverse(forward(i)) == i`.
+    ///
+    /// Implementations can panic if `index` isn't less than the length.
+    fn inverse(&self, index: u64) -> u64;
+
+    /// The opposite permutation, where `forward` is this one's `inverse`
+    /// and the other way around.
+    ///
+    /// ```
+    /// # use perfect_rand::{PerfectRng, Permutation};
+    /// let randomizer = PerfectRng::with_key(1000u64, [1, 2], 4);
+    /// let inverted = (&randomizer).inverted();
+    /// assert_eq!(inverted.forward(5), randomizer.unshuffle(5));
+    /// ```
+    #[must_use]
+    fn inverted(self) -> Inverted<Self>
+    where
+        Self: Sized,
+    {
+        Inverted(self)
+    }
+
+    /// A permutation that applies this one and then `next`, for building up
+    /// a permutation from several stages.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the permutations don't have the same length.
+    ///
+    /// ```
+    /// # use perfect_rand::{PerfectRng, Permutation};
+    /// let first = PerfectRng::with_key(1000u64, [1, 2], 4);
+    /// let second = PerfectRng::with_key(1000u64, [3, 4], 4);
+    /// let both = (&first).compose(&second);
+    /// assert_eq!(both.forward(5), second.shuffle(first.shuffle(5)));
+    /// assert_eq!(both.inverse(both.forward(5)), 5);
+    /// ```
+    #[must_use]
+    fn compose<P: Permutation>(self, next: P) -> Composed<Self, P>
+    where
+        Self: Sized,
+    {
+        assert_eq!(
+            self.len(),
+            next.len(),
+            "the permutations must have the same length"
+        );
+        Composed {
+            first: self,
+            second: next,
+        }
+    }
+}
+
+/// The inverse of a permutation, from [`Permutation::inverted`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Inverted<P>(pub P);
+
+impl<P: Permutation> Permutation for Inverted<P> {
+    fn len(&self) -> u64 {
+        self.0.len()
+    }
+
+    fn forward(&self, index: u64) -> u64 {
+        self.0.inverse(index)
+    }
+
+    fn inverse(&self, index: u64) -> u64 {
+        self.0.forward(index)
+    }
+}
+
+/// Two permutations applied one after the other, from
+/// [`Permutation::compose`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Composed<A, B> {
+    first: A,
+    second: B,
+}
+
+impl<A: Permutation, B: Permutation> Permutation for Composed<A, B> {
+    fn len(&self) -> u64 {
+        self.first.len()
+    }
+
+    fn forward(&self, index: u64) -> u64 {
+        self.second.forward(self.first.forward(index))
+    }
+
+    fn inverse(&self, index: u64) -> u64 {
+        self.first.inverse(self.second.inverse(index))
+    }
+}
+
+impl<P: Permutation + ?Sized> Permutation for &P {
+    fn len(&self) -> u64 {
+        (**self).len()
+    }
+
+    fn forward(&self, index: u64) -> u64 {
+        (**self).forward(index)
+    }
+
+    fn inverse(&self, index: u64) -> u64 {
+        (**self).inverse(index)
+    }
+}
+
+impl<P: Permutation + ?Sized> Permutation for Box<P> {
+    fn len(&self) -> u64 {
+        (**self).len()
+    }
+
+    fn forward(&self, index: u64) -> u64 {
+        (**self).forward(index)
+    }
+
+    fn inverse(&self, index: u64) -> u64 {
+        (**self).inverse(index)
+    }
+}
+
+macro_rules! impl_permutation {
+    ($($ty:ty),*) => {
+        $(
+            /// The indices are positions in the range, so they don't include
+            /// the offset. A range of all of the type's values has one more
+            /// index than fits in a `u64` for `PerfectRng<u64>`, so its
+            /// length is reported as `u64::MAX`.
+            ///
+            /// ```
+            /// # use perfect_rand::{PerfectRng, Permutation};
+            #[doc = concat!("let randomizer = PerfectRng::<", stringify!($ty), ">::with_key_inclusive(10..=19, [1, 2], 4);")]
+            /// assert_eq!(randomizer.len(), 10);
+            /// assert!(randomizer.forward(0) < 10);
+            /// assert_eq!(randomizer.inverse(randomizer.forward(3)), 3);
+            /// ```
+            impl Permutation for PerfectRng<$ty> {
+                fn len(&self) -> u64 {
+                    u64::from(self.max).saturating_add(1)
+                }
+
+                fn forward(&self, index: u64) -> u64 {
+                    u64::from(self.shuffle_index(self.checked_index(index)))
+                }
+
+                fn inverse(&self, index: u64) -> u64 {
+                    let index = self.checked_index(index);
+                    u64::from(self.unshuffle(index + self.offset) - self.offset)
+                }
+            }
+
+            impl PerfectRng<$ty> {
+                fn checked_index(&self, index: u64) -> $ty {
+                    <$ty>::try_from(index)
+                        .ok()
+                        .filter(|&index| index <= self.max)
+                        .expect("the index must be less than the length")
+                }
+            }
+        )*
+    };
+}
+
+impl_permutation!(u16, u32, u64);
+
+fn assert_in_range(index: u64, len: u64) {
+    assert!(index < len, "the index must be less than the length");
+}
+
+/// A [`Permutation`] that leaves every index where it is.
+///
+/// ```
+/// # use perfect_rand::{IdentityPermutation, Permutation};
+/// let permutation = IdentityPermutation::new(10);
+/// assert_eq!(permutation.forward(3), 3);
+/// assert_eq!(permutation.inverse(3), 3);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IdentityPermutation {
+    len: u64,
+}
+
+impl IdentityPermutation {
+    /// A permutation of `0..len` that doesn't move anything.
+    #[must_use]
+    pub fn new(len: u64) -> Self {
+        IdentityPermutation { len }
+    }
+}
+
+impl Permutation for IdentityPermutation {
+    fn len(&self) -> u64 {
+        self.len
+    }
+
+    fn forward(&self, index: u64) -> u64 {
+        assert_in_range(index, self.len);
+        index
+    }
+
+    fn inverse(&self, index: u64) -> u64 {
+        assert_in_range(index, self.len);
+        index
+    }
+}
+
+/// A [`Permutation`] that reverses the order of the indices.
+///
+/// ```
+/// # use perfect_rand::{Permutation, ReversePermutation};
+/// let permutation = ReversePermutation::new(10);
+/// assert_eq!(permutation.forward(0), 9);
+/// assert_eq!(permutation.inverse(9), 0);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReversePermutation {
+    len: u64,
+}
+
+impl ReversePermutation {
+    /// A permutation of `0..len` that maps `i` to `len - 1 - i`.
+    #[must_use]
+    pub fn new(len: u64) -> Self {
+        ReversePermutation { len }
+    }
+}
+
+impl Permutation for ReversePermutation {
+    fn len(&self) -> u64 {
+        self.len
+    }
+
+    fn forward(&self, index: u64) -> u64 {
+        assert_in_range(index, self.len);
+        self.len - 1 - index
+    }
+
+    fn inverse(&self, index: u64) -> u64 {
+        self.forward(index)
+    }
+}
+
+/// A [`Permutation`] that's written out as a table, for testing with an
+/// exact order.
+///
+/// ```
+/// # use perfect_rand::{Permutation, TablePermutation};
+/// let permutation = TablePermutation::new(vec![2, 0, 1]).unwrap();
+/// assert_eq!(permutation.forward(0), 2);
+/// assert_eq!(permutation.inverse(2), 0);
+///
+/// assert!(TablePermutation::new(vec![0, 0, 1]).is_err());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TablePermutation {
+    forward: Vec<u64>,
+    inverse: Vec<u64>,
+}
+
+impl TablePermutation {
+    /// A permutation that maps each index `i` to `table[i]`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PerfectRngError::NotAPermutation`] if the table doesn't have
+    /// every index from `0` to its length exactly once.
+    pub fn new(table: Vec<u64>) -> Result<Self, PerfectRngError> {
+        const UNSET: u64 = u64::MAX;
+
+        let mut inverse = vec![UNSET; table.len()];
+        for (i, &c) in table.iter().enumerate() {
+            let slot = usize::try_from(c)
+                .ok()
+                .and_then(|c| inverse.get_mut(c))
+                .ok_or(PerfectRngError::NotAPermutation)?;
+            if *slot != UNSET {
+                return Err(PerfectRngError::NotAPermutation);
+            }
+            *slot = i as u64;
+        }
+        Ok(TablePermutation {
+            forward: table,
+            inverse,
+        })
+    }
+}
+
+impl Permutation for TablePermutation {
+    fn len(&self) -> u64 {
+        self.forward.len() as u64
+    }
+
+    fn forward(&self, index: u64) -> u64 {
+        assert_in_range(index, self.len());
+        self.forward[index as usize]
+    }
+
+    fn inverse(&self, index: u64) -> u64 {
+        assert_in_range(index, self.len());
+        self.inverse[index as usize]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{IdentityPermutation, Inverted, Permutation, ReversePermutation, TablePermutation};
+    use crate::{Backend, PerfectRng, PerfectRng16, PerfectRngError};
+
+    fn check(permutation: &impl Permutation) {
+        let mut seen = vec![false; permutation.len() as usize];
+        for i in 0..permutation.len() {
+            let c = permutation.forward(i);
+            assert!(!std::mem::replace(&mut seen[c as usize], true));
+            assert_eq!(permutation.inverse(c), i);
+        }
+    }
+
+    #[test]
+    fn doubles() {
+        check(&IdentityPermutation::new(10));
+        check(&ReversePermutation::new(10));
+        check(&TablePermutation::new(vec![3, 1, 0, 2]).unwrap());
+        assert!(IdentityPermutation::new(0).is_empty());
+        assert!(ReversePermutation::new(0).is_empty());
+        assert!(TablePermutation::new(Vec::new()).unwrap().is_empty());
+        assert_eq!(ReversePermutation::new(3).forward(1), 1);
+    }
+
+    #[test]
+    fn perfect_rng() {
+        let backends = [
+            Backend::SipHash,
+            Backend::Aes,
+            Backend::ChaCha,
+            Backend::Philox,
+            Backend::Blackrock2,
+            Backend::SwapOrNot,
+            Backend::Thorp,
+            Backend::EvenMansour,
+        ];
+        #[cfg(feature = "fpe")]
+        let backends = backends.into_iter().chain([Backend::Ff1, Backend::Ff3_1]);
+
+        for backend in backends {
+            let randomizer =
+                PerfectRng::with_key_inclusive(100..=1099u64, [1, 2], 4).with_backend(backend);
+            check(&randomizer);
+            for i in 0..1000 {
+                assert_eq!(randomizer.forward(i) + 100, randomizer.shuffle(i + 100));
+            }
+        }
+
+        // through a reference and a trait object too
+        let randomizer = PerfectRng16::with_key(1000, [1, 2], 4);
+        check(&&randomizer);
+        check(&(Box::new(randomizer) as Box<dyn Permutation>));
+
+        let randomizer = PerfectRng::with_key_inclusive(0..=u64::MAX, [1, 2], 4);
+        assert_eq!(randomizer.len(), u64::MAX);
+        assert_eq!(randomizer.inverse(randomizer.forward(u64::MAX)), u64::MAX);
+    }
+
+    #[test]
+    #[should_panic(expected = "less than the length")]
+    fn perfect_rng_out_of_range() {
+        let _ = PerfectRng16::from_range(1000).forward(1000);
+    }
+
+    #[test]
+    fn inverted_and_composed() {
+        let table = TablePermutation::new(vec![2, 0, 3, 1]).unwrap();
+        let inverted = table.clone().inverted();
+        check(&inverted);
+        assert_eq!(inverted.forward(2), 0);
+        assert_eq!(Inverted(inverted.clone()).forward(0), 2);
+
+        // reversing twice puts everything back
+        let reversed = ReversePermutation::new(4).compose(ReversePermutation::new(4));
+        assert!((0..4).all(|i| reversed.forward(i) == i));
+
+        let composed = table.clone().compose(ReversePermutation::new(4));
+        check(&composed);
+        assert_eq!(composed.forward(0), 1);
+        assert_eq!(composed.inverse(1), 0);
+
+        // a permutation followed by its inverse is the identity
+        let randomizer = PerfectRng::with_key(1000u64, [1, 2], 4);
+        let identity = (&randomizer).compose((&randomizer).inverted());
+        assert!((0..1000).all(|i| identity.forward(i) == i));
+    }
+
+    #[test]
+    #[should_panic(expected = "the same length")]
+    fn compose_different_lengths() {
+        let _ = IdentityPermutation::new(3).compose(IdentityPermutation::new(4));
+    }
+
+    #[test]
+    fn invalid_table() {
+        for table in [vec![0, 0], vec![1, 2], vec![u64::MAX]] {
+            assert_eq!(
+                TablePermutation::new(table),
+                Err(PerfectRngError::NotAPermutation)
+            );
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "less than the length")]
+    fn out_of_range() {
+        let _ = ReversePermutation::new(10).forward(10);
+    }
+}
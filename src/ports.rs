@@ -0,0 +1,367 @@
+use std::{fmt, iter::FusedIterator, ops::RangeInclusive, str::FromStr};
+
+use crate::{targets::RangeList, PerfectRng, PerfectRngError, ShuffleCursor};
+
+/// The transport protocol of a [`Port`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Protocol {
+    /// TCP, which is used for ports without a prefix.
+    #[default]
+    Tcp,
+    /// UDP, written with a `U:` prefix.
+    Udp,
+    /// SCTP, written with an `S:` prefix.
+    Sctp,
+}
+
+impl Protocol {
+    const ALL: [Protocol; 3] = [Protocol::Tcp, Protocol::Udp, Protocol::Sctp];
+
+    fn from_index(index: u32) -> Self {
+        Self::ALL[index as usize]
+    }
+}
+
+/// A port number along with its protocol.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Port {
+    /// The transport protocol.
+    pub protocol: Protocol,
+    /// The port number.
+    pub number: u16,
+}
+
+impl Port {
+    /// A TCP port.
+    #[must_use]
+    pub const fn tcp(number: u16) -> Self {
+        Port {
+            protocol: Protocol::Tcp,
+            number,
+        }
+    }
+
+    /// A UDP port.
+    #[must_use]
+    pub const fn udp(number: u16) -> Self {
+        Port {
+            protocol: Protocol::Udp,
+            number,
+        }
+    }
+
+    /// The position of the port in the space of every port of every
+    /// protocol.
+    fn key(self) -> u32 {
+        (self.protocol as u32) << 16 | u32::from(self.number)
+    }
+
+    fn from_key(key: u32) -> Self {
+        Port {
+            protocol: Protocol::from_index(key >> 16),
+            number: key as u16,
+        }
+    }
+}
+
+impl fmt::Display for Port {
+    /// Formats the port the same way it's parsed in a [`PortSet`], like `80`
+    /// or `U:53`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.protocol {
+            Protocol::Tcp => write!(f, "{}", self.number),
+            Protocol::Udp => write!(f, "U:{}", self.number),
+            Protocol::Sctp => write!(f, "S:{}", self.number),
+        }
+    }
+}
+
+/// A set of ports, like the ports that a scan targets.
+///
+/// The ports are laid out in a dense index space, TCP then UDP then SCTP and
+/// in ascending order within each protocol, so they can be shuffled by a
+/// [`PerfectRng`] without listing every port in memory.
+///
+/// It's usually parsed from a port specification in the same format as
+/// masscan and nmap, which is a list of ports and inclusive ranges separated
+/// by commas. Each one can have a `T:`, `U:`, or `S:` prefix for TCP, UDP, or
+/// SCTP, and is TCP if it doesn't have a prefix.
+///
+/// ```
+/// # use perfect_rand::{Port, PortSet};
+/// let ports = "80,443,8000-8100,U:53".parse::<PortSet>().unwrap();
+/// assert_eq!(ports.len(), 104);
+/// assert!(ports.contains(Port::udp(53)));
+/// assert!(!ports.contains(Port::tcp(53)));
+///
+/// for port in ports.shuffled().unwrap() {
+///     println!("{port}");
+/// }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct PortSet {
+    ranges: RangeList,
+}
+
+impl PortSet {
+    /// Create an empty set.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add every port in a range. Nothing is added if the range is empty.
+    pub fn add_range(&mut self, protocol: Protocol, range: RangeInclusive<u16>) {
+        let (start, end) = range.into_inner();
+        self.ranges.insert(
+            Port {
+                protocol,
+                number: start,
+            }
+            .key()..=Port {
+                protocol,
+                number: end,
+            }
+            .key(),
+        );
+    }
+
+    /// Add a single port.
+    pub fn add(&mut self, port: Port) {
+        self.add_range(port.protocol, port.number..=port.number);
+    }
+
+    /// The number of ports in the set.
+    #[must_use]
+    pub fn len(&self) -> u32 {
+        self.ranges
+            .0
+            .iter()
+            .map(|range| range.end() - range.start() + 1)
+            .sum()
+    }
+
+    /// Whether the set has no ports.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.ranges.0.is_empty()
+    }
+
+    /// Whether the port is in the set.
+    #[must_use]
+    pub fn contains(&self, port: Port) -> bool {
+        self.ranges.contains(port.key())
+    }
+
+    /// Get the port at a position in the dense index space, or `None` if the
+    /// index isn't less than [`PortSet::len`].
+    ///
+    /// ```
+    /// # use perfect_rand::{Port, PortSet};
+    /// let ports = "U:53,22,80".parse::<PortSet>().unwrap();
+    /// assert_eq!(ports.get(1), Some(Port::tcp(80)));
+    /// assert_eq!(ports.get(2), Some(Port::udp(53)));
+    /// assert_eq!(ports.get(3), None);
+    /// ```
+    #[must_use]
+    pub fn get(&self, mut index: u32) -> Option<Port> {
+        for range in &self.ranges.0 {
+            let len = range.end() - range.start() + 1;
+            if index < len {
+                return Some(Port::from_key(range.start() + index));
+            }
+            index -= len;
+        }
+        None
+    }
+
+    /// Reverse [`PortSet::get`], getting the position of a port in the dense
+    /// index space. Returns `None` if the port isn't in the set.
+    #[must_use]
+    pub fn index_of(&self, port: Port) -> Option<u32> {
+        let key = port.key();
+        let mut index = 0;
+        for range in &self.ranges.0 {
+            if range.contains(&key) {
+                return Some(index + (key - range.start()));
+            }
+            index += range.end() - range.start() + 1;
+        }
+        None
+    }
+
+    /// Iterate over every port in the order of the dense index space.
+    pub fn iter(&self) -> impl Iterator<Item = Port> + '_ {
+        self.ranges.0.iter().cloned().flatten().map(Port::from_key)
+    }
+
+    /// Iterate over every port in shuffled order, with a random key and
+    /// default rounds.
+    ///
+    /// This returns an error if the set is empty.
+    pub fn shuffled(&self) -> Result<ShuffledPorts, PerfectRngError> {
+        self.shuffled_with_key(rand::random(), 4)
+    }
+
+    /// Iterate over every port in shuffled order, keyed like
+    /// [`PerfectRng::with_key`]. See [`PortSet::shuffled`].
+    pub fn shuffled_with_key(
+        &self,
+        key: [u32; 2],
+        rounds: usize,
+    ) -> Result<ShuffledPorts, PerfectRngError> {
+        let rng = PerfectRng::try_with_key(self.len(), key, rounds)?;
+        Ok(ShuffledPorts {
+            ports: self.clone(),
+            cursor: ShuffleCursor::new(rng),
+        })
+    }
+}
+
+impl FromStr for PortSet {
+    type Err = PerfectRngError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut ports = PortSet::new();
+        for spec in s.split(',').map(str::trim).filter(|spec| !spec.is_empty()) {
+            let (protocol, range) = match spec.split_once(':') {
+                Some((prefix, range)) => {
+                    let protocol = match prefix.trim() {
+                        "T" | "t" => Protocol::Tcp,
+                        "U" | "u" => Protocol::Udp,
+                        "S" | "s" => Protocol::Sctp,
+                        _ => return Err(PerfectRngError::InvalidPortSpec),
+                    };
+                    (protocol, range)
+                }
+                None => (Protocol::Tcp, spec),
+            };
+            let (start, end) = match range.split_once('-') {
+                Some((start, end)) => (parse_port(start)?, parse_port(end)?),
+                None => (parse_port(range)?, parse_port(range)?),
+            };
+            if start > end {
+                return Err(PerfectRngError::InvalidPortSpec);
+            }
+            ports.add_range(protocol, start..=end);
+        }
+        Ok(ports)
+    }
+}
+
+fn parse_port(s: &str) -> Result<u16, PerfectRngError> {
+    s.trim()
+        .parse()
+        .map_err(|_| PerfectRngError::InvalidPortSpec)
+}
+
+impl Extend<Port> for PortSet {
+    fn extend<I: IntoIterator<Item = Port>>(&mut self, iter: I) {
+        for port in iter {
+            self.add(port);
+        }
+    }
+}
+
+impl FromIterator<Port> for PortSet {
+    fn from_iter<I: IntoIterator<Item = Port>>(iter: I) -> Self {
+        let mut ports = PortSet::new();
+        ports.extend(iter);
+        ports
+    }
+}
+
+/// An iterator over every port in a [`PortSet`], in shuffled order.
+///
+/// This is created by [`PortSet::shuffled`].
+#[derive(Debug)]
+pub struct ShuffledPorts {
+    ports: PortSet,
+    cursor: ShuffleCursor<u32>,
+}
+
+impl ShuffledPorts {
+    /// The [`PerfectRng`] that shuffles the dense index space of the ports.
+    #[must_use]
+    pub fn rng(&self) -> &PerfectRng<u32> {
+        self.cursor.rng()
+    }
+}
+
+impl Iterator for ShuffledPorts {
+    type Item = Port;
+
+    #[inline]
+    fn next(&mut self) -> Option<Port> {
+        let index = self.cursor.next()?;
+        self.ports.get(index)
+    }
+}
+
+impl FusedIterator for ShuffledPorts {}
+
+#[cfg(test)]
+mod tests {
+    use super::{Port, PortSet, Protocol};
+    use crate::PerfectRngError;
+
+    #[test]
+    fn parse() {
+        let ports = " 80, 443,8000-8010,U:53 ,u:50-60,S:1, 79-81,T:22"
+            .parse::<PortSet>()
+            .unwrap();
+        assert_eq!(ports.len(), 28);
+        assert!(ports.iter().eq([22, 79, 80, 81, 443]
+            .into_iter()
+            .chain(8000..=8010)
+            .map(Port::tcp)
+            .chain((50..=60).map(Port::udp))
+            .chain([Port {
+                protocol: Protocol::Sctp,
+                number: 1
+            }])));
+        assert_eq!(
+            ports
+                .iter()
+                .map(|port| port.to_string())
+                .collect::<Vec<_>>()[16..19],
+            ["U:50", "U:51", "U:52"]
+        );
+
+        for invalid in ["80-79", "65536", "X:80", "80-", "http"] {
+            assert_eq!(
+                invalid.parse::<PortSet>().unwrap_err(),
+                PerfectRngError::InvalidPortSpec
+            );
+        }
+        assert!("".parse::<PortSet>().unwrap().is_empty());
+    }
+
+    #[test]
+    fn index() {
+        let ports = "0,65535,U:0-1,U:65535".parse::<PortSet>().unwrap();
+        for (i, port) in ports.iter().enumerate() {
+            assert_eq!(ports.get(i as u32), Some(port));
+            assert_eq!(ports.index_of(port), Some(i as u32));
+        }
+        assert_eq!(ports.get(5), None);
+        assert_eq!(ports.index_of(Port::udp(2)), None);
+    }
+
+    #[test]
+    fn shuffled() {
+        let ports = "1-1000,U:1-1000".parse::<PortSet>().unwrap();
+        let mut shuffled = ports
+            .shuffled_with_key([1, 2], 4)
+            .unwrap()
+            .collect::<Vec<_>>();
+        assert!(!shuffled.iter().eq(ports.iter().collect::<Vec<_>>().iter()));
+        shuffled.sort();
+        assert!(shuffled.into_iter().eq(ports.iter()));
+
+        assert_eq!(
+            PortSet::new().shuffled().unwrap_err(),
+            PerfectRngError::ZeroRange
+        );
+    }
+}
@@ -0,0 +1,179 @@
+use std::{
+    iter::FusedIterator,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        mpsc::{self, Receiver, SyncSender},
+        Arc,
+    },
+    thread::{self, JoinHandle},
+};
+
+use crate::{PerfectRng, UnsignedInt};
+
+/// How many chunks each thread can have waiting in the channel before it
+/// blocks.
+const CHUNKS_PER_THREAD: usize = 2;
+
+/// Chunks of shuffled values that are generated by background threads.
+///
+/// Every chunk is a contiguous part of the permutation, but the chunks can
+/// arrive in any order. The threads stop when the permutation is finished or
+/// the `Producer` is dropped.
+///
+/// This is created by [`PerfectRng::spawn_producer`].
+#[derive(Debug)]
+pub struct Producer<T: UnsignedInt = u64> {
+    receiver: Receiver<ShuffledChunk<T>>,
+    rng: Arc<PerfectRng<T>>,
+    threads: Vec<JoinHandle<()>>,
+}
+
+/// A chunk of shuffled values from a [`Producer`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ShuffledChunk<T> {
+    /// The position in the permutation of the first value, so
+    /// `values[i]` is the shuffled value at position `start + i`.
+    pub start: T,
+    /// The shuffled values.
+    pub values: Vec<T>,
+}
+
+impl<T: UnsignedInt> PerfectRng<T> {
+    /// Generate the permutation in chunks of `chunk_size` values on one
+    /// thread per CPU, for scanners that want target generation off of the
+    /// thread that sends packets. This only uses threads from the standard
+    /// library, unlike `par_iter` which needs the `rayon` feature.
+    ///
+    /// The channel is bounded, so the threads wait for chunks to be taken
+    /// instead of filling up memory.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `chunk_size` is 0.
+    ///
+    /// ```
+    /// # use perfect_rand::PerfectRng;
+    /// let producer = PerfectRng::from_range(100_000u64).spawn_producer(1000);
+    /// let mut values = producer.flat_map(|chunk| chunk.values).collect::<Vec<_>>();
+    /// values.sort_unstable();
+    /// assert!(values.into_iter().eq(0..100_000));
+    /// ```
+    #[must_use]
+    pub fn spawn_producer(self, chunk_size: usize) -> Producer<T> {
+        let threads = thread::available_parallelism().map_or(1, |n| n.get());
+        self.spawn_producer_with_threads(chunk_size, threads)
+    }
+
+    /// Like [`PerfectRng::spawn_producer`], but with a specific number of
+    /// threads.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `chunk_size` or `threads` is 0.
+    #[must_use]
+    pub fn spawn_producer_with_threads(self, chunk_size: usize, threads: usize) -> Producer<T> {
+        assert!(chunk_size > 0, "the chunk size must not be 0");
+        assert!(threads > 0, "there must be at least one thread");
+
+        let rng = Arc::new(self);
+        let next_chunk = Arc::new(AtomicU64::new(0));
+        let (sender, receiver) = mpsc::sync_channel(threads * CHUNKS_PER_THREAD);
+        let threads = (0..threads)
+            .map(|_| {
+                let (rng, next_chunk, sender) = (rng.clone(), next_chunk.clone(), sender.clone());
+                thread::spawn(move || produce(&rng, &next_chunk, chunk_size, &sender))
+            })
+            .collect();
+        Producer {
+            receiver,
+            rng,
+            threads,
+        }
+    }
+}
+
+/// Send chunks until there are none left or the receiver is dropped.
+fn produce<T: UnsignedInt>(
+    rng: &PerfectRng<T>,
+    next_chunk: &AtomicU64,
+    chunk_size: usize,
+    sender: &SyncSender<ShuffledChunk<T>>,
+) {
+    let len = rng.max.as_u128() + 1;
+    loop {
+        let start = u128::from(next_chunk.fetch_add(1, Ordering::Relaxed)) * chunk_size as u128;
+        if start >= len {
+            return;
+        }
+        let start = T::from_u128(start);
+        let mut values = vec![T::ZERO; (len - start.as_u128()).min(chunk_size as u128) as usize];
+        rng.shuffle_n(start + rng.offset, &mut values);
+        if sender.send(ShuffledChunk { start, values }).is_err() {
+            return;
+        }
+    }
+}
+
+impl<T: UnsignedInt> Producer<T> {
+    /// The [`PerfectRng`] that the values come from.
+    #[must_use]
+    pub fn rng(&self) -> &PerfectRng<T> {
+        &self.rng
+    }
+}
+
+impl<T: UnsignedInt> Iterator for Producer<T> {
+    type Item = ShuffledChunk<T>;
+
+    fn next(&mut self) -> Option<ShuffledChunk<T>> {
+        match self.receiver.recv() {
+            Ok(chunk) => Some(chunk),
+            Err(_) => {
+                // every thread has finished, so a panic in one of them should
+                // be passed on instead of ending the permutation early
+                for thread in self.threads.drain(..) {
+                    if let Err(panic) = thread.join() {
+                        std::panic::resume_unwind(panic);
+                    }
+                }
+                None
+            }
+        }
+    }
+}
+
+impl<T: UnsignedInt> FusedIterator for Producer<T> {}
+
+#[cfg(test)]
+mod tests {
+    use crate::PerfectRng;
+
+    #[test]
+    fn every_value() {
+        let randomizer = || PerfectRng::with_key_inclusive(10..=10_009u32, [1, 2], 4);
+        let mut chunks = randomizer()
+            .spawn_producer_with_threads(64, 3)
+            .collect::<Vec<_>>();
+        assert_eq!(chunks.len(), 157);
+        chunks.sort_by_key(|chunk| chunk.start);
+        assert_eq!(chunks.last().unwrap().values.len(), 10_000 % 64);
+        let values = chunks.into_iter().flat_map(|chunk| chunk.values);
+        assert!(values.eq(randomizer().iter()));
+    }
+
+    #[test]
+    fn full_domain() {
+        let producer = PerfectRng::with_key_inclusive(0..=u16::MAX, [1, 2], 4).spawn_producer(1000);
+        assert_eq!(
+            producer.map(|chunk| chunk.values.len()).sum::<usize>(),
+            65536
+        );
+    }
+
+    #[test]
+    fn drop_early() {
+        let mut producer = PerfectRng::from_range(u64::MAX).spawn_producer_with_threads(10, 2);
+        assert_eq!(producer.next().unwrap().values.len(), 10);
+        drop(producer);
+    }
+}
@@ -0,0 +1,215 @@
+use std::{iter::FusedIterator, ops::Range};
+
+use crate::{PerfectRng, PerfectRngError, ShuffledIter};
+
+/// A permutation over every `(address, port)` pair in the cross product of an
+/// address range and a list of ports.
+///
+/// Like masscan, the address is the fastest-changing part of the index, so
+/// the ports of a single host are spread out over the whole scan instead of
+/// being hit one after another.
+///
+/// ```
+/// # use perfect_rand::ProductDomain;
+/// let domain = ProductDomain::new(0..256, vec![22, 80, 443]).unwrap();
+/// assert_eq!(domain.len(), 768);
+///
+/// for (address, port) in domain.iter() {
+///     assert!(address < 256);
+///     assert!([22, 80, 443].contains(&port));
+/// }
+/// ```
+#[derive(Debug)]
+pub struct ProductDomain {
+    addresses: Range<u64>,
+    ports: Vec<u16>,
+    rng: PerfectRng<u64>,
+}
+
+impl ProductDomain {
+    /// Create a domain with a random key and default rounds.
+    ///
+    /// This returns an error if either the addresses or the ports are empty,
+    /// or if the number of pairs doesn't fit in a `u64`.
+    pub fn new(addresses: Range<u64>, ports: Vec<u16>) -> Result<Self, PerfectRngError> {
+        Self::with_key(addresses, ports, rand::random(), 4)
+    }
+
+    /// Create a domain keyed like [`PerfectRng::with_key`]. See
+    /// [`ProductDomain::new`].
+    pub fn with_key(
+        addresses: Range<u64>,
+        ports: Vec<u16>,
+        key: [u64; 2],
+        rounds: usize,
+    ) -> Result<Self, PerfectRngError> {
+        let address_count = addresses.end.saturating_sub(addresses.start);
+        let len = address_count
+            .checked_mul(ports.len() as u64)
+            .ok_or(PerfectRngError::DomainTooLarge)?;
+        let rng = PerfectRng::try_with_key(len, key, rounds)?;
+        Ok(ProductDomain {
+            addresses,
+            ports,
+            rng,
+        })
+    }
+
+    /// The number of `(address, port)` pairs.
+    #[must_use]
+    pub fn len(&self) -> u64 {
+        self.rng.max + 1
+    }
+
+    /// Always `false`, since a domain can't be created without any values.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        false
+    }
+
+    /// Get the pair at a position in the shuffled domain.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` isn't less than [`ProductDomain::len`].
+    #[must_use]
+    pub fn shuffle(&self, index: u64) -> (u64, u16) {
+        self.pair_at(self.rng.shuffle(index))
+    }
+
+    /// Reverse [`ProductDomain::shuffle`], getting the position of a pair in
+    /// the shuffled domain. Returns `None` if the pair isn't in the domain.
+    ///
+    /// ```
+    /// # use perfect_rand::ProductDomain;
+    /// let domain = ProductDomain::new(100..200, vec![80, 443]).unwrap();
+    /// let index = domain.unshuffle((150, 443)).unwrap();
+    /// assert_eq!(domain.shuffle(index), (150, 443));
+    /// assert_eq!(domain.unshuffle((150, 22)), None);
+    /// ```
+    #[must_use]
+    pub fn unshuffle(&self, (address, port): (u64, u16)) -> Option<u64> {
+        if !self.addresses.contains(&address) {
+            return None;
+        }
+        let port_index = self.ports.iter().position(|&p| p == port)? as u64;
+        let address_count = self.addresses.end - self.addresses.start;
+        let index = port_index * address_count + (address - self.addresses.start);
+        Some(self.rng.unshuffle(index))
+    }
+
+    /// Iterate over every pair in shuffled order.
+    #[must_use]
+    pub fn iter(&self) -> ProductDomainIter<'_> {
+        ProductDomainIter {
+            domain: self,
+            iter: self.rng.iter(),
+        }
+    }
+
+    /// The [`PerfectRng`] that shuffles the index space.
+    #[must_use]
+    pub fn rng(&self) -> &PerfectRng<u64> {
+        &self.rng
+    }
+
+    fn pair_at(&self, index: u64) -> (u64, u16) {
+        let address_count = self.addresses.end - self.addresses.start;
+        let address = self.addresses.start + index % address_count;
+        let port = self.ports[(index / address_count) as usize];
+        (address, port)
+    }
+}
+
+impl<'a> IntoIterator for &'a ProductDomain {
+    type Item = (u64, u16);
+    type IntoIter = ProductDomainIter<'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// An iterator over every pair in a [`ProductDomain`], in shuffled order.
+///
+/// This is created by [`ProductDomain::iter`].
+#[derive(Debug, Clone)]
+pub struct ProductDomainIter<'a> {
+    domain: &'a ProductDomain,
+    iter: ShuffledIter<'a, u64>,
+}
+
+impl ProductDomainIter<'_> {
+    /// Jump to a position in the shuffled domain. See [`ShuffledIter::seek`].
+    pub fn seek(&mut self, index: u64) {
+        self.iter.seek(index);
+    }
+}
+
+impl Iterator for ProductDomainIter<'_> {
+    type Item = (u64, u16);
+
+    #[inline]
+    fn next(&mut self) -> Option<(u64, u16)> {
+        self.iter.next().map(|index| self.domain.pair_at(index))
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+
+    #[inline]
+    fn nth(&mut self, n: usize) -> Option<(u64, u16)> {
+        self.iter.nth(n).map(|index| self.domain.pair_at(index))
+    }
+}
+
+impl DoubleEndedIterator for ProductDomainIter<'_> {
+    #[inline]
+    fn next_back(&mut self) -> Option<(u64, u16)> {
+        self.iter
+            .next_back()
+            .map(|index| self.domain.pair_at(index))
+    }
+}
+
+impl ExactSizeIterator for ProductDomainIter<'_> {}
+
+impl FusedIterator for ProductDomainIter<'_> {}
+
+#[cfg(test)]
+mod tests {
+    use super::ProductDomain;
+    use crate::PerfectRngError;
+
+    #[test]
+    fn covers_every_pair() {
+        let ports = vec![22, 80, 443, 8080];
+        let domain = ProductDomain::with_key(1000..1250, ports.clone(), [1, 2], 4).unwrap();
+
+        let mut pairs = domain.iter().collect::<Vec<_>>();
+        for (i, &pair) in pairs.iter().enumerate() {
+            assert_eq!(domain.shuffle(i as u64), pair);
+            assert_eq!(domain.unshuffle(pair), Some(i as u64));
+        }
+        pairs.sort_unstable();
+        let mut expected = (1000..1250)
+            .flat_map(|address| ports.iter().map(move |&port| (address, port)))
+            .collect::<Vec<_>>();
+        expected.sort_unstable();
+        assert_eq!(pairs, expected);
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(
+            ProductDomain::new(0..10, vec![]).unwrap_err(),
+            PerfectRngError::ZeroRange
+        );
+        assert_eq!(
+            ProductDomain::new(0..u64::MAX, vec![80, 443]).unwrap_err(),
+            PerfectRngError::DomainTooLarge
+        );
+    }
+}
@@ -0,0 +1,462 @@
+//! Statistical tests of how random a permutation looks, with the `quality`
+//! feature.
+//!
+//! These are for comparing round counts and backends on your own ranges
+//! without exporting the outputs to an external test suite like dieharder.
+//! Passing them doesn't mean the permutation is secure, but failing them
+//! means the configuration is too weak for anything that needs the order to
+//! look random.
+//!
+//! ```
+//! use perfect_rand::{quality, PerfectRng};
+//!
+//! let weak = PerfectRng::new(1u64 << 20, 0, 1);
+//! let strong = PerfectRng::new(1u64 << 20, 0, 4);
+//! assert!(quality::avalanche(&weak, 256).max_bias > 0.4);
+//! assert!(quality::avalanche(&strong, 256).max_bias < 0.2);
+//! assert!(quality::serial_correlation(&weak, 10_000).abs() > 0.5);
+//! assert!(quality::serial_correlation(&strong, 10_000).abs() < 0.05);
+//! ```
+
+use std::collections::BTreeMap;
+
+use crate::{PerfectRng, UnsignedInt};
+
+/// The result of [`avalanche`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct Avalanche {
+    /// The average probability that an output bit flips when one input bit
+    /// is flipped. This should be close to 0.5.
+    pub mean: f64,
+    /// The furthest that the probability of any output bit flipping for any
+    /// input bit is from 0.5. This is 0.5 if some output bit never depends
+    /// on some input bit.
+    pub max_bias: f64,
+    /// The number of pairs of inputs that were compared.
+    pub pairs: u64,
+    /// For each bit of the input, starting from the lowest, the average
+    /// fraction of the output bits that flip when it's flipped. These should
+    /// all be close to 0.5, and ones that aren't show which bits the rounds
+    /// don't mix in well enough.
+    pub by_input_bit: Vec<f64>,
+}
+
+/// The result of [`chi_square`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ChiSquare {
+    /// The chi-square statistic for the number of outputs in each bucket.
+    pub statistic: f64,
+    /// The degrees of freedom, which is one less than the number of buckets.
+    /// The statistic should be close to this.
+    pub degrees_of_freedom: u64,
+}
+
+impl ChiSquare {
+    /// How many standard deviations the statistic is from what's expected,
+    /// using the normal approximation. Values past about 3 or -3 are
+    /// suspicious.
+    #[must_use]
+    pub fn z_score(&self) -> f64 {
+        let df = self.degrees_of_freedom as f64;
+        (self.statistic - df) / (2. * df).sqrt()
+    }
+}
+
+/// Check how much flipping one bit of an input changes the output, for
+/// `samples` inputs spread over the range. Flips that would leave the range
+/// are skipped.
+///
+/// The outputs of ranges that aren't powers of two never have their highest
+/// bits set together, so those bits are a bit biased even for a perfect
+/// permutation.
+///
+/// ```
+/// # use perfect_rand::{quality, PerfectRng};
+/// // after two rounds, the low half of the input isn't mixed in well yet
+/// let result = quality::avalanche(&PerfectRng::new(1u64 << 32, 0, 2), 256);
+/// assert!(result.by_input_bit[..16].iter().all(|&ratio| ratio < 0.4));
+///
+/// let result = quality::avalanche(&PerfectRng::new(1u64 << 32, 0, 3), 256);
+/// assert!(result.by_input_bit.iter().all(|&ratio| (ratio - 0.5).abs() < 0.05));
+/// ```
+///
+/// # Panics
+///
+/// Panics if `samples` is 0.
+#[must_use]
+pub fn avalanche<T: UnsignedInt>(rng: &PerfectRng<T>, samples: u64) -> Avalanche {
+    assert_ne!(samples, 0, "there must be at least one sample");
+    let bits = rng.max.count_bits() as usize;
+    let step = (rng.max.as_u128() / u128::from(samples)).max(1);
+
+    // flips[i][j] is how many times output bit j flipped for input bit i
+    let mut flips = vec![[0u64; 128]; bits];
+    let mut pairs = vec![0u64; bits];
+    for k in 0..samples {
+        let Some(m) = u128::from(k)
+            .checked_mul(step)
+            .filter(|&m| m <= rng.max.as_u128())
+        else {
+            break;
+        };
+        let m = T::from_u128(m);
+        let c = rng.shuffle_index(m);
+        for i in 0..bits {
+            let flipped = m ^ (T::ONE << i as u32);
+            if flipped > rng.max {
+                continue;
+            }
+            let diff = (c ^ rng.shuffle_index(flipped)).as_u128();
+            for (j, count) in flips[i].iter_mut().enumerate().take(bits) {
+                *count += (diff >> j & 1) as u64;
+            }
+            pairs[i] += 1;
+        }
+    }
+
+    let mut total = 0;
+    let mut max_bias: f64 = 0.;
+    let mut by_input_bit = Vec::with_capacity(bits);
+    for (row, &n) in flips.iter().zip(&pairs) {
+        // every bit can be flipped in the first sample, which is 0
+        let row_total = row[..bits].iter().sum::<u64>();
+        by_input_bit.push(row_total as f64 / (n as f64 * bits as f64));
+        for &count in &row[..bits] {
+            max_bias = max_bias.max((count as f64 / n as f64 - 0.5).abs());
+        }
+        total += row_total;
+    }
+    let pairs = pairs.iter().sum::<u64>();
+    Avalanche {
+        mean: if pairs == 0 {
+            0.
+        } else {
+            total as f64 / (pairs as f64 * bits as f64)
+        },
+        max_bias,
+        pairs,
+        by_input_bit,
+    }
+}
+
+/// Put the outputs for the first `samples` positions of the permutation into
+/// `buckets` equally sized buckets over the range, and check how evenly
+/// they're spread. A whole permutation is always perfectly even, so this is
+/// only meaningful when `samples` is much less than the range, and the range
+/// is much more than `buckets`.
+///
+/// # Panics
+///
+/// Panics if `buckets` is less than 2, or `samples` is 0 or more than the
+/// range.
+#[must_use]
+pub fn chi_square<T: UnsignedInt>(rng: &PerfectRng<T>, samples: u64, buckets: u64) -> ChiSquare {
+    assert!(buckets >= 2, "there must be at least two buckets");
+    assert_sample_size(rng, samples);
+
+    let len = rng.max.as_u128() as f64 + 1.;
+    let mut counts = vec![0u64; buckets as usize];
+    for m in 0..samples {
+        let c = rng.shuffle_index(T::from_u128(u128::from(m))).as_u128() as f64;
+        let bucket = ((c / len * buckets as f64) as usize).min(buckets as usize - 1);
+        counts[bucket] += 1;
+    }
+
+    let expected = samples as f64 / buckets as f64;
+    ChiSquare {
+        statistic: counts
+            .iter()
+            .map(|&count| (count as f64 - expected).powi(2) / expected)
+            .sum(),
+        degrees_of_freedom: buckets - 1,
+    }
+}
+
+/// The correlation between each of the first `samples` outputs of the
+/// permutation and the next one, like Knuth's serial correlation test. This
+/// should be close to 0, within about `2 / sqrt(samples)`.
+///
+/// # Panics
+///
+/// Panics if `samples` is less than 2 or more than the range.
+#[must_use]
+pub fn serial_correlation<T: UnsignedInt>(rng: &PerfectRng<T>, samples: u64) -> f64 {
+    assert!(samples >= 2, "there must be at least two samples");
+    assert_sample_size(rng, samples);
+
+    let len = rng.max.as_u128() as f64 + 1.;
+    let u = |m: u64| rng.shuffle_index(T::from_u128(u128::from(m))).as_u128() as f64 / len;
+    let first = u(0);
+    let (mut sum, mut sum_squares, mut sum_products) = (0., 0., 0.);
+    let mut current = first;
+    for m in 0..samples {
+        // the last output is paired with the first, so every one is used
+        // twice like in Knuth's version
+        let next = if m + 1 == samples { first } else { u(m + 1) };
+        sum += current;
+        sum_squares += current * current;
+        sum_products += current * next;
+        current = next;
+    }
+
+    let n = samples as f64;
+    let denominator = n * sum_squares - sum * sum;
+    if denominator == 0. {
+        return 0.;
+    }
+    (n * sum_products - sum * sum) / denominator
+}
+
+/// The cycle structure of a whole permutation, from [`cycles`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cycles {
+    /// How many cycles there are of each length. A fixed point is a cycle of
+    /// length 1.
+    pub lengths: BTreeMap<u64, u64>,
+}
+
+impl Cycles {
+    /// The total number of cycles.
+    #[must_use]
+    pub fn count(&self) -> u64 {
+        self.lengths.values().sum()
+    }
+
+    /// The length of the longest cycle.
+    #[must_use]
+    pub fn longest(&self) -> u64 {
+        self.lengths.keys().next_back().copied().unwrap_or(0)
+    }
+
+    /// The number of inputs that are shuffled to themselves.
+    #[must_use]
+    pub fn fixed_points(&self) -> u64 {
+        self.lengths.get(&1).copied().unwrap_or(0)
+    }
+
+    /// The average number of cycles in a random permutation of the same
+    /// size, which is the harmonic number of the range, or about `ln(range) +
+    /// 0.577`.
+    #[must_use]
+    pub fn expected_count(&self) -> f64 {
+        let len = self
+            .lengths
+            .iter()
+            .map(|(len, count)| len * count)
+            .sum::<u64>();
+        (1..=len).map(|k| 1. / k as f64).sum()
+    }
+}
+
+/// Follow every cycle of the permutation to find how many there are and how
+/// long they are. A random permutation has about `ln(range)` cycles, one
+/// fixed point on average, and a longest cycle of about 62% of the range.
+///
+/// This shuffles every value in the range once and needs a bit of memory for
+/// each, so it's only for small ranges. Use [`fixed_points`] for big ones.
+///
+/// ```
+/// # use perfect_rand::{quality, PerfectRng};
+/// let cycles = quality::cycles(&PerfectRng::with_key(1000u64, [1, 2], 4));
+/// assert_eq!(cycles.lengths.iter().map(|(len, count)| len * count).sum::<u64>(), 1000);
+/// assert!(cycles.longest() <= 1000);
+/// ```
+///
+/// # Panics
+///
+/// Panics if the range doesn't fit in a `usize`.
+#[must_use]
+pub fn cycles<T: UnsignedInt>(rng: &PerfectRng<T>) -> Cycles {
+    let len = usize::try_from(rng.max.as_u128())
+        .ok()
+        .and_then(|max| max.checked_add(1))
+        .expect("the range is too big to find every cycle");
+
+    let mut seen = vec![0u64; len.div_ceil(64)];
+    let mut lengths = BTreeMap::new();
+    for start in 0..len {
+        if seen[start / 64] >> (start % 64) & 1 != 0 {
+            continue;
+        }
+        let mut cycle_len = 0;
+        let mut m = start;
+        loop {
+            seen[m / 64] |= 1 << (m % 64);
+            cycle_len += 1;
+            m = rng.shuffle_index(T::from_usize(m)).as_u128() as usize;
+            if m == start {
+                break;
+            }
+        }
+        *lengths.entry(cycle_len).or_insert(0) += 1;
+    }
+    Cycles { lengths }
+}
+
+/// The result of [`fixed_points`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FixedPoints {
+    /// The number of inputs that were checked.
+    pub samples: u64,
+    /// How many of them were shuffled to themselves.
+    pub found: u64,
+    /// The chance of an input being a fixed point in a random permutation of
+    /// the same size, which is one over the range.
+    pub expected_probability: f64,
+}
+
+impl FixedPoints {
+    /// The fraction of the checked inputs that were fixed points, which
+    /// estimates the chance of any input being one.
+    #[must_use]
+    pub fn probability(&self) -> f64 {
+        self.found as f64 / self.samples as f64
+    }
+}
+
+/// Estimate how likely an input is to be shuffled to itself by checking
+/// `samples` inputs spread over the range, for ranges that are too big for
+/// [`cycles`]. A random permutation has one fixed point on average, so a
+/// probability much higher than [`FixedPoints::expected_probability`] means
+/// the permutation leaves too much alone.
+///
+/// ```
+/// # use perfect_rand::{quality, PerfectRng};
+/// let result = quality::fixed_points(&PerfectRng::with_key(1u64 << 40, [1, 2], 4), 10_000);
+/// assert_eq!(result.samples, 10_000);
+/// assert_eq!(result.found, 0);
+/// ```
+///
+/// # Panics
+///
+/// Panics if `samples` is 0 or more than the range.
+#[must_use]
+pub fn fixed_points<T: UnsignedInt>(rng: &PerfectRng<T>, samples: u64) -> FixedPoints {
+    assert_sample_size(rng, samples);
+
+    let step = (rng.max.as_u128() / u128::from(samples)).max(1);
+    let found = (0..u128::from(samples))
+        .map(|k| T::from_u128(k * step))
+        .filter(|&m| rng.shuffle_index(m) == m)
+        .count() as u64;
+    FixedPoints {
+        samples,
+        found,
+        expected_probability: 1. / (rng.max.as_u128() as f64 + 1.),
+    }
+}
+
+fn assert_sample_size<T: UnsignedInt>(rng: &PerfectRng<T>, samples: u64) {
+    assert!(
+        samples != 0 && u128::from(samples - 1) <= rng.max.as_u128(),
+        "the number of samples must be at most the range"
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{avalanche, chi_square, cycles, fixed_points, serial_correlation};
+    use crate::{Backend, PerfectRng, RoundFunction};
+
+    #[test]
+    fn good_permutations_pass() {
+        for backend in [Backend::SipHash, Backend::Aes, Backend::Philox] {
+            let randomizer = PerfectRng::with_key(1u64 << 32, [1, 2], 4).with_backend(backend);
+            let result = avalanche(&randomizer, 512);
+            assert!((result.mean - 0.5).abs() < 0.01, "{backend:?} {result:?}");
+            assert!(result.max_bias < 0.1, "{backend:?} {result:?}");
+            assert_eq!(result.pairs, 512 * 32);
+            assert_eq!(result.by_input_bit.len(), 32);
+
+            let result = chi_square(&randomizer, 100_000, 100);
+            assert!(result.z_score().abs() < 4., "{backend:?} {result:?}");
+            assert!(serial_correlation(&randomizer, 100_000).abs() < 0.01);
+        }
+    }
+
+    #[test]
+    fn weak_permutations_fail() {
+        let randomizer = PerfectRng::with_key(1u64 << 32, [1, 2], 1);
+        assert!(avalanche(&randomizer, 512).max_bias > 0.4);
+        // one round only changes the low half, so the outputs of
+        // consecutive inputs all land in the same bucket
+        assert!(chi_square(&randomizer, 100_000, 100).z_score() > 100.);
+        assert!(serial_correlation(&randomizer, 100_000) > 0.9);
+    }
+
+    #[test]
+    fn small_and_full_ranges() {
+        let randomizer = PerfectRng::with_key_inclusive(0..=u128::MAX, [1, 2], 4);
+        let result = avalanche(&randomizer, 16);
+        assert!((result.mean - 0.5).abs() < 0.05, "{result:?}");
+        assert_eq!(result.pairs, 16 * 128);
+        let randomizer = PerfectRng::with_key(3u64, [1, 2], 4);
+        let _ = avalanche(&randomizer, 10);
+        let _ = serial_correlation(&randomizer, 3);
+        assert_eq!(chi_square(&randomizer, 3, 3).statistic, 0.);
+    }
+
+    #[test]
+    fn cycle_structure() {
+        let mut total_cycles = 0;
+        let mut total_fixed_points = 0;
+        for seed in 0..100 {
+            let result = cycles(&PerfectRng::new(1000u64, seed, 4));
+            assert_eq!(
+                result
+                    .lengths
+                    .iter()
+                    .map(|(len, count)| len * count)
+                    .sum::<u64>(),
+                1000
+            );
+            total_cycles += result.count();
+            total_fixed_points += result.fixed_points();
+        }
+        // a random permutation of 1000 has about 7.5 cycles and one fixed
+        // point on average
+        let expected = cycles(&PerfectRng::new(1000u64, 0, 4)).expected_count();
+        assert!((expected - 7.485).abs() < 0.001, "{expected}");
+        assert!((600..900).contains(&total_cycles), "{total_cycles}");
+        assert!(
+            (50..150).contains(&total_fixed_points),
+            "{total_fixed_points}"
+        );
+
+        let result = cycles(&PerfectRng::new(1u64, 0, 4));
+        assert_eq!(result.fixed_points(), 1);
+        assert_eq!(result.longest(), 1);
+    }
+
+    #[test]
+    fn fixed_point_rate() {
+        let result = fixed_points(&PerfectRng::with_key(1u64 << 40, [1, 2], 4), 100_000);
+        assert_eq!(result.found, 0);
+        assert_eq!(result.expected_probability, 1. / (1u64 << 40) as f64);
+
+        // with a round function that does nothing, two rounds only swap the
+        // halves twice, so every input is a fixed point
+        struct Zero;
+        impl RoundFunction for Zero {
+            fn name(&self) -> &str {
+                "zero"
+            }
+
+            fn round(&self, _key: u128, _j: u128, _right: u128) -> u128 {
+                0
+            }
+        }
+        let randomizer =
+            PerfectRng::with_key(1u64 << 40, [1, 2], 2).with_backend(Backend::Custom(&Zero));
+        assert_eq!(fixed_points(&randomizer, 1000).probability(), 1.);
+        let randomizer =
+            PerfectRng::with_key(1024u64, [1, 2], 2).with_backend(Backend::Custom(&Zero));
+        assert_eq!(cycles(&randomizer).fixed_points(), 1024);
+    }
+
+    #[test]
+    #[should_panic(expected = "at most the range")]
+    fn too_many_samples() {
+        let _ = serial_correlation(&PerfectRng::from_range(10u64), 11);
+    }
+}
@@ -0,0 +1,121 @@
+use std::fmt;
+
+use crate::{PerfectRng, UnsignedInt};
+
+/// A key that doesn't show up in `Debug` output, so it can't accidentally end
+/// up in logs. With the `zeroize` feature, it's also wiped from memory when
+/// it's dropped.
+///
+/// A [`PerfectRng`] created with [`PerfectRng::with_secret_key`] keeps the key
+/// secret too: it's left out of the `PerfectRng`'s `Debug` output, and it
+/// can't be serialized until [`PerfectRng::expose_key`] is called.
+///
+/// ```
+/// # use perfect_rand::{PerfectRng, SecretKey};
+/// let key = SecretKey::new([123456789u64, 987654321]);
+/// assert_eq!(format!("{key:?}"), "SecretKey([REDACTED])");
+///
+/// let randomizer = PerfectRng::with_secret_key(1000, &key, 4);
+/// assert!(!format!("{randomizer:?}").contains("123456789"));
+/// ```
+pub struct SecretKey<T: UnsignedInt = u64>([T; 2]);
+
+impl<T: UnsignedInt> SecretKey<T> {
+    /// Wrap a key like the one passed to [`PerfectRng::with_key`].
+    #[must_use]
+    pub fn new(key: [T; 2]) -> Self {
+        SecretKey(key)
+    }
+
+    /// Wrap a seed like the one passed to [`PerfectRng::new`].
+    #[must_use]
+    pub fn from_seed(seed: T) -> Self {
+        SecretKey([seed, T::ZERO])
+    }
+
+    /// Generate a random key.
+    #[must_use]
+    pub fn random() -> Self {
+        SecretKey([T::random(), T::random()])
+    }
+
+    /// Get the key. Be careful not to log it.
+    #[must_use]
+    pub fn expose_secret(&self) -> &[T; 2] {
+        &self.0
+    }
+}
+
+impl<T: UnsignedInt> fmt::Debug for SecretKey<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("SecretKey([REDACTED])")
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl<T: UnsignedInt> Drop for SecretKey<T> {
+    fn drop(&mut self) {
+        for k in &mut self.0 {
+            k.zeroize();
+        }
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl<T: UnsignedInt> zeroize::ZeroizeOnDrop for SecretKey<T> {}
+
+impl<T: UnsignedInt> PerfectRng<T> {
+    /// Like [`PerfectRng::with_key`], but the key stays secret. See
+    /// [`SecretKey`].
+    #[must_use]
+    pub fn with_secret_key(range: T, key: &SecretKey<T>, rounds: usize) -> Self {
+        let mut rng = Self::with_key(range, *key.expose_secret(), rounds);
+        rng.secret = true;
+        rng
+    }
+
+    /// Allow the key to be serialized, and shown in `Debug` output with the
+    /// `expose_seed` feature, if this was created with
+    /// [`PerfectRng::with_secret_key`].
+    #[must_use]
+    pub fn expose_key(mut self) -> Self {
+        self.secret = false;
+        self
+    }
+
+    /// Whether the key is kept secret. See [`PerfectRng::with_secret_key`].
+    #[must_use]
+    pub fn is_key_secret(&self) -> bool {
+        self.secret
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SecretKey;
+    use crate::PerfectRng;
+
+    #[test]
+    fn debug_hides_key() {
+        let key = SecretKey::new([123456789u64, 987654321]);
+        let randomizer = PerfectRng::with_secret_key(10, &key, 4);
+        assert!(randomizer.is_key_secret());
+        for debug in [format!("{key:?}"), format!("{randomizer:?}")] {
+            assert!(!debug.contains("123456789"), "{debug}");
+            assert!(!debug.contains("987654321"), "{debug}");
+        }
+
+        assert!(randomizer
+            .iter()
+            .eq(PerfectRng::with_key(10, [123456789, 987654321], 4).iter()));
+    }
+
+    #[test]
+    fn expose_key() {
+        let randomizer =
+            PerfectRng::with_secret_key(10u64, &SecretKey::from_seed(123456789), 4).expose_key();
+        assert!(!randomizer.is_key_secret());
+        #[cfg(all(feature = "expose_seed", not(feature = "zeroize")))]
+        assert!(format!("{randomizer:?}").contains("123456789"));
+    }
+}
@@ -0,0 +1,141 @@
+use crate::{PerfectRng, UnsignedInt};
+
+/// Named presets for how much work a [`PerfectRng`] does, for when you don't
+/// want to pick the number of rounds yourself.
+///
+/// Shuffling the order of a scan only needs the output to look random, but
+/// hiding sequential IDs needs the permutation to be hard to recover from
+/// outputs that an attacker can see, which takes more rounds.
+///
+/// ```
+/// # use perfect_rand::{PerfectRng, SecurityLevel};
+/// let randomizer = PerfectRng::builder()
+///     .range(1_000_000u64)
+///     .security(SecurityLevel::Hardened)
+///     .build()
+///     .unwrap();
+/// ```
+///
+/// The presets don't depend on the size of the domain, but small domains
+/// need more rounds. See [`PerfectRng::recommended_rounds`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum SecurityLevel {
+    /// 3 rounds of 2 siprounds each, which is enough for randomizing the
+    /// order of scans.
+    Fast,
+    /// 4 rounds of 4 siprounds each, the default.
+    #[default]
+    Balanced,
+    /// 8 rounds of 8 siprounds each, for when the outputs are seen by people
+    /// who shouldn't be able to tell what the inputs were, like obfuscated
+    /// IDs.
+    Hardened,
+}
+
+impl SecurityLevel {
+    /// The number of Feistel rounds for this level.
+    ///
+    /// ```
+    /// # use perfect_rand::SecurityLevel;
+    /// assert_eq!(SecurityLevel::Hardened.rounds(), 8);
+    /// ```
+    #[must_use]
+    pub const fn rounds(self) -> usize {
+        match self {
+            SecurityLevel::Fast => 3,
+            SecurityLevel::Balanced => 4,
+            SecurityLevel::Hardened => 8,
+        }
+    }
+
+    /// The number of SipHash rounds in each Feistel round for this level. See
+    /// [`PerfectRng::with_siprounds`](crate::PerfectRng::with_siprounds).
+    #[must_use]
+    pub const fn siprounds(self) -> usize {
+        match self {
+            SecurityLevel::Fast => 2,
+            SecurityLevel::Balanced => 4,
+            SecurityLevel::Hardened => 8,
+        }
+    }
+}
+
+/// Domains with fewer bits than this are "small", where there are known
+/// attacks that recover messages from few queries. This is the same as NIST's
+/// minimum of a million values for FF1 and FF3-1, rounded to a power of two.
+const SMALL_DOMAIN_BITS: u32 = 20;
+
+impl<T: UnsignedInt> PerfectRng<T> {
+    /// The number of rounds that's recommended for a range and security
+    /// level, based on the known security bounds of Feistel networks. Pass
+    /// this as the `rounds` to [`PerfectRng::new`].
+    ///
+    /// - [`SecurityLevel::Fast`] is always 3 rounds, which Luby and Rackoff
+    ///   showed is a pseudorandom permutation against attackers that can't
+    ///   decrypt. This is fine for ordering scans, where nobody is attacking
+    ///   the permutation.
+    /// - [`SecurityLevel::Balanced`] is 4 rounds, which is a strong
+    ///   pseudorandom permutation up to about `2^(bits / 4)` queries.
+    ///   Domains under 32 bits get 6 rounds, and domains under 2^20 get 8,
+    ///   since the number of queries an attacker needs shrinks with the
+    ///   domain.
+    /// - [`SecurityLevel::Hardened`] is 8 rounds, more than the 6 that Patarin
+    ///   showed are secure up to about `2^(bits / 2)` queries. Domains under
+    ///   2^20 get 12, more than FF1's 10, since Bellare, Hoang, and Tessaro's
+    ///   message recovery attacks on small domains need more rounds to resist.
+    ///
+    /// A range of 0 is treated as the full domain of `T`.
+    ///
+    /// ```
+    /// # use perfect_rand::{PerfectRng, SecurityLevel};
+    /// assert_eq!(PerfectRng::recommended_rounds(1000u64, SecurityLevel::Balanced), 8);
+    /// assert_eq!(PerfectRng::recommended_rounds(1u64 << 40, SecurityLevel::Balanced), 4);
+    /// ```
+    #[must_use]
+    pub fn recommended_rounds(range: T, security: SecurityLevel) -> usize {
+        let bits = range.wrapping_sub(T::ONE).count_bits();
+        match security {
+            SecurityLevel::Fast => 3,
+            SecurityLevel::Balanced if bits < SMALL_DOMAIN_BITS => 8,
+            SecurityLevel::Balanced if bits < 32 => 6,
+            SecurityLevel::Balanced => 4,
+            SecurityLevel::Hardened if bits < SMALL_DOMAIN_BITS => 12,
+            SecurityLevel::Hardened => 8,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SecurityLevel;
+    use crate::{PerfectRng, MAX_ROUNDS};
+
+    #[test]
+    fn recommended_rounds() {
+        for level in [
+            SecurityLevel::Fast,
+            SecurityLevel::Balanced,
+            SecurityLevel::Hardened,
+        ] {
+            // large domains get the same rounds as the preset
+            assert_eq!(
+                PerfectRng::recommended_rounds(u64::MAX, level),
+                level.rounds()
+            );
+            assert_eq!(PerfectRng::recommended_rounds(0u64, level), level.rounds());
+
+            // and small domains never get fewer
+            let mut previous = level.rounds();
+            for bits in (0..64).rev() {
+                let rounds = PerfectRng::recommended_rounds(1u64 << bits, level);
+                assert!(rounds >= previous && rounds <= MAX_ROUNDS);
+                previous = rounds;
+            }
+        }
+        assert_eq!(
+            PerfectRng::recommended_rounds(10_000u32, SecurityLevel::Hardened),
+            12
+        );
+    }
+}
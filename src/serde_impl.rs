@@ -0,0 +1,181 @@
+use serde::{de::Error, Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::{Algorithm, Backend, PerfectRng, PerfectRngError, UnsignedInt, MAX_ROUNDS};
+
+/// The version of the cipher that's written when serializing. This changes
+/// whenever the same configuration would produce a different permutation, so
+/// old state can't silently be restored as a different permutation.
+const VERSION: u32 = 1;
+
+/// The configuration that's needed to recreate a [`PerfectRng`]. Everything
+/// else is derived from this in the constructor.
+#[derive(Serialize, Deserialize)]
+#[serde(rename = "PerfectRng")]
+struct Config<T> {
+    version: u32,
+    /// The highest index in the domain, so the full domain of `T` can be
+    /// stored.
+    max: T,
+    offset: T,
+    key: [T; 2],
+    rounds: usize,
+    siprounds: usize,
+    tweak: T,
+    backend: Backend,
+    constant_time: bool,
+    tight: bool,
+    algorithm: Algorithm,
+}
+
+impl<T: UnsignedInt + Serialize> Serialize for PerfectRng<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if self.secret {
+            return Err(serde::ser::Error::custom(
+                "the key is secret, call PerfectRng::expose_key to serialize it",
+            ));
+        }
+        Config {
+            version: VERSION,
+            max: self.max,
+            offset: self.offset,
+            key: self.key,
+            rounds: self.rounds,
+            siprounds: self.siprounds,
+            tweak: self.tweak,
+            backend: self.backend,
+            constant_time: self.constant_time,
+            tight: self.tight,
+            algorithm: self.algorithm,
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de, T: UnsignedInt + Deserialize<'de>> Deserialize<'de> for PerfectRng<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let config = Config::<T>::deserialize(deserializer)?;
+        if config.version != VERSION {
+            return Err(D::Error::custom(format!(
+                "unsupported PerfectRng version {} (expected {VERSION})",
+                config.version
+            )));
+        }
+
+        // the same checks as the constructors, which allow 0 rounds
+        if config.rounds > MAX_ROUNDS {
+            return Err(D::Error::custom(PerfectRngError::TooManyRounds(
+                config.rounds,
+            )));
+        }
+        let mut rng = PerfectRng::with_key_max(config.max, config.key, config.rounds);
+        if T::MAX - config.offset < config.max {
+            return Err(D::Error::custom(
+                "offset + range doesn't fit in the integer type",
+            ));
+        }
+        if config.siprounds == 0 {
+            return Err(D::Error::custom(PerfectRngError::ZeroSiprounds));
+        }
+        rng.siprounds = config.siprounds;
+        rng.offset = config.offset;
+        rng.algorithm = config.algorithm;
+        rng.set_tweak(config.tweak);
+        rng.set_tight(config.tight).map_err(D::Error::custom)?;
+        rng.set_backend(config.backend).map_err(D::Error::custom)?;
+        rng.set_constant_time(config.constant_time)
+            .map_err(D::Error::custom)?;
+        Ok(rng)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Algorithm, Backend, PerfectRng, RoundFunction, SecretKey};
+
+    #[test]
+    fn roundtrip() {
+        let randomizer = PerfectRng::with_key(1000u64, [1, 2], 3)
+            .with_tweak(b"tweak")
+            .with_backend(Backend::Aes)
+            .with_offset(50);
+        let json = serde_json::to_string(&randomizer).unwrap();
+        let restored: PerfectRng = serde_json::from_str(&json).unwrap();
+        assert!(randomizer.iter().eq(restored.iter()));
+
+        let randomizer = PerfectRng::with_key(1000u64, [1, 2], 3)
+            .with_siprounds(2)
+            .with_constant_time();
+        let json = serde_json::to_string(&randomizer).unwrap();
+        let restored: PerfectRng = serde_json::from_str(&json).unwrap();
+        assert!(randomizer.iter().eq(restored.iter()));
+        assert!(restored.is_constant_time());
+
+        let randomizer = PerfectRng::with_key(1025u64, [1, 2], 3).with_tight_domain();
+        let json = serde_json::to_string(&randomizer).unwrap();
+        let restored: PerfectRng = serde_json::from_str(&json).unwrap();
+        assert!(randomizer.iter().eq(restored.iter()));
+        assert!(restored.is_tight_domain());
+
+        let randomizer = PerfectRng::with_key(1000u64, [1, 2], 0);
+        let json = serde_json::to_string(&randomizer).unwrap();
+        let restored: PerfectRng = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored.rounds(), 0);
+        assert!(randomizer.iter().eq(restored.iter()));
+    }
+
+    #[test]
+    fn algorithm() {
+        let randomizer = PerfectRng::with_key(1000u64, [1, 2], 3).with_algorithm(Algorithm::V1);
+        let json = serde_json::to_string(&randomizer).unwrap();
+        assert!(json.contains(r#""algorithm":"V1""#));
+        let restored: PerfectRng = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored.algorithm(), Algorithm::V1);
+
+        let json = json.replace(r#""algorithm":"V1""#, r#""algorithm":"V9""#);
+        assert!(serde_json::from_str::<PerfectRng>(&json).is_err());
+    }
+
+    #[test]
+    fn rejects_invalid() {
+        let valid = serde_json::to_string(&PerfectRng::with_key(10u64, [1, 2], 3)).unwrap();
+        assert!(serde_json::from_str::<PerfectRng>(&valid).is_ok());
+        for (from, to) in [
+            (r#""rounds":3"#, r#""rounds":33"#),
+            (r#""offset":0"#, r#""offset":18446744073709551615"#),
+            (r#""siprounds":4"#, r#""siprounds":0"#),
+            (r#""version":1"#, r#""version":2"#),
+            // every field is required
+            (r#","tight":false"#, ""),
+        ] {
+            assert!(valid.contains(from), "{valid}");
+            let json = valid.replace(from, to);
+            assert!(serde_json::from_str::<PerfectRng>(&json).is_err(), "{json}");
+        }
+    }
+
+    #[test]
+    fn secret_key() {
+        let randomizer = PerfectRng::with_secret_key(1000u64, &SecretKey::new([1, 2]), 3);
+        assert!(serde_json::to_string(&randomizer).is_err());
+        let randomizer = randomizer.expose_key();
+        let json = serde_json::to_string(&randomizer).unwrap();
+        let restored: PerfectRng = serde_json::from_str(&json).unwrap();
+        assert!(randomizer.iter().eq(restored.iter()));
+    }
+
+    #[test]
+    fn custom_backend() {
+        struct Xor;
+        impl RoundFunction for Xor {
+            fn name(&self) -> &str {
+                "xor"
+            }
+            fn round(&self, key: u128, j: u128, right: u128) -> u128 {
+                key ^ j ^ right
+            }
+        }
+
+        let randomizer = PerfectRng::new(1000u64, 1, 3).with_backend(Backend::Custom(&Xor));
+        assert!(serde_json::to_string(&randomizer).is_err());
+    }
+}
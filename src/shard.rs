@@ -0,0 +1,157 @@
+use std::iter::FusedIterator;
+
+use crate::{PerfectRng, UnsignedInt};
+
+/// An iterator over one shard of the permutation of a [`PerfectRng`], which
+/// is every `count`-th value starting at position `index`.
+///
+/// Like [`ShuffledIter`](crate::ShuffledIter), it's only an
+/// [`ExactSizeIterator`] for `u16`, and `u32` on 64-bit targets.
+///
+/// This is created by [`PerfectRng::shard`].
+#[derive(Debug, Clone)]
+pub struct ShardIter<'a, T: UnsignedInt = u64> {
+    rng: &'a PerfectRng<T>,
+    /// The next position in the permutation that'll be shuffled.
+    next: T,
+    count: T,
+    /// Whether `next` has passed the end of the range. This can't be stored
+    /// as `next > max`, since adding `count` can overflow.
+    finished: bool,
+}
+
+impl<T: UnsignedInt> PerfectRng<T> {
+    /// Iterate over one of `count` shards of the permutation, for splitting a
+    /// scan across machines with no coordination between them, like
+    /// masscan's `--shard`.
+    ///
+    /// The shard with index `index` gets the values at positions `index`,
+    /// `index + count`, `index + 2 * count`, and so on. Every machine uses
+    /// the same key with a different `index`, and together the shards cover
+    /// every value exactly once. Note that `index` starts at 0, so masscan's
+    /// `--shard 1/3` is `shard(0, 3)`.
+    ///
+    /// ```
+    /// # use perfect_rand::PerfectRng;
+    /// let key = rand::random();
+    ///
+    /// // each machine would do one of these
+    /// let mut values = Vec::new();
+    /// for index in 0..3 {
+    ///     let randomizer = PerfectRng::with_key(100u64, key, 4);
+    ///     values.extend(randomizer.shard(index, 3));
+    /// }
+    ///
+    /// values.sort();
+    /// assert_eq!(values, (0..100).collect::<Vec<_>>());
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if `count` is 0 or `index` isn't less than `count`.
+    #[must_use]
+    pub fn shard(&self, index: T, count: T) -> ShardIter<'_, T> {
+        assert!(
+            index < count,
+            "the shard index must be less than the shard count"
+        );
+        ShardIter {
+            rng: self,
+            next: index,
+            count,
+            finished: index > self.max,
+        }
+    }
+}
+
+impl<T: UnsignedInt> ShardIter<'_, T> {
+    /// The position in the permutation that the next call to
+    /// [`Iterator::next`] will shuffle.
+    #[must_use]
+    pub fn position(&self) -> T {
+        self.next
+    }
+}
+
+impl<T: UnsignedInt> Iterator for ShardIter<'_, T> {
+    type Item = T;
+
+    #[inline]
+    fn next(&mut self) -> Option<T> {
+        if self.finished {
+            return None;
+        }
+        let shuffled = self.rng.shuffle_nth(self.next);
+        if self.rng.max - self.next < self.count {
+            self.finished = true;
+        } else {
+            self.next = self.next + self.count;
+        }
+        Some(shuffled)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        if self.finished {
+            return (0, Some(0));
+        }
+        let remaining = (self.rng.max - self.next).as_u128() / self.count.as_u128();
+        match usize::try_from(remaining)
+            .ok()
+            .and_then(|n| n.checked_add(1))
+        {
+            Some(remaining) => (remaining, Some(remaining)),
+            None => (usize::MAX, None),
+        }
+    }
+}
+
+// the length has to fit in a usize, which it doesn't for the full range of
+// wider types
+impl ExactSizeIterator for ShardIter<'_, u16> {}
+#[cfg(target_pointer_width = "64")]
+impl ExactSizeIterator for ShardIter<'_, u32> {}
+
+impl<T: UnsignedInt> FusedIterator for ShardIter<'_, T> {}
+
+#[cfg(test)]
+mod tests {
+    use crate::{PerfectRng, PerfectRng32};
+
+    #[test]
+    fn shards_cover_range() {
+        let randomizer = PerfectRng::new(1000u16, 0, 4);
+        let all = randomizer.iter().collect::<Vec<_>>();
+        let mut combined = vec![None; 1000];
+        for index in 0..7 {
+            let shard = randomizer.shard(index, 7);
+            let len = shard.len();
+            let mut n = 0;
+            for (i, value) in shard.enumerate() {
+                let position = index as usize + i * 7;
+                assert_eq!(combined[position], None);
+                combined[position] = Some(value);
+                n += 1;
+            }
+            assert_eq!(n, len);
+        }
+        assert!(combined.into_iter().map(Option::unwrap).eq(all));
+    }
+
+    #[test]
+    fn shards_near_max() {
+        let randomizer = PerfectRng32::with_key_inclusive(u32::MAX - 9..=u32::MAX, [1, 2], 4);
+        assert_eq!(randomizer.shard(3, 4).count(), 2);
+        assert_eq!(randomizer.shard(0, u32::MAX).count(), 1);
+        assert_eq!(randomizer.shard(10, 11).count(), 0);
+
+        let full = PerfectRng32::with_key_inclusive(0..=u32::MAX, [1, 2], 4);
+        let mut shard = full.shard(0, u32::MAX);
+        assert_eq!(shard.len(), 2);
+        assert_eq!(shard.next(), Some(full.shuffle(0)));
+        assert_eq!(shard.position(), u32::MAX);
+        assert_eq!(shard.next(), Some(full.shuffle(u32::MAX)));
+        assert_eq!(shard.next(), None);
+        assert_eq!(full.shard(1, u32::MAX).len(), 1);
+    }
+}
@@ -0,0 +1,68 @@
+use std::ops::{Range, RangeInclusive};
+
+use crate::{PerfectRng, ShuffleCursor, UnsignedInt};
+
+/// Iterate over a range of integers in shuffled order, without collecting
+/// it into a `Vec` first.
+///
+/// The order is the same as [`PerfectRng::new`] with the seed and 4 rounds,
+/// offset to the start of the range.
+///
+/// ```
+/// use perfect_rand::{PerfectRng, ShuffleExt};
+///
+/// let shuffled = (100..200u64).shuffled(5).collect::<Vec<_>>();
+/// assert_eq!(shuffled.len(), 100);
+/// assert_eq!(shuffled[0], PerfectRng::new(100u64, 5, 4).shuffle(0) + 100);
+///
+/// assert_eq!((0..0u64).shuffled(5).next(), None);
+/// ```
+pub trait ShuffleExt<T: UnsignedInt> {
+    /// Iterate over every value in the range in an order that's decided by
+    /// `seed`. An empty range gives an empty iterator.
+    #[must_use]
+    fn shuffled(self, seed: T) -> ShuffleCursor<T>;
+}
+
+impl<T: UnsignedInt> ShuffleExt<T> for Range<T> {
+    fn shuffled(self, seed: T) -> ShuffleCursor<T> {
+        if self.is_empty() {
+            return ShuffleCursor::empty(PerfectRng::new(T::ONE, seed, 4));
+        }
+        ShuffleCursor::new(PerfectRng::new(self.end - self.start, seed, 4).with_offset(self.start))
+    }
+}
+
+impl<T: UnsignedInt> ShuffleExt<T> for RangeInclusive<T> {
+    fn shuffled(self, seed: T) -> ShuffleCursor<T> {
+        if self.is_empty() {
+            return ShuffleCursor::empty(PerfectRng::new(T::ONE, seed, 4));
+        }
+        ShuffleCursor::new(PerfectRng::with_key_inclusive(self, [seed, T::ZERO], 4))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ShuffleExt;
+    use crate::PerfectRng;
+
+    #[test]
+    fn same_as_perfect_rng() {
+        let randomizer = PerfectRng::new(1000u32, 7, 4).with_offset(50);
+        assert!((50..1050u32).shuffled(7).eq(randomizer.iter()));
+        assert!((50..=1049u32).shuffled(7).eq(randomizer.iter()));
+
+        let mut sorted = (0..=u16::MAX).shuffled(7).collect::<Vec<_>>();
+        sorted.sort_unstable();
+        assert!(sorted.into_iter().eq(0..=u16::MAX));
+    }
+
+    #[test]
+    #[allow(clippy::reversed_empty_ranges)]
+    fn empty() {
+        assert_eq!((5..5u64).shuffled(1).next(), None);
+        assert_eq!((5..=4u64).shuffled(1).next(), None);
+        assert!((5..=5u64).shuffled(1).eq([5]));
+    }
+}
@@ -0,0 +1,183 @@
+use std::{iter::FusedIterator, ops::Index};
+
+use crate::{PerfectRng, ShuffledIter};
+
+/// A view of a slice in shuffled order, without moving or copying any of its
+/// elements.
+///
+/// Indexing into the view maps the index through a [`PerfectRng`], so this
+/// works for slices that are too big to shuffle in place.
+///
+/// ```
+/// # use perfect_rand::PermutedSlice;
+/// let names = ["alice", "bob", "carol", "dave"];
+/// let permuted = PermutedSlice::new(&names);
+/// assert_eq!(permuted.len(), 4);
+///
+/// let mut shuffled = permuted.iter().copied().collect::<Vec<_>>();
+/// assert_eq!(shuffled[0], permuted[0]);
+/// shuffled.sort();
+/// assert_eq!(shuffled, names);
+/// ```
+#[derive(Debug)]
+pub struct PermutedSlice<'a, T> {
+    slice: &'a [T],
+    /// `None` if the slice is empty, since a `PerfectRng` can't have a range
+    /// of 0.
+    rng: Option<PerfectRng<u64>>,
+}
+
+impl<'a, T> PermutedSlice<'a, T> {
+    /// Create a view with a random key and default rounds.
+    #[must_use]
+    pub fn new(slice: &'a [T]) -> Self {
+        Self::with_key(slice, rand::random(), 4)
+    }
+
+    /// Create a view keyed like [`PerfectRng::with_key`].
+    #[must_use]
+    pub fn with_key(slice: &'a [T], key: [u64; 2], rounds: usize) -> Self {
+        let rng =
+            (!slice.is_empty()).then(|| PerfectRng::with_key(slice.len() as u64, key, rounds));
+        PermutedSlice { slice, rng }
+    }
+
+    /// The number of elements in the slice.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.slice.len()
+    }
+
+    /// Whether the slice is empty.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.slice.is_empty()
+    }
+
+    /// Get the element at a position in the shuffled order, or `None` if the
+    /// index is out of bounds.
+    #[must_use]
+    pub fn get(&self, index: usize) -> Option<&'a T> {
+        if index >= self.slice.len() {
+            return None;
+        }
+        let rng = self.rng.as_ref()?;
+        Some(&self.slice[rng.shuffle(index as u64) as usize])
+    }
+
+    /// Iterate over the elements in shuffled order.
+    #[must_use]
+    pub fn iter(&self) -> PermutedSliceIter<'_, 'a, T> {
+        PermutedSliceIter {
+            slice: self.slice,
+            iter: self.rng.as_ref().map(PerfectRng::iter),
+        }
+    }
+
+    /// The underlying slice, in its original order.
+    #[must_use]
+    pub fn as_slice(&self) -> &'a [T] {
+        self.slice
+    }
+}
+
+impl<T> Index<usize> for PermutedSlice<'_, T> {
+    type Output = T;
+
+    fn index(&self, index: usize) -> &T {
+        match self.get(index) {
+            Some(element) => element,
+            None => panic!(
+                "index out of bounds: the len is {} but the index is {index}",
+                self.len()
+            ),
+        }
+    }
+}
+
+impl<'s, 'a, T> IntoIterator for &'s PermutedSlice<'a, T> {
+    type Item = &'a T;
+    type IntoIter = PermutedSliceIter<'s, 'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// An iterator over the elements of a [`PermutedSlice`], in shuffled order.
+///
+/// This is created by [`PermutedSlice::iter`].
+#[derive(Debug, Clone)]
+pub struct PermutedSliceIter<'s, 'a, T> {
+    slice: &'a [T],
+    iter: Option<ShuffledIter<'s, u64>>,
+}
+
+impl<'a, T> Iterator for PermutedSliceIter<'_, 'a, T> {
+    type Item = &'a T;
+
+    #[inline]
+    fn next(&mut self) -> Option<&'a T> {
+        let index = self.iter.as_mut()?.next()?;
+        Some(&self.slice[index as usize])
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.as_ref().map_or((0, Some(0)), Iterator::size_hint)
+    }
+
+    #[inline]
+    fn nth(&mut self, n: usize) -> Option<&'a T> {
+        let index = self.iter.as_mut()?.nth(n)?;
+        Some(&self.slice[index as usize])
+    }
+}
+
+impl<T> DoubleEndedIterator for PermutedSliceIter<'_, '_, T> {
+    #[inline]
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let index = self.iter.as_mut()?.next_back()?;
+        Some(&self.slice[index as usize])
+    }
+}
+
+impl<T> ExactSizeIterator for PermutedSliceIter<'_, '_, T> {}
+
+impl<T> FusedIterator for PermutedSliceIter<'_, '_, T> {}
+
+#[cfg(test)]
+mod tests {
+    use super::PermutedSlice;
+
+    #[test]
+    fn permutes_every_element() {
+        let values = (0..1000).map(|i| i * 3).collect::<Vec<_>>();
+        let permuted = PermutedSlice::with_key(&values, [1, 2], 4);
+
+        let mut shuffled = permuted.iter().copied().collect::<Vec<_>>();
+        for (i, &value) in shuffled.iter().enumerate() {
+            assert_eq!(permuted[i], value);
+        }
+        assert_eq!(permuted.get(1000), None);
+        assert_ne!(shuffled, values);
+        shuffled.sort_unstable();
+        assert_eq!(shuffled, values);
+    }
+
+    #[test]
+    fn empty() {
+        let permuted = PermutedSlice::<u8>::new(&[]);
+        assert!(permuted.is_empty());
+        assert_eq!(permuted.get(0), None);
+        assert_eq!(permuted.iter().len(), 0);
+        assert_eq!(permuted.iter().next(), None);
+    }
+
+    #[test]
+    #[should_panic]
+    fn index_out_of_bounds() {
+        let permuted = PermutedSlice::new(&[1, 2, 3]);
+        let _ = permuted[3];
+    }
+}
@@ -0,0 +1,194 @@
+use std::ops::Range;
+
+use crate::PerfectRng;
+
+/// Which part of a dataset an example was assigned to by a [`DatasetSplit`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Split {
+    /// The training set.
+    Train,
+    /// The validation set.
+    Val,
+    /// The test set.
+    Test,
+}
+
+/// A random assignment of a dataset's examples to training, validation, and
+/// test sets, without storing lists of indices.
+///
+/// The examples are shuffled, and the first positions of the shuffled order
+/// are the training set, the next are the validation set, and the rest are
+/// the test set. Finding which set an example is in only takes an
+/// [`PerfectRng::unshuffle`], so it's O(1) even for billions of examples.
+///
+/// ```
+/// # use perfect_rand::{DatasetSplit, Split};
+/// let split = DatasetSplit::new(1000, 42, 0.8, 0.1);
+/// assert_eq!(split.len(Split::Train), 800);
+/// assert_eq!(split.len(Split::Val), 100);
+/// assert_eq!(split.len(Split::Test), 100);
+///
+/// for i in split.indices(Split::Val) {
+///     assert_eq!(split.assignment(i), Split::Val);
+/// }
+/// ```
+#[derive(Debug)]
+pub struct DatasetSplit {
+    rng: PerfectRng<u64>,
+    len: u64,
+    train: u64,
+    val: u64,
+}
+
+impl DatasetSplit {
+    /// Split a dataset with `dataset_len` examples, so about `train` of them
+    /// are in the training set, `val` in the validation set, and the rest in
+    /// the test set. The sizes are rounded to whole examples.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `dataset_len` is 0, or if the fractions are negative or add
+    /// up to more than 1.
+    #[must_use]
+    pub fn new(dataset_len: u64, seed: u64, train: f64, val: f64) -> Self {
+        assert!(
+            train >= 0.0 && val >= 0.0 && train + val <= 1.0,
+            "the fractions must not be negative and must add up to at most 1"
+        );
+        let train = (dataset_len as f64 * train).round() as u64;
+        let val = ((dataset_len as f64 * val).round() as u64).min(dataset_len - train);
+        Self::with_sizes(dataset_len, seed, train, val)
+    }
+
+    /// Split a dataset with `dataset_len` examples, so exactly `train` of them
+    /// are in the training set, `val` in the validation set, and the rest in
+    /// the test set.
+    ///
+    /// ```
+    /// # use perfect_rand::{DatasetSplit, Split};
+    /// let split = DatasetSplit::with_sizes(1000, 42, 900, 100);
+    /// assert_eq!(split.len(Split::Test), 0);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if `dataset_len` is 0, or if `train + val` is more than
+    /// `dataset_len`.
+    #[must_use]
+    pub fn with_sizes(dataset_len: u64, seed: u64, train: u64, val: u64) -> Self {
+        assert!(dataset_len != 0, "the dataset must not be empty");
+        assert!(
+            train.checked_add(val).is_some_and(|sum| sum <= dataset_len),
+            "the splits must not be bigger than the dataset"
+        );
+        DatasetSplit {
+            rng: PerfectRng::new(dataset_len, seed, 4),
+            len: dataset_len,
+            train,
+            val,
+        }
+    }
+
+    /// The set that example `i` is in.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `i` isn't less than the length of the dataset.
+    #[must_use]
+    pub fn assignment(&self, i: u64) -> Split {
+        assert!(i < self.len, "the index must be in the dataset");
+        let position = self.rng.unshuffle(i);
+        if position < self.train {
+            Split::Train
+        } else if position < self.train + self.val {
+            Split::Val
+        } else {
+            Split::Test
+        }
+    }
+
+    /// The number of examples in a set.
+    #[must_use]
+    pub fn len(&self, split: Split) -> u64 {
+        let positions = self.positions(split);
+        positions.end - positions.start
+    }
+
+    /// The indices of the examples in a set, in shuffled order.
+    pub fn indices(&self, split: Split) -> impl Iterator<Item = u64> + '_ {
+        self.positions(split)
+            .map(|position| self.rng.shuffle(position))
+    }
+
+    /// The [`PerfectRng`] over the dataset's indices that the examples are
+    /// shuffled with.
+    #[must_use]
+    pub fn rng(&self) -> &PerfectRng<u64> {
+        &self.rng
+    }
+
+    fn positions(&self, split: Split) -> Range<u64> {
+        match split {
+            Split::Train => 0..self.train,
+            Split::Val => self.train..self.train + self.val,
+            Split::Test => self.train + self.val..self.len,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{DatasetSplit, Split};
+
+    #[test]
+    fn every_index_once() {
+        let split = DatasetSplit::new(1003, 1, 0.7, 0.2);
+        let mut seen = vec![false; 1003];
+        for set in [Split::Train, Split::Val, Split::Test] {
+            let mut count = 0;
+            for i in split.indices(set) {
+                assert_eq!(split.assignment(i), set);
+                assert!(!seen[i as usize]);
+                seen[i as usize] = true;
+                count += 1;
+            }
+            assert_eq!(count, split.len(set));
+        }
+        assert!(seen.into_iter().all(|seen| seen));
+        assert_eq!(split.len(Split::Train), 702);
+        assert_eq!(split.len(Split::Val), 201);
+        assert_eq!(split.len(Split::Test), 100);
+    }
+
+    #[test]
+    fn sizes() {
+        let split = DatasetSplit::new(10, 1, 0.5, 0.5);
+        assert_eq!(split.len(Split::Test), 0);
+        let split = DatasetSplit::new(3, 1, 0.5, 0.5);
+        assert_eq!(split.len(Split::Train) + split.len(Split::Val), 3);
+        let split = DatasetSplit::with_sizes(1, 1, 0, 0);
+        assert_eq!(split.assignment(0), Split::Test);
+    }
+
+    #[test]
+    fn seeds() {
+        let a = DatasetSplit::new(1000, 1, 0.5, 0.2);
+        let b = DatasetSplit::new(1000, 2, 0.5, 0.2);
+        assert!(a
+            .indices(Split::Val)
+            .eq(DatasetSplit::new(1000, 1, 0.5, 0.2).indices(Split::Val)));
+        assert!(!a.indices(Split::Val).eq(b.indices(Split::Val)));
+    }
+
+    #[test]
+    #[should_panic(expected = "add up to at most 1")]
+    fn too_big() {
+        let _ = DatasetSplit::new(10, 1, 0.8, 0.3);
+    }
+
+    #[test]
+    #[should_panic(expected = "in the dataset")]
+    fn out_of_range() {
+        let _ = DatasetSplit::new(10, 1, 0.8, 0.1).assignment(10);
+    }
+}
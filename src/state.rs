@@ -0,0 +1,322 @@
+use std::{
+    io::{self, Read, Write},
+    iter::FusedIterator,
+};
+
+use crate::{Algorithm, Backend, PerfectRng, PerfectRngError, UnsignedInt, MAX_ROUNDS};
+
+/// The bytes that every saved state starts with.
+const MAGIC: [u8; 4] = *b"PRST";
+/// The version of the format, which changes whenever the layout does.
+const VERSION: u8 = 1;
+
+/// The progress of a scan, which can be saved to a file so the scan can
+/// restart exactly where it left off if it's interrupted.
+///
+/// This is like a [`ShuffleCursor`](crate::ShuffleCursor), except the saved
+/// state includes everything that's needed to recreate the [`PerfectRng`]
+/// (including the key), and it can also be one shard of the scan like
+/// [`PerfectRng::shard`].
+///
+/// ```
+/// # use perfect_rand::{PerfectRng, ScanState};
+/// let mut state = ScanState::new(PerfectRng::from_range(1000u64)).with_shard(1, 4);
+/// let first = state.by_ref().take(100).collect::<Vec<_>>();
+///
+/// let mut file = Vec::new();
+/// state.save_state(&mut file).unwrap();
+/// let resumed = ScanState::<u64>::load_state(&file[..]).unwrap();
+/// assert_eq!(resumed.shard(), (1, 4));
+///
+/// let rest = resumed.collect::<Vec<_>>();
+/// assert_eq!(first.len() + rest.len(), 250);
+/// ```
+#[derive(Debug)]
+pub struct ScanState<T: UnsignedInt = u64> {
+    rng: PerfectRng<T>,
+    /// The position in the permutation that'll be shuffled next.
+    position: T,
+    shard_index: T,
+    shard_count: T,
+    /// Whether the whole shard has been shuffled. This can't be stored as
+    /// `position > max`, since adding the shard count can overflow.
+    finished: bool,
+}
+
+impl<T: UnsignedInt> ScanState<T> {
+    /// Start a scan over every value of the permutation.
+    #[must_use]
+    pub fn new(rng: PerfectRng<T>) -> Self {
+        ScanState {
+            rng,
+            position: T::ZERO,
+            shard_index: T::ZERO,
+            shard_count: T::ONE,
+            finished: false,
+        }
+    }
+
+    /// Only scan one of `count` shards of the permutation. See
+    /// [`PerfectRng::shard`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `count` is 0 or `index` isn't less than `count`.
+    #[must_use]
+    pub fn with_shard(self, index: T, count: T) -> Self {
+        assert!(
+            index < count,
+            "the shard index must be less than the shard count"
+        );
+        ScanState {
+            position: index,
+            shard_index: index,
+            shard_count: count,
+            finished: index > self.rng.max,
+            ..self
+        }
+    }
+
+    /// The [`PerfectRng`] that the scan is shuffling.
+    #[must_use]
+    pub fn rng(&self) -> &PerfectRng<T> {
+        &self.rng
+    }
+
+    /// The position in the permutation that the next call to
+    /// [`Iterator::next`] will shuffle.
+    #[must_use]
+    pub fn position(&self) -> T {
+        self.position
+    }
+
+    /// The shard index and count, which are `(0, 1)` if the scan isn't
+    /// sharded.
+    #[must_use]
+    pub fn shard(&self) -> (T, T) {
+        (self.shard_index, self.shard_count)
+    }
+
+    /// Encode the state in a small versioned binary format.
+    ///
+    /// This returns an error if the key is secret (see
+    /// [`PerfectRng::expose_key`]) or the backend is
+    /// [`Backend::Custom`], since neither can be saved.
+    pub fn to_bytes(&self) -> Result<Vec<u8>, PerfectRngError> {
+        let rng = &self.rng;
+        if rng.secret || matches!(rng.backend, Backend::Custom(_)) {
+            return Err(PerfectRngError::NotSerializable);
+        }
+
+        let mut bytes = Vec::with_capacity(25 + T::BITS as usize);
+        bytes.extend_from_slice(&MAGIC);
+        bytes.push(VERSION);
+        bytes.push((T::BITS / 8) as u8);
+        bytes.push(rng.backend.id());
+        bytes.push(rng.algorithm.id());
+        bytes.push(
+            u8::from(rng.constant_time) | u8::from(self.finished) << 1 | u8::from(rng.tight) << 2,
+        );
+        bytes.extend_from_slice(&(rng.rounds as u32).to_le_bytes());
+        bytes.extend_from_slice(&(rng.siprounds as u32).to_le_bytes());
+        for n in [
+            rng.max,
+            rng.offset,
+            rng.key[0],
+            rng.key[1],
+            rng.tweak,
+            self.position,
+            self.shard_index,
+            self.shard_count,
+        ] {
+            bytes.extend_from_slice(&n.as_u128().to_le_bytes()[..T::BITS as usize / 8]);
+        }
+        // this catches corrupted files, and states saved by a version of the
+        // crate where the same configuration gives a different permutation
+        bytes.extend_from_slice(&rng.config_hash().to_le_bytes());
+        Ok(bytes)
+    }
+
+    /// Decode a state encoded with [`ScanState::to_bytes`].
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, PerfectRngError> {
+        let width = T::BITS as usize / 8;
+        if bytes.len() != 25 + width * 8
+            || bytes[..4] != MAGIC
+            || bytes[4] != VERSION
+            || bytes[5] as usize != width
+            || bytes[8] >> 3 != 0
+        {
+            return Err(PerfectRngError::InvalidState);
+        }
+        let backend = Backend::from_id(bytes[6]).ok_or(PerfectRngError::UnknownBackend)?;
+        let algorithm = Algorithm::from_id(bytes[7]).ok_or(PerfectRngError::InvalidState)?;
+        let flags = bytes[8];
+        let rounds = u32::from_le_bytes(bytes[9..13].try_into().unwrap()) as usize;
+        let siprounds = u32::from_le_bytes(bytes[13..17].try_into().unwrap()) as usize;
+        let mut n = bytes[17..].chunks_exact(width).map(|chunk| {
+            let mut n = [0; 16];
+            n[..width].copy_from_slice(chunk);
+            T::from_u128(u128::from_le_bytes(n))
+        });
+        let mut next = || n.next().unwrap();
+        let (max, offset, key, tweak) = (next(), next(), [next(), next()], next());
+        let (position, shard_index, shard_count) = (next(), next(), next());
+        let config_hash = u64::from_le_bytes(bytes[bytes.len() - 8..].try_into().unwrap());
+
+        if siprounds == 0 || T::MAX - offset < max {
+            return Err(PerfectRngError::InvalidState);
+        }
+        // the same checks as the constructors, which allow 0 rounds
+        if rounds > MAX_ROUNDS {
+            return Err(PerfectRngError::TooManyRounds(rounds));
+        }
+        let mut rng = PerfectRng::with_key_max(max, key, rounds).with_siprounds(siprounds);
+        rng.offset = offset;
+        rng.algorithm = algorithm;
+        rng.set_tweak(tweak);
+        rng.set_tight(flags & 0b100 != 0)?;
+        rng.set_backend(backend)?;
+        rng.set_constant_time(flags & 1 != 0)?;
+        if rng.config_hash() != config_hash {
+            return Err(PerfectRngError::InvalidState);
+        }
+
+        let finished = flags & 0b10 != 0;
+        if shard_index >= shard_count || (position > max && !finished) {
+            return Err(PerfectRngError::InvalidState);
+        }
+        Ok(ScanState {
+            rng,
+            position,
+            shard_index,
+            shard_count,
+            finished,
+        })
+    }
+
+    /// Write the state to a file or any other writer. See
+    /// [`ScanState::to_bytes`].
+    pub fn save_state(&self, mut writer: impl Write) -> io::Result<()> {
+        let bytes = self
+            .to_bytes()
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidInput, err))?;
+        writer.write_all(&bytes)
+    }
+
+    /// Read a state written by [`ScanState::save_state`].
+    pub fn load_state(mut reader: impl Read) -> io::Result<Self> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes)?;
+        Self::from_bytes(&bytes).map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+    }
+}
+
+impl<T: UnsignedInt> Iterator for ScanState<T> {
+    type Item = T;
+
+    #[inline]
+    fn next(&mut self) -> Option<T> {
+        if self.finished {
+            return None;
+        }
+        let shuffled = self.rng.shuffle_nth(self.position);
+        if self.rng.max - self.position < self.shard_count {
+            self.finished = true;
+        } else {
+            self.position = self.position + self.shard_count;
+        }
+        Some(shuffled)
+    }
+}
+
+impl<T: UnsignedInt> FusedIterator for ScanState<T> {}
+
+#[cfg(test)]
+mod tests {
+    use super::ScanState;
+    use crate::{Backend, PerfectRng, PerfectRngError, SecretKey};
+
+    #[test]
+    fn resume() {
+        let rng = || {
+            PerfectRng::with_key(1000u32, [1, 2], 3)
+                .with_offset(50)
+                .with_tweak(b"tweak")
+                .with_siprounds(2)
+                .with_backend(Backend::Philox)
+                .with_constant_time()
+        };
+        let mut state = ScanState::new(rng()).with_shard(2, 3);
+        let first = state.by_ref().take(100).collect::<Vec<_>>();
+        let resumed = ScanState::from_bytes(&state.to_bytes().unwrap()).unwrap();
+        assert_eq!(resumed.position(), 302);
+        assert!(resumed.rng().is_constant_time());
+
+        let all = first.into_iter().chain(resumed).collect::<Vec<_>>();
+        assert!(all.into_iter().eq(rng().shard(2, 3)));
+    }
+
+    #[test]
+    fn resume_finished() {
+        let mut state = ScanState::new(PerfectRng::with_key_inclusive(0..=u16::MAX, [1, 2], 4));
+        assert_eq!(state.by_ref().count(), 65536);
+        let mut bytes = Vec::new();
+        state.save_state(&mut bytes).unwrap();
+        let mut resumed = ScanState::<u16>::load_state(&bytes[..]).unwrap();
+        assert_eq!(resumed.next(), None);
+    }
+
+    #[test]
+    fn resume_tight() {
+        let rng = || PerfectRng::with_key(1025u64, [1, 2], 4).with_tight_domain();
+        let mut state = ScanState::new(rng());
+        let first = state.by_ref().take(500).collect::<Vec<_>>();
+        let resumed = ScanState::from_bytes(&state.to_bytes().unwrap()).unwrap();
+        assert!(resumed.rng().is_tight_domain());
+        assert!(first.into_iter().chain(resumed).eq(rng().iter()));
+    }
+
+    #[test]
+    fn resume_zero_rounds() {
+        let state = ScanState::new(PerfectRng::with_key(1000u64, [1, 2], 0));
+        let resumed = ScanState::<u64>::from_bytes(&state.to_bytes().unwrap()).unwrap();
+        assert_eq!(resumed.rng().rounds(), 0);
+        assert!(resumed.eq(0..1000));
+    }
+
+    #[test]
+    fn invalid() {
+        let state = ScanState::new(PerfectRng::with_key(1000u64, [1, 2], 4));
+        let bytes = state.to_bytes().unwrap();
+        assert!(ScanState::<u64>::from_bytes(&bytes).is_ok());
+
+        assert_eq!(
+            ScanState::<u32>::from_bytes(&bytes).unwrap_err(),
+            PerfectRngError::InvalidState
+        );
+        for i in [0, 4, 5, 8, 12, 20, bytes.len() - 1] {
+            let mut corrupted = bytes.clone();
+            corrupted[i] ^= 0x80;
+            assert!(ScanState::<u64>::from_bytes(&corrupted).is_err());
+        }
+        // an algorithm from a newer version of the crate
+        let mut newer = bytes.clone();
+        newer[7] = 1;
+        assert_eq!(
+            ScanState::<u64>::from_bytes(&newer).unwrap_err(),
+            PerfectRngError::InvalidState
+        );
+        let mut unknown = bytes.clone();
+        unknown[6] = 200;
+        assert_eq!(
+            ScanState::<u64>::from_bytes(&unknown).unwrap_err(),
+            PerfectRngError::UnknownBackend
+        );
+
+        let secret = PerfectRng::with_secret_key(1000u64, &SecretKey::new([1, 2]), 4);
+        assert_eq!(
+            ScanState::new(secret).to_bytes().unwrap_err(),
+            PerfectRngError::NotSerializable
+        );
+    }
+}
@@ -0,0 +1,146 @@
+//! Counters for how much time is spent cycle walking, with the `stats`
+//! feature. Without it, the counters are empty and recording does nothing.
+
+#[cfg(feature = "stats")]
+use std::sync::atomic::{AtomicU64, Ordering};
+
+#[cfg(feature = "stats")]
+use crate::{PerfectRng, UnsignedInt};
+
+/// How much cycle walking a [`PerfectRng`](crate::PerfectRng) has done,
+/// returned by [`PerfectRng::stats`](crate::PerfectRng::stats).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg(feature = "stats")]
+pub struct WalkStats {
+    /// The number of values that were shuffled or unshuffled.
+    pub calls: u64,
+    /// The number of times a value had to be encrypted again because it was
+    /// out of the range, over every call.
+    pub walks: u64,
+    /// The most times a single value had to be encrypted again.
+    pub max_walk: u64,
+}
+
+#[cfg(feature = "stats")]
+impl WalkStats {
+    /// The average number of times a value is encrypted again. The number of
+    /// encryptions per call is one more than this.
+    #[must_use]
+    pub fn walks_per_call(&self) -> f64 {
+        if self.calls == 0 {
+            return 0.;
+        }
+        self.walks as f64 / self.calls as f64
+    }
+}
+
+#[derive(Debug, Default)]
+pub(crate) struct Counters {
+    #[cfg(feature = "stats")]
+    calls: AtomicU64,
+    #[cfg(feature = "stats")]
+    walks: AtomicU64,
+    #[cfg(feature = "stats")]
+    max_walk: AtomicU64,
+}
+
+impl Counters {
+    pub(crate) const fn new() -> Self {
+        Counters {
+            #[cfg(feature = "stats")]
+            calls: AtomicU64::new(0),
+            #[cfg(feature = "stats")]
+            walks: AtomicU64::new(0),
+            #[cfg(feature = "stats")]
+            max_walk: AtomicU64::new(0),
+        }
+    }
+
+    /// Record a call that had to encrypt `walks` more times.
+    #[inline]
+    pub(crate) fn record(&self, walks: u64) {
+        #[cfg(feature = "stats")]
+        {
+            // these are only statistics, so they don't need to be ordered
+            // with anything else
+            self.calls.fetch_add(1, Ordering::Relaxed);
+            if walks > 0 {
+                self.walks.fetch_add(walks, Ordering::Relaxed);
+                self.max_walk.fetch_max(walks, Ordering::Relaxed);
+            }
+        }
+        #[cfg(not(feature = "stats"))]
+        let _ = walks;
+    }
+}
+
+#[cfg(feature = "stats")]
+impl<T: UnsignedInt> PerfectRng<T> {
+    /// How much cycle walking has been done by [`PerfectRng::shuffle`],
+    /// [`PerfectRng::unshuffle`], and everything that uses them, since the
+    /// `PerfectRng` was created or [`PerfectRng::reset_stats`] was called.
+    ///
+    /// This is for tuning, like comparing [`PerfectRng::with_tight_domain`]
+    /// for a specific range. The counters are shared between threads.
+    ///
+    /// ```
+    /// # use perfect_rand::PerfectRng;
+    /// let randomizer = PerfectRng::from_range(1025u64);
+    /// let _ = randomizer.iter().count();
+    /// let stats = randomizer.stats();
+    /// assert_eq!(stats.calls, 1025);
+    /// assert!(stats.walks_per_call() > 0.5);
+    ///
+    /// let randomizer = PerfectRng::from_range(1025u64).with_tight_domain();
+    /// let _ = randomizer.iter().count();
+    /// assert!(randomizer.stats().walks_per_call() < 0.1);
+    /// ```
+    #[must_use]
+    pub fn stats(&self) -> WalkStats {
+        WalkStats {
+            calls: self.counters.calls.load(Ordering::Relaxed),
+            walks: self.counters.walks.load(Ordering::Relaxed),
+            max_walk: self.counters.max_walk.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Set the counters for [`PerfectRng::stats`] back to zero.
+    pub fn reset_stats(&self) {
+        self.counters.calls.store(0, Ordering::Relaxed);
+        self.counters.walks.store(0, Ordering::Relaxed);
+        self.counters.max_walk.store(0, Ordering::Relaxed);
+    }
+}
+
+#[cfg(all(test, feature = "stats"))]
+mod tests {
+    use crate::PerfectRng;
+
+    #[test]
+    fn counts_walks() {
+        let randomizer = PerfectRng::with_key(1000u64, [1, 2], 4);
+        let mut walks = 0;
+        for i in 0..1000 {
+            let mut c = randomizer.encrypt_raw(i);
+            while c >= 1000 {
+                c = randomizer.encrypt_raw(c);
+                walks += 1;
+            }
+        }
+        let _ = randomizer.iter().count();
+        let stats = randomizer.stats();
+        assert_eq!(stats.calls, 1000);
+        assert_eq!(stats.walks, walks);
+        assert!(stats.max_walk > 0);
+
+        randomizer.reset_stats();
+        let _ = randomizer.unshuffle(5);
+        assert_eq!(randomizer.stats().calls, 1);
+
+        let exact = PerfectRng::with_key(1024u64, [1, 2], 4);
+        let mut values = (0..1024).collect::<Vec<_>>();
+        exact.shuffle_batch(&mut values);
+        let _ = exact.shuffle(0);
+        assert_eq!(exact.stats().walks, 0);
+    }
+}
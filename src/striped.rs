@@ -0,0 +1,263 @@
+use std::iter::FusedIterator;
+
+use crate::{PerfectRng, PerfectRngError, UnsignedInt};
+
+/// A permutation of `0..range` that's split into contiguous stripes, where
+/// the output goes through every stripe once before coming back to any of
+/// them.
+///
+/// This is a stronger politeness guarantee than a plain [`PerfectRng`], which
+/// only makes it unlikely for consecutive values to be close together. For
+/// example, shuffling every IPv4 address with 65536 stripes makes every
+/// stripe a /16, so no /16 gets more than one probe in any run of 65536.
+///
+/// The stripes are visited in the same shuffled order every time, so two
+/// values from the same stripe are always exactly `stripes` apart. If `range`
+/// isn't a multiple of the number of stripes, the first `range % stripes`
+/// stripes have one more value than the others.
+///
+/// ```
+/// # use perfect_rand::StripedRng;
+/// let striped = StripedRng::new(1000u64, 10).unwrap();
+/// let values = striped.iter().collect::<Vec<_>>();
+/// // every run of 10 values has one value from each stripe of 100
+/// for run in values.chunks(10) {
+///     let mut stripes = run.iter().map(|value| value / 100).collect::<Vec<_>>();
+///     stripes.sort();
+///     assert_eq!(stripes, (0..10).collect::<Vec<_>>());
+/// }
+/// ```
+#[derive(Debug)]
+pub struct StripedRng<T: UnsignedInt = u64> {
+    range: T,
+    /// The number of stripes.
+    count: u128,
+    /// The number of values in the shorter stripes.
+    width: u128,
+    /// The number of stripes that have `width + 1` values, which come first.
+    long: u128,
+    /// Shuffles the order that the long stripes are visited in, which is
+    /// before the short ones in every round so the last round (which only
+    /// has long stripes) keeps the same order. This is `None` if there are no
+    /// long stripes.
+    long_order: Option<PerfectRng<T>>,
+    /// Shuffles the order that the short stripes are visited in.
+    short_order: PerfectRng<T>,
+    /// Shuffles the values in each stripe.
+    rows: PerfectRng<T>,
+}
+
+impl<T: UnsignedInt> StripedRng<T> {
+    /// Create a striped permutation with a random key and default rounds.
+    ///
+    /// This returns an error if `range` or `stripes` is 0.
+    pub fn new(range: T, stripes: T) -> Result<Self, PerfectRngError> {
+        Self::with_key(range, stripes, [T::random(), T::random()], 4)
+    }
+
+    /// Create a striped permutation keyed like [`PerfectRng::with_key`]. See
+    /// [`StripedRng::new`].
+    pub fn with_key(
+        range: T,
+        stripes: T,
+        key: [T; 2],
+        rounds: usize,
+    ) -> Result<Self, PerfectRngError> {
+        if range == T::ZERO || stripes == T::ZERO {
+            return Err(PerfectRngError::ZeroRange);
+        }
+        // more stripes than values would make some of them empty
+        let count = range.min(stripes).as_u128();
+        let (width, long) = (range.as_u128() / count, range.as_u128() % count);
+
+        // the permutations are independent even though they share a key
+        let long_order = (long > 0)
+            .then(|| PerfectRng::try_with_key(T::from_u128(long), key, rounds))
+            .transpose()?
+            .map(|rng| rng.with_tweak(b"long stripes"));
+        let short_order = PerfectRng::try_with_key(T::from_u128(count - long), key, rounds)?
+            .with_tweak(b"short stripes");
+        let rows_len = T::from_u128(width + u128::from(long > 0));
+        let rows = PerfectRng::try_with_key(rows_len, key, rounds)?.with_tweak(b"rows");
+        Ok(StripedRng {
+            range,
+            count,
+            width,
+            long,
+            long_order,
+            short_order,
+            rows,
+        })
+    }
+
+    /// The number of values in each of the shorter stripes. The first
+    /// `range % stripes` stripes have one more.
+    #[must_use]
+    pub fn stripe_width(&self) -> T {
+        T::from_u128(self.width)
+    }
+
+    /// Get the value at a position in the striped order.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` isn't less than the range.
+    #[must_use]
+    pub fn shuffle(&self, index: T) -> T {
+        assert!(index < self.range, "the index must be less than the range");
+        let (row, column) = (index.as_u128() / self.count, index.as_u128() % self.count);
+        let (start, len) = match &self.long_order {
+            Some(long_order) if column < self.long => {
+                let stripe = long_order.shuffle(T::from_u128(column)).as_u128();
+                (stripe * (self.width + 1), self.width + 1)
+            }
+            _ => {
+                let stripe = self
+                    .short_order
+                    .shuffle(T::from_u128(column - self.long))
+                    .as_u128();
+                (
+                    self.long * (self.width + 1) + stripe * self.width,
+                    self.width,
+                )
+            }
+        };
+
+        // rotating the row by the column keeps it a permutation of each
+        // stripe, while making the stripes in the same row look unrelated.
+        // the short stripes cycle walk, since the rows are shuffled with the
+        // width of the long stripes.
+        let mut offset = self
+            .rows
+            .shuffle(T::from_u128((row + column) % len))
+            .as_u128();
+        while offset >= len {
+            offset = self.rows.shuffle(T::from_u128(offset)).as_u128();
+        }
+        T::from_u128(start + offset)
+    }
+
+    /// Iterate over every value in `0..range` in striped order.
+    #[must_use]
+    pub fn iter(&self) -> StripedIter<'_, T> {
+        StripedIter {
+            rng: self,
+            index: T::ZERO,
+        }
+    }
+}
+
+impl<'a, T: UnsignedInt> IntoIterator for &'a StripedRng<T> {
+    type Item = T;
+    type IntoIter = StripedIter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// An iterator over every value of a [`StripedRng`].
+///
+/// This is created by [`StripedRng::iter`].
+#[derive(Debug, Clone)]
+pub struct StripedIter<'a, T: UnsignedInt = u64> {
+    rng: &'a StripedRng<T>,
+    /// The index of the next value.
+    index: T,
+}
+
+impl<T: UnsignedInt> Iterator for StripedIter<'_, T> {
+    type Item = T;
+
+    #[inline]
+    fn next(&mut self) -> Option<T> {
+        if self.index == self.rng.range {
+            return None;
+        }
+        let value = self.rng.shuffle(self.index);
+        self.index = self.index + T::ONE;
+        Some(value)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        match usize::try_from((self.rng.range - self.index).as_u128()) {
+            Ok(remaining) => (remaining, Some(remaining)),
+            Err(_) => (usize::MAX, None),
+        }
+    }
+}
+
+impl<T: UnsignedInt> ExactSizeIterator for StripedIter<'_, T> {}
+
+impl<T: UnsignedInt> FusedIterator for StripedIter<'_, T> {}
+
+#[cfg(test)]
+mod tests {
+    use super::StripedRng;
+    use crate::PerfectRngError;
+
+    fn check(range: u32, stripes: u32) {
+        let striped = StripedRng::with_key(range, stripes, [1, 2], 4).unwrap();
+        let (width, long) = (striped.stripe_width(), range % stripes.min(range));
+        let values = striped.iter().collect::<Vec<_>>();
+        assert_eq!(values.len(), range as usize);
+
+        let mut sorted = values.clone();
+        sorted.sort_unstable();
+        assert!(sorted.into_iter().eq(0..range));
+
+        let stripe_of = |value: u32| {
+            if value < long * (width + 1) {
+                value / (width + 1)
+            } else {
+                long + (value - long * (width + 1)) / width
+            }
+        };
+        let stripes = stripes.min(range) as usize;
+        let mut last_seen = vec![None; stripes];
+        for (i, value) in values.into_iter().enumerate() {
+            let stripe = stripe_of(value) as usize;
+            if let Some(last) = last_seen[stripe] {
+                assert_eq!(i - last, stripes);
+            }
+            last_seen[stripe] = Some(i);
+        }
+    }
+
+    #[test]
+    fn stripes() {
+        check(1000, 10);
+        check(1000, 7);
+        check(1 << 16, 256);
+        check(10, 100);
+        check(10, 9);
+        check(10, 4);
+        check(1, 1);
+        check(5000, 1);
+    }
+
+    #[test]
+    fn full_u32() {
+        let striped = StripedRng::with_key(u32::MAX, 1 << 16, [1, 2], 4).unwrap();
+        assert_eq!(striped.stripe_width(), (1 << 16) - 1);
+        let slash_16s = striped
+            .iter()
+            .take(1 << 16)
+            .map(|value| value >> 16)
+            .collect::<std::collections::HashSet<_>>();
+        assert_eq!(slash_16s.len(), 1 << 16);
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(
+            StripedRng::new(0u64, 10).unwrap_err(),
+            PerfectRngError::ZeroRange
+        );
+        assert_eq!(
+            StripedRng::new(10u64, 0).unwrap_err(),
+            PerfectRngError::ZeroRange
+        );
+    }
+}
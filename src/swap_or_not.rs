@@ -0,0 +1,103 @@
+//! The swap-or-not shuffle from Hoang, Morris, and Rogaway's
+//! [An Enciphering Scheme Based on a Card Shuffle](https://eprint.iacr.org/2012/475),
+//! for [`Backend::SwapOrNot`](crate::Backend::SwapOrNot).
+//!
+//! Every round picks a key `k` in the domain and pairs each `x` with
+//! `k - x`. A keyed bit of the larger value in each pair decides whether the
+//! pair is swapped. Since this works on any domain size directly, it never has
+//! to cycle walk, and each round is its own inverse.
+
+use crate::{PerfectRng, UnsignedInt};
+
+impl<T: UnsignedInt> PerfectRng<T> {
+    /// The pseudorandom function that the round keys and swap decisions are
+    /// made from. Every call has a different `i`, so they're independent.
+    #[inline]
+    fn swap_or_not_prf(&self, i: usize, x: T) -> T {
+        T::round(
+            self.round_keys[0],
+            T::from_usize(i) ^ self.tweak,
+            x,
+            self.siprounds,
+        )
+    }
+
+    /// Do swap-or-not round `r`.
+    #[inline]
+    fn swap_or_not_round(&self, r: usize, x: T) -> T {
+        let mut k = self.swap_or_not_prf(2 * r, T::ZERO);
+        if self.max != T::MAX {
+            k = T::from_u128(k.as_u128() % (self.max.as_u128() + 1));
+        }
+
+        // k - x mod range, where the range can also be the whole of T
+        let partner = if x <= k {
+            k - x
+        } else {
+            k.wrapping_sub(x)
+                .wrapping_add(self.max)
+                .wrapping_add(T::ONE)
+        };
+        let larger = x.max(partner);
+        if self.swap_or_not_prf(2 * r + 1, larger) & T::ONE == T::ONE {
+            partner
+        } else {
+            x
+        }
+    }
+
+    #[inline]
+    pub(crate) fn swap_or_not_encrypt(&self, m: T) -> T {
+        (0..self.card_shuffle_rounds()).fold(m, |x, r| self.swap_or_not_round(r, x))
+    }
+
+    #[inline]
+    pub(crate) fn swap_or_not_decrypt(&self, c: T) -> T {
+        (0..self.card_shuffle_rounds())
+            .rev()
+            .fold(c, |x, r| self.swap_or_not_round(r, x))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Backend, PerfectRng};
+
+    #[test]
+    fn is_a_permutation() {
+        for range in [1u64, 2, 3, 10, 1000] {
+            for rounds in [1, 4] {
+                let randomizer =
+                    PerfectRng::with_key(range, [1, 2], rounds).with_backend(Backend::SwapOrNot);
+                let mut seen = vec![false; range as usize];
+                for i in 0..range {
+                    let x = randomizer.shuffle(i);
+                    assert_eq!(randomizer.unshuffle(x), i);
+                    assert!(!seen[x as usize]);
+                    seen[x as usize] = true;
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn full_domain() {
+        let randomizer = PerfectRng::<u16>::with_key_inclusive(0..=u16::MAX, [1, 2], 1)
+            .with_backend(Backend::SwapOrNot);
+        let mut seen = vec![false; 1 << 16];
+        for i in 0..=u16::MAX {
+            let x = randomizer.shuffle(i);
+            assert_eq!(randomizer.unshuffle(x), i);
+            assert!(!seen[x as usize]);
+            seen[x as usize] = true;
+        }
+    }
+
+    #[test]
+    fn never_cycle_walks() {
+        let randomizer = PerfectRng::with_key(1000u64, [1, 2], 4).with_backend(Backend::SwapOrNot);
+        for i in 0..1000 {
+            assert!(randomizer.encrypt(i) < 1000);
+        }
+    }
+}
@@ -0,0 +1,147 @@
+use crate::{PerfectRng, UnsignedInt};
+
+/// The biggest range that [`TableRng::new`] precomputes a table for. Past
+/// this, the tables stop fitting in the CPU's caches and a lookup ends up
+/// slower than encrypting.
+pub const DEFAULT_TABLE_THRESHOLD: usize = 1 << 18;
+
+/// A [`PerfectRng`] that precomputes its whole permutation if the range is
+/// small enough, so shuffling and unshuffling are array lookups.
+///
+/// The tables are filled from the [`PerfectRng`], so it's the same
+/// permutation whether or not they're used. Ranges over the threshold don't
+/// allocate anything and shuffle with the cipher like usual.
+///
+/// ```
+/// # use perfect_rand::{PerfectRng, TableRng};
+/// let key = rand::random();
+/// let table = TableRng::new(PerfectRng::with_key(1000u64, key, 4));
+/// assert!(table.is_table());
+/// assert_eq!(table.shuffle(5), PerfectRng::with_key(1000u64, key, 4).shuffle(5));
+///
+/// let big = TableRng::new(PerfectRng::from_range(2u64.pow(32)));
+/// assert!(!big.is_table());
+/// ```
+#[derive(Debug)]
+pub struct TableRng<T: UnsignedInt = u64> {
+    rng: PerfectRng<T>,
+    /// The shuffled value for every index, or empty if the range is over the
+    /// threshold.
+    shuffled: Vec<T>,
+    /// The index for every shuffled value, without the offset.
+    unshuffled: Vec<T>,
+}
+
+impl<T: UnsignedInt> TableRng<T> {
+    /// Precompute the permutation if the range is at most
+    /// [`DEFAULT_TABLE_THRESHOLD`].
+    #[must_use]
+    pub fn new(rng: PerfectRng<T>) -> Self {
+        Self::with_threshold(rng, DEFAULT_TABLE_THRESHOLD)
+    }
+
+    /// Precompute the permutation if the range is at most `threshold`. The
+    /// tables take `2 * range * size_of::<T>()` bytes.
+    #[must_use]
+    pub fn with_threshold(rng: PerfectRng<T>, threshold: usize) -> Self {
+        let (mut shuffled, mut unshuffled) = (Vec::new(), Vec::new());
+        if rng.max.as_u128() < threshold as u128 {
+            let len = rng.max.as_u128() as usize + 1;
+            shuffled = vec![T::ZERO; len];
+            unshuffled = vec![T::ZERO; len];
+            rng.shuffle_n(rng.offset, &mut shuffled);
+            for (i, &c) in shuffled.iter().enumerate() {
+                unshuffled[(c - rng.offset).as_u128() as usize] = T::from_usize(i);
+            }
+        }
+        TableRng {
+            rng,
+            shuffled,
+            unshuffled,
+        }
+    }
+
+    /// Whether the permutation was precomputed.
+    #[must_use]
+    pub fn is_table(&self) -> bool {
+        !self.shuffled.is_empty()
+    }
+
+    /// The [`PerfectRng`] that the permutation comes from.
+    #[must_use]
+    pub fn rng(&self) -> &PerfectRng<T> {
+        &self.rng
+    }
+
+    /// Randomize your input. See [`PerfectRng::shuffle`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `m` isn't in the range.
+    #[must_use]
+    #[inline]
+    pub fn shuffle(&self, m: T) -> T {
+        if !self.is_table() {
+            return self.rng.shuffle(m);
+        }
+        assert!(m >= self.rng.offset && m - self.rng.offset <= self.rng.max);
+        self.shuffled[(m - self.rng.offset).as_u128() as usize]
+    }
+
+    /// Reverse [`TableRng::shuffle`]. See [`PerfectRng::unshuffle`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `c` isn't in the range.
+    #[must_use]
+    #[inline]
+    pub fn unshuffle(&self, c: T) -> T {
+        if !self.is_table() {
+            return self.rng.unshuffle(c);
+        }
+        assert!(c >= self.rng.offset && c - self.rng.offset <= self.rng.max);
+        self.unshuffled[(c - self.rng.offset).as_u128() as usize] + self.rng.offset
+    }
+}
+
+impl<T: UnsignedInt> From<PerfectRng<T>> for TableRng<T> {
+    fn from(rng: PerfectRng<T>) -> Self {
+        Self::new(rng)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::TableRng;
+    use crate::PerfectRng;
+
+    #[test]
+    fn same_as_perfect_rng() {
+        let rng = || PerfectRng::with_key_inclusive(100..=1099u32, [1, 2], 4);
+        let table = TableRng::new(rng());
+        assert!(table.is_table());
+        let cipher = TableRng::with_threshold(rng(), 999);
+        assert!(!cipher.is_table());
+
+        for i in 100..1100 {
+            let x = table.shuffle(i);
+            assert_eq!(x, rng().shuffle(i));
+            assert_eq!(cipher.shuffle(i), x);
+            assert_eq!(table.unshuffle(x), i);
+            assert_eq!(cipher.unshuffle(x), i);
+        }
+    }
+
+    #[test]
+    fn full_u16() {
+        let table = TableRng::from(PerfectRng::with_key_inclusive(0..=u16::MAX, [1, 2], 4));
+        assert!(table.is_table());
+        assert_eq!(table.unshuffle(table.shuffle(u16::MAX)), u16::MAX);
+    }
+
+    #[test]
+    #[should_panic]
+    fn out_of_range() {
+        let _ = TableRng::new(PerfectRng::from_range(10u64)).shuffle(10);
+    }
+}
@@ -0,0 +1,590 @@
+use std::{iter::FusedIterator, net::Ipv4Addr, ops::RangeInclusive, str::FromStr};
+
+use crate::{DomainSet, PerfectRng, PerfectRngError};
+
+/// A set of IPv4 addresses built from CIDR blocks, ranges, and single
+/// addresses, like the targets of a scan.
+///
+/// Overlapping and adjacent ranges are merged as they're added, so every
+/// address is only shuffled once no matter how many times it was added.
+/// Addresses can also be excluded, like private networks or hosts that asked
+/// not to be scanned, and they're removed no matter whether they're added
+/// before or after the exclusion.
+///
+/// ```
+/// # use std::net::Ipv4Addr;
+/// # use perfect_rand::TargetSet;
+/// let mut targets = TargetSet::new();
+/// targets.add_net(Ipv4Addr::new(10, 0, 0, 0), 24);
+/// targets.add_range(Ipv4Addr::new(10, 0, 0, 200)..=Ipv4Addr::new(10, 0, 1, 9));
+/// targets.add_addr(Ipv4Addr::new(192, 168, 0, 1));
+/// assert_eq!(targets.len(), 267);
+///
+/// for ip in targets.shuffled().unwrap() {
+///     assert!(targets.contains(ip));
+/// }
+/// ```
+///
+/// It can also be parsed from a list of targets separated by commas or
+/// whitespace, in the same format as masscan:
+///
+/// ```
+/// # use perfect_rand::TargetSet;
+/// let targets = "10.0.0.0/24, 192.168.0.1-192.168.0.10 1.1.1.1"
+///     .parse::<TargetSet>()
+///     .unwrap();
+/// assert_eq!(targets.len(), 267);
+/// ```
+///
+/// ```
+/// # use std::net::Ipv4Addr;
+/// # use perfect_rand::TargetSet;
+/// let mut targets = TargetSet::new();
+/// targets.add_net(Ipv4Addr::new(10, 0, 0, 0), 24);
+/// targets.exclude_addr(Ipv4Addr::new(10, 0, 0, 1));
+/// assert_eq!(targets.len(), 255);
+/// assert!(!targets.contains(Ipv4Addr::new(10, 0, 0, 1)));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct TargetSet {
+    included: RangeList,
+    excluded: RangeList,
+}
+
+impl TargetSet {
+    /// Create an empty set.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add every address in a range. Nothing is added if the range is empty.
+    pub fn add_range(&mut self, range: RangeInclusive<Ipv4Addr>) {
+        self.included
+            .insert(u32::from(*range.start())..=u32::from(*range.end()));
+    }
+
+    /// Add every address in a CIDR block. The host bits of `network` are
+    /// ignored.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `prefix_len` is more than 32.
+    pub fn add_net(&mut self, network: Ipv4Addr, prefix_len: u8) {
+        self.add_range(net_range(network, prefix_len));
+    }
+
+    /// Add a single address.
+    pub fn add_addr(&mut self, addr: Ipv4Addr) {
+        self.add_range(addr..=addr);
+    }
+
+    /// Exclude every address in a range, so it's never in the set even if
+    /// it's added later.
+    pub fn exclude_range(&mut self, range: RangeInclusive<Ipv4Addr>) {
+        self.excluded
+            .insert(u32::from(*range.start())..=u32::from(*range.end()));
+    }
+
+    /// Exclude every address in a CIDR block. See
+    /// [`TargetSet::exclude_range`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `prefix_len` is more than 32.
+    pub fn exclude_net(&mut self, network: Ipv4Addr, prefix_len: u8) {
+        self.exclude_range(net_range(network, prefix_len));
+    }
+
+    /// Exclude a single address. See [`TargetSet::exclude_range`].
+    pub fn exclude_addr(&mut self, addr: Ipv4Addr) {
+        self.exclude_range(addr..=addr);
+    }
+
+    /// Exclude every address in another set, like one parsed from an
+    /// exclude file. See [`TargetSet::exclude_range`].
+    ///
+    /// ```
+    /// # use perfect_rand::TargetSet;
+    /// let mut targets = "0.0.0.0/0".parse::<TargetSet>().unwrap();
+    /// targets.exclude(&"10.0.0.0/8 172.16.0.0/12 192.168.0.0/16".parse().unwrap());
+    /// assert_eq!(targets.len(), (1 << 32) - (1 << 24) - (1 << 20) - (1 << 16));
+    /// ```
+    pub fn exclude(&mut self, other: &TargetSet) {
+        for range in other.ranges() {
+            self.exclude_range(range);
+        }
+    }
+
+    /// The number of addresses in the set.
+    #[must_use]
+    pub fn len(&self) -> u64 {
+        self.merged()
+            .iter()
+            .map(|range| u64::from(range.end() - range.start()) + 1)
+            .sum()
+    }
+
+    /// Whether the set has no addresses.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.merged().is_empty()
+    }
+
+    /// Whether the address is in the set.
+    #[must_use]
+    pub fn contains(&self, addr: Ipv4Addr) -> bool {
+        let addr = u32::from(addr);
+        self.included.contains(addr) && !self.excluded.contains(addr)
+    }
+
+    /// The merged ranges in the set with the exclusions removed, in
+    /// ascending order.
+    pub fn ranges(&self) -> impl Iterator<Item = RangeInclusive<Ipv4Addr>> {
+        self.merged()
+            .into_iter()
+            .map(|range| Ipv4Addr::from(*range.start())..=Ipv4Addr::from(*range.end()))
+    }
+
+    /// Iterate over every address in the set in shuffled order, with a
+    /// random key and default rounds.
+    ///
+    /// This returns an error if the set is empty.
+    pub fn shuffled(&self) -> Result<ShuffledTargets, PerfectRngError> {
+        self.shuffled_with_key(rand::random(), 4)
+    }
+
+    /// Iterate over every address in the set in shuffled order, keyed like
+    /// [`PerfectRng::with_key`]. See [`TargetSet::shuffled`].
+    pub fn shuffled_with_key(
+        &self,
+        key: [u64; 2],
+        rounds: usize,
+    ) -> Result<ShuffledTargets, PerfectRngError> {
+        // the ranges are widened to u64 so 255.255.255.255 fits in an
+        // exclusive range
+        let ranges = self
+            .merged()
+            .into_iter()
+            .map(|range| u64::from(*range.start())..u64::from(*range.end()) + 1);
+        Ok(ShuffledTargets {
+            set: DomainSet::with_key(ranges, key, rounds)?,
+            index: 0,
+        })
+    }
+
+    /// Iterate over every `(address, port)` pair of an address in the set
+    /// and one of the ports in shuffled order, with a random key and default
+    /// rounds.
+    ///
+    /// Like [`ProductDomain`](crate::ProductDomain), the hosts and ports are
+    /// shuffled together, so the probes to a single host are spread out over
+    /// the whole scan instead of being sent in a burst.
+    ///
+    /// ```
+    /// # use perfect_rand::TargetSet;
+    /// let targets = "10.0.0.0/24".parse::<TargetSet>().unwrap();
+    /// let pairs = targets.shuffled_ports(vec![80, 443]).unwrap();
+    /// assert_eq!(pairs.count(), 512);
+    /// ```
+    ///
+    /// This returns an error if the set or the ports are empty.
+    pub fn shuffled_ports(&self, ports: Vec<u16>) -> Result<ShuffledTargetPorts, PerfectRngError> {
+        self.shuffled_ports_with_key(ports, rand::random(), 4)
+    }
+
+    /// Iterate over every `(address, port)` pair in shuffled order, keyed like
+    /// [`PerfectRng::with_key`]. See [`TargetSet::shuffled_ports`].
+    pub fn shuffled_ports_with_key(
+        &self,
+        ports: Vec<u16>,
+        key: [u64; 2],
+        rounds: usize,
+    ) -> Result<ShuffledTargetPorts, PerfectRngError> {
+        let ranges = self.merged();
+        let mut starts = Vec::with_capacity(ranges.len());
+        let mut address_count = 0;
+        for range in &ranges {
+            starts.push(address_count);
+            address_count += u64::from(range.end() - range.start()) + 1;
+        }
+        let len = address_count
+            .checked_mul(ports.len() as u64)
+            .ok_or(PerfectRngError::DomainTooLarge)?;
+        let rng = PerfectRng::try_with_key(len, key, rounds)?;
+        Ok(ShuffledTargetPorts {
+            ranges,
+            starts,
+            address_count,
+            ports,
+            rng,
+            index: 0,
+        })
+    }
+
+    /// The included ranges with the excluded ones removed from them.
+    fn merged(&self) -> Vec<RangeInclusive<u32>> {
+        let excluded = &self.excluded.0;
+        let mut merged = Vec::with_capacity(self.included.0.len());
+        for range in &self.included.0 {
+            // this is None once an exclusion reaches 255.255.255.255
+            let mut start = Some(*range.start());
+            // the first exclusion that doesn't end before this range
+            let mut i = excluded.partition_point(|excluded| excluded.end() < range.start());
+            while let (Some(next), Some(excluded)) =
+                (start, excluded.get(i).filter(|e| e.start() <= range.end()))
+            {
+                if *excluded.start() > next {
+                    merged.push(next..=excluded.start() - 1);
+                }
+                start = excluded.end().checked_add(1);
+                i += 1;
+            }
+            if let Some(start) = start.filter(|start| start <= range.end()) {
+                merged.push(start..=*range.end());
+            }
+        }
+        merged
+    }
+}
+
+impl PartialEq for TargetSet {
+    fn eq(&self, other: &Self) -> bool {
+        self.merged() == other.merged()
+    }
+}
+
+impl Eq for TargetSet {}
+
+/// Sorted inclusive ranges, with no two overlapping or adjacent.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct RangeList(pub(crate) Vec<RangeInclusive<u32>>);
+
+impl RangeList {
+    pub(crate) fn insert(&mut self, range: RangeInclusive<u32>) {
+        let (start, end) = range.into_inner();
+        if start > end {
+            return;
+        }
+
+        // the ranges that overlap or are adjacent to the new one get merged
+        // into it
+        let ranges = &mut self.0;
+        let i = ranges.partition_point(|range| range.end().saturating_add(1) < start);
+        let j = ranges.partition_point(|range| *range.start() <= end.saturating_add(1));
+        let (start, end) = if i < j {
+            (start.min(*ranges[i].start()), end.max(*ranges[j - 1].end()))
+        } else {
+            (start, end)
+        };
+        ranges.splice(i..j, [start..=end]);
+    }
+
+    pub(crate) fn contains(&self, value: u32) -> bool {
+        let i = self.0.partition_point(|range| *range.end() < value);
+        self.0.get(i).is_some_and(|range| range.contains(&value))
+    }
+}
+
+/// The addresses in a CIDR block.
+fn net_range(network: Ipv4Addr, prefix_len: u8) -> RangeInclusive<Ipv4Addr> {
+    assert!(prefix_len <= 32, "the prefix length must be at most 32");
+    let host_mask = u32::MAX.checked_shr(u32::from(prefix_len)).unwrap_or(0);
+    let start = u32::from(network) & !host_mask;
+    Ipv4Addr::from(start)..=Ipv4Addr::from(start | host_mask)
+}
+
+impl FromStr for TargetSet {
+    type Err = PerfectRngError;
+
+    /// Parse a list of targets separated by commas or whitespace, where each
+    /// target is an address (`10.0.0.1`), a CIDR block (`10.0.0.0/8`), or an
+    /// inclusive range (`10.0.0.1-10.0.0.20`).
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut targets = TargetSet::new();
+        for target in s
+            .split(|c: char| c == ',' || c.is_whitespace())
+            .filter(|target| !target.is_empty())
+        {
+            if let Some((network, prefix_len)) = target.split_once('/') {
+                let prefix_len = prefix_len
+                    .parse::<u8>()
+                    .ok()
+                    .filter(|&prefix_len| prefix_len <= 32)
+                    .ok_or(PerfectRngError::InvalidTarget)?;
+                targets.add_net(parse_addr(network)?, prefix_len);
+            } else if let Some((start, end)) = target.split_once('-') {
+                let (start, end) = (parse_addr(start)?, parse_addr(end)?);
+                if start > end {
+                    return Err(PerfectRngError::InvalidTarget);
+                }
+                targets.add_range(start..=end);
+            } else {
+                targets.add_addr(parse_addr(target)?);
+            }
+        }
+        Ok(targets)
+    }
+}
+
+fn parse_addr(s: &str) -> Result<Ipv4Addr, PerfectRngError> {
+    s.parse().map_err(|_| PerfectRngError::InvalidTarget)
+}
+
+impl Extend<Ipv4Addr> for TargetSet {
+    fn extend<I: IntoIterator<Item = Ipv4Addr>>(&mut self, iter: I) {
+        for addr in iter {
+            self.add_addr(addr);
+        }
+    }
+}
+
+impl FromIterator<Ipv4Addr> for TargetSet {
+    fn from_iter<I: IntoIterator<Item = Ipv4Addr>>(iter: I) -> Self {
+        let mut targets = TargetSet::new();
+        targets.extend(iter);
+        targets
+    }
+}
+
+/// An iterator over every address in a [`TargetSet`], in shuffled order.
+///
+/// This is created by [`TargetSet::shuffled`].
+#[derive(Debug)]
+pub struct ShuffledTargets {
+    set: DomainSet<u64>,
+    index: u64,
+}
+
+impl ShuffledTargets {
+    /// The [`PerfectRng`] that shuffles the addresses, where each index is
+    /// the position of an address in the sorted set.
+    #[must_use]
+    pub fn rng(&self) -> &PerfectRng<u64> {
+        self.set.rng()
+    }
+}
+
+impl Iterator for ShuffledTargets {
+    type Item = Ipv4Addr;
+
+    #[inline]
+    fn next(&mut self) -> Option<Ipv4Addr> {
+        if self.index == self.set.len() {
+            return None;
+        }
+        let addr = self.set.shuffle(self.index);
+        self.index += 1;
+        // every value in the set came from a u32
+        Some(Ipv4Addr::from(addr as u32))
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.set.len() - self.index;
+        match usize::try_from(remaining) {
+            Ok(remaining) => (remaining, Some(remaining)),
+            Err(_) => (usize::MAX, None),
+        }
+    }
+}
+
+impl FusedIterator for ShuffledTargets {}
+
+/// An iterator over every `(address, port)` pair of a [`TargetSet`] and a
+/// list of ports, in shuffled order.
+///
+/// This is created by [`TargetSet::shuffled_ports`].
+#[derive(Debug)]
+pub struct ShuffledTargetPorts {
+    ranges: Vec<RangeInclusive<u32>>,
+    /// The index of the first address of each range.
+    starts: Vec<u64>,
+    address_count: u64,
+    ports: Vec<u16>,
+    rng: PerfectRng<u64>,
+    index: u64,
+}
+
+impl ShuffledTargetPorts {
+    /// The [`PerfectRng`] that shuffles the pairs. Like masscan, the address
+    /// is the fastest-changing part of each index.
+    #[must_use]
+    pub fn rng(&self) -> &PerfectRng<u64> {
+        &self.rng
+    }
+
+    fn pair_at(&self, index: u64) -> (Ipv4Addr, u16) {
+        let address_index = index % self.address_count;
+        let i = self.starts.partition_point(|&start| start <= address_index) - 1;
+        // the offset is less than the length of the range, so it fits in u32
+        let address = self.ranges[i].start() + (address_index - self.starts[i]) as u32;
+        let port = self.ports[(index / self.address_count) as usize];
+        (Ipv4Addr::from(address), port)
+    }
+}
+
+impl Iterator for ShuffledTargetPorts {
+    type Item = (Ipv4Addr, u16);
+
+    #[inline]
+    fn next(&mut self) -> Option<(Ipv4Addr, u16)> {
+        if self.index > self.rng.max {
+            return None;
+        }
+        let pair = self.pair_at(self.rng.shuffle(self.index));
+        self.index += 1;
+        Some(pair)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.rng.max + 1 - self.index;
+        match usize::try_from(remaining) {
+            Ok(remaining) => (remaining, Some(remaining)),
+            Err(_) => (usize::MAX, None),
+        }
+    }
+}
+
+impl FusedIterator for ShuffledTargetPorts {}
+
+#[cfg(test)]
+mod tests {
+    use std::net::Ipv4Addr;
+
+    use super::TargetSet;
+    use crate::PerfectRngError;
+
+    #[test]
+    fn merges() {
+        let mut targets = TargetSet::new();
+        targets.add_net(Ipv4Addr::new(10, 0, 0, 77), 24);
+        targets.add_range(Ipv4Addr::new(10, 0, 2, 0)..=Ipv4Addr::new(10, 0, 2, 9));
+        targets.add_range(Ipv4Addr::new(10, 0, 1, 0)..=Ipv4Addr::new(10, 0, 1, 255));
+        targets.add_addr(Ipv4Addr::new(10, 0, 0, 5));
+        targets.add_addr(Ipv4Addr::new(1, 2, 3, 4));
+        targets.add_range(Ipv4Addr::new(1, 2, 3, 5)..=Ipv4Addr::new(1, 2, 3, 4));
+
+        assert!(targets.ranges().eq([
+            Ipv4Addr::new(1, 2, 3, 4)..=Ipv4Addr::new(1, 2, 3, 4),
+            Ipv4Addr::new(10, 0, 0, 0)..=Ipv4Addr::new(10, 0, 2, 9),
+        ]));
+        assert_eq!(targets.len(), 523);
+        assert!(targets.contains(Ipv4Addr::new(10, 0, 1, 128)));
+        assert!(!targets.contains(Ipv4Addr::new(10, 0, 2, 10)));
+        assert!(!targets.contains(Ipv4Addr::new(1, 2, 3, 3)));
+
+        let mut shuffled = targets.shuffled_with_key([1, 2], 4).unwrap();
+        assert_eq!(shuffled.size_hint(), (523, Some(523)));
+        let mut ips = shuffled.by_ref().collect::<Vec<_>>();
+        assert_eq!(shuffled.next(), None);
+        ips.sort();
+        ips.dedup();
+        assert_eq!(ips.len(), 523);
+        assert!(ips.into_iter().all(|ip| targets.contains(ip)));
+    }
+
+    #[test]
+    fn full() {
+        let mut targets = TargetSet::new();
+        targets.add_addr(Ipv4Addr::BROADCAST);
+        targets.add_net(Ipv4Addr::UNSPECIFIED, 1);
+        targets.add_net(Ipv4Addr::new(128, 0, 0, 0), 1);
+        assert_eq!(targets.len(), 1 << 32);
+        assert_eq!(targets.ranges().count(), 1);
+        assert!(targets.shuffled().unwrap().next().is_some());
+    }
+
+    #[test]
+    fn exclude() {
+        let mut targets = TargetSet::new();
+        targets.exclude_addr(Ipv4Addr::new(10, 0, 0, 0));
+        targets.add_net(Ipv4Addr::new(10, 0, 0, 0), 24);
+        targets.exclude_range(Ipv4Addr::new(10, 0, 0, 10)..=Ipv4Addr::new(10, 0, 0, 19));
+        targets.exclude_net(Ipv4Addr::new(10, 0, 0, 128), 25);
+        targets.exclude_net(Ipv4Addr::new(192, 168, 0, 0), 16);
+        targets.add_addr(Ipv4Addr::new(192, 168, 1, 1));
+
+        assert!(targets.ranges().eq([
+            Ipv4Addr::new(10, 0, 0, 1)..=Ipv4Addr::new(10, 0, 0, 9),
+            Ipv4Addr::new(10, 0, 0, 20)..=Ipv4Addr::new(10, 0, 0, 127),
+        ]));
+        assert_eq!(targets.len(), 117);
+        assert!(!targets.contains(Ipv4Addr::new(192, 168, 1, 1)));
+        assert!(!targets.contains(Ipv4Addr::new(10, 0, 0, 15)));
+        assert!(targets.contains(Ipv4Addr::new(10, 0, 0, 20)));
+
+        let mut ips = targets.shuffled().unwrap().collect::<Vec<_>>();
+        ips.sort();
+        assert!(ips.into_iter().eq(targets.ranges().flatten()));
+
+        let mut everything = TargetSet::new();
+        everything.add_net(Ipv4Addr::UNSPECIFIED, 0);
+        everything.exclude_addr(Ipv4Addr::BROADCAST);
+        everything.exclude_addr(Ipv4Addr::UNSPECIFIED);
+        assert_eq!(everything.len(), (1 << 32) - 2);
+        everything.exclude(&everything.clone());
+        assert!(everything.is_empty());
+    }
+
+    #[test]
+    fn ports() {
+        let mut targets = TargetSet::new();
+        targets.add_net(Ipv4Addr::new(10, 0, 0, 0), 28);
+        targets.add_net(Ipv4Addr::new(10, 0, 1, 0), 28);
+        targets.exclude_addr(Ipv4Addr::new(10, 0, 0, 3));
+        let ports = vec![22, 80, 443];
+
+        let shuffled = targets
+            .shuffled_ports_with_key(ports.clone(), [1, 2], 4)
+            .unwrap();
+        assert_eq!(shuffled.size_hint(), (93, Some(93)));
+        let mut pairs = shuffled.collect::<Vec<_>>();
+        pairs.sort_unstable();
+        let mut expected = targets
+            .ranges()
+            .flatten()
+            .flat_map(|ip| ports.iter().map(move |&port| (ip, port)))
+            .collect::<Vec<_>>();
+        expected.sort_unstable();
+        assert_eq!(pairs, expected);
+
+        assert_eq!(
+            targets.shuffled_ports(vec![]).unwrap_err(),
+            PerfectRngError::ZeroRange
+        );
+    }
+
+    #[test]
+    fn parse() {
+        let targets = "10.0.0.0/30,10.0.0.8-10.0.0.9\n 8.8.8.8,,"
+            .parse::<TargetSet>()
+            .unwrap();
+        assert_eq!(
+            targets,
+            [
+                Ipv4Addr::new(8, 8, 8, 8),
+                Ipv4Addr::new(10, 0, 0, 0),
+                Ipv4Addr::new(10, 0, 0, 1),
+                Ipv4Addr::new(10, 0, 0, 2),
+                Ipv4Addr::new(10, 0, 0, 3),
+                Ipv4Addr::new(10, 0, 0, 9),
+                Ipv4Addr::new(10, 0, 0, 8),
+            ]
+            .into_iter()
+            .collect()
+        );
+
+        for invalid in ["10.0.0.0/33", "10.0.0.2-10.0.0.1", "10.0.0", "example.com"] {
+            assert_eq!(
+                invalid.parse::<TargetSet>().unwrap_err(),
+                PerfectRngError::InvalidTarget
+            );
+        }
+        assert_eq!(
+            TargetSet::new().shuffled().unwrap_err(),
+            PerfectRngError::ZeroRange
+        );
+    }
+}
@@ -0,0 +1,163 @@
+//! Known-answer vectors for [`PerfectRng`], with the `test_vectors`
+//! feature.
+//!
+//! These are part of the crate's semver contract: a release that changes
+//! any of them is a breaking change, since the same range, seed, and rounds
+//! would give a different permutation. If you store shuffled values and need
+//! them to stay the same across upgrades, check these in your own tests so
+//! an algorithm change is caught when you update the crate instead of in
+//! production.
+//!
+//! ```
+//! use perfect_rand::test_vectors;
+//!
+//! assert_eq!(test_vectors::check_all(), Ok(()));
+//! ```
+
+use crate::PerfectRng;
+
+/// One known output of [`PerfectRng::new`] with the default backend.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TestVector {
+    /// The range passed to [`PerfectRng::new`].
+    pub range: u64,
+    /// The seed passed to [`PerfectRng::new`].
+    pub seed: u64,
+    /// The number of rounds passed to [`PerfectRng::new`].
+    pub rounds: usize,
+    /// The value that's shuffled.
+    pub input: u64,
+    /// What [`PerfectRng::shuffle`] has to return for `input`.
+    pub output: u64,
+}
+
+impl TestVector {
+    /// The `PerfectRng` that this vector is for.
+    #[must_use]
+    pub fn rng(&self) -> PerfectRng {
+        PerfectRng::new(self.range, self.seed, self.rounds)
+    }
+
+    /// Whether this version of the crate shuffles and unshuffles like the
+    /// vector says.
+    #[must_use]
+    pub fn check(&self) -> bool {
+        let rng = self.rng();
+        rng.shuffle(self.input) == self.output && rng.unshuffle(self.output) == self.input
+    }
+}
+
+const fn v(range: u64, seed: u64, rounds: usize, input: u64, output: u64) -> TestVector {
+    TestVector {
+        range,
+        seed,
+        rounds,
+        input,
+        output,
+    }
+}
+
+/// Every known-answer vector. These cover ranges that are and aren't powers
+/// of two, the full range of `u64`, and a few round counts.
+pub const VECTORS: &[TestVector] = &[
+    v(1, 0, 4, 0, 0),
+    v(10, 0, 4, 0, 4),
+    v(10, 0, 4, 1, 8),
+    v(10, 0, 4, 5, 7),
+    v(10, 0, 4, 9, 0),
+    v(1000, 1234, 3, 0, 896),
+    v(1000, 1234, 3, 1, 883),
+    v(1000, 1234, 3, 500, 293),
+    v(1000, 1234, 3, 999, 117),
+    v(1000, 1234, 4, 0, 384),
+    v(1000, 1234, 4, 1, 883),
+    v(1000, 1234, 4, 500, 965),
+    v(1000, 1234, 4, 999, 469),
+    v(1024, 1, 4, 0, 312),
+    v(1024, 1, 4, 1, 328),
+    v(1024, 1, 4, 512, 341),
+    v(1024, 1, 4, 1023, 501),
+    v(1025, 1, 4, 0, 600),
+    v(1025, 1, 4, 1, 616),
+    v(1025, 1, 4, 512, 988),
+    v(1025, 1, 4, 1024, 1002),
+    v(65536, 3735928559, 2, 0, 56498),
+    v(65536, 3735928559, 2, 1, 12467),
+    v(65536, 3735928559, 2, 32768, 42876),
+    v(65536, 3735928559, 2, 65535, 43292),
+    v(4294967296, 3735928559, 4, 0, 3769209873),
+    v(4294967296, 3735928559, 4, 1, 3722024807),
+    v(4294967296, 3735928559, 4, 2147483648, 3225657235),
+    v(4294967296, 3735928559, 4, 4294967295, 3903960965),
+    v(1431655765, 42, 4, 0, 239908792),
+    v(1431655765, 42, 4, 1, 239624668),
+    v(1431655765, 42, 4, 715827882, 879934503),
+    v(1431655765, 42, 4, 1431655764, 1064639837),
+    v(18446744073709551615, 7, 4, 0, 9622056307051045750),
+    v(18446744073709551615, 7, 4, 1, 11894054488775319232),
+    v(
+        18446744073709551615,
+        7,
+        4,
+        9223372036854775807,
+        11014892686694660321,
+    ),
+    v(
+        18446744073709551615,
+        7,
+        4,
+        18446744073709551614,
+        229810824653716296,
+    ),
+    v(18446744073709551615, 7, 8, 0, 14853507271919799003),
+    v(18446744073709551615, 7, 8, 1, 7492163701948571400),
+    v(
+        18446744073709551615,
+        7,
+        8,
+        9223372036854775807,
+        3612880005859868726,
+    ),
+    v(
+        18446744073709551615,
+        7,
+        8,
+        18446744073709551614,
+        11944814040294631918,
+    ),
+    v(1000000007, 99, 1, 0, 7427),
+    v(1000000007, 99, 1, 1, 7428),
+    v(1000000007, 99, 1, 500000003, 499998726),
+    v(1000000007, 99, 1, 1000000006, 999986674),
+];
+
+/// Check every vector in [`VECTORS`], returning the first one that doesn't
+/// match.
+///
+/// # Errors
+///
+/// Returns the first [`TestVector`] that this version of the crate gives a
+/// different output for.
+pub fn check_all() -> Result<(), TestVector> {
+    match VECTORS.iter().find(|vector| !vector.check()) {
+        Some(vector) => Err(*vector),
+        None => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{check_all, VECTORS};
+
+    #[test]
+    fn vectors() {
+        assert_eq!(check_all(), Ok(()));
+        assert!(VECTORS
+            .iter()
+            .all(|v| v.input < v.range && v.output < v.range));
+
+        let mut wrong = VECTORS[0];
+        wrong.output ^= 1;
+        assert!(!wrong.check());
+    }
+}
@@ -0,0 +1,92 @@
+//! The Thorp shuffle, as analyzed by Morris, Rogaway, and Stegers in
+//! [How to Encipher Messages on a Small Domain](https://www.iacr.org/archive/crypto2009/56770282/56770282.pdf),
+//! for [`Backend::Thorp`](crate::Backend::Thorp).
+//!
+//! Every round cuts the deck into two halves and riffles them together,
+//! where a keyed bit decides which of each pair of cards goes first. The deck
+//! has to have an even number of cards, so odd ranges get an extra card that's
+//! removed by cycle walking. Every card's position in a round only depends on
+//! its own position, so cards can be shuffled in parallel.
+
+use crate::{PerfectRng, UnsignedInt};
+
+impl<T: UnsignedInt> PerfectRng<T> {
+    /// Half the number of cards, rounded up.
+    #[inline]
+    fn thorp_half(&self) -> T {
+        (self.max >> 1) + T::ONE
+    }
+
+    /// The keyed bit that decides whether the cards at `i` and `i + half` are
+    /// swapped in round `r`.
+    #[inline]
+    fn thorp_bit(&self, r: usize, i: T) -> T {
+        T::round(
+            self.round_keys[0],
+            T::from_usize(r) ^ self.tweak,
+            i,
+            self.siprounds,
+        ) & T::ONE
+    }
+
+    #[inline]
+    pub(crate) fn thorp_encrypt(&self, m: T) -> T {
+        let half = self.thorp_half();
+        (0..self.card_shuffle_rounds()).fold(m, |x, r| {
+            let (i, upper) = if x < half {
+                (x, T::ZERO)
+            } else {
+                (x - half, T::ONE)
+            };
+            (i << 1) + (self.thorp_bit(r, i) ^ upper)
+        })
+    }
+
+    #[inline]
+    pub(crate) fn thorp_decrypt(&self, c: T) -> T {
+        let half = self.thorp_half();
+        (0..self.card_shuffle_rounds()).rev().fold(c, |y, r| {
+            let i = y >> 1;
+            if (y & T::ONE) == self.thorp_bit(r, i) {
+                i
+            } else {
+                i + half
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Backend, PerfectRng};
+
+    #[test]
+    fn is_a_permutation() {
+        for range in [1u64, 2, 3, 10, 999, 1000] {
+            for rounds in [1, 4] {
+                let randomizer =
+                    PerfectRng::with_key(range, [1, 2], rounds).with_backend(Backend::Thorp);
+                let mut seen = vec![false; range as usize];
+                for i in 0..range {
+                    let x = randomizer.shuffle(i);
+                    assert_eq!(randomizer.unshuffle(x), i);
+                    assert!(!seen[x as usize]);
+                    seen[x as usize] = true;
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn full_domain() {
+        let randomizer = PerfectRng::<u16>::with_key_inclusive(0..=u16::MAX, [1, 2], 1)
+            .with_backend(Backend::Thorp);
+        let mut seen = vec![false; 1 << 16];
+        for i in 0..=u16::MAX {
+            let x = randomizer.shuffle(i);
+            assert_eq!(randomizer.unshuffle(x), i);
+            assert!(!seen[x as usize]);
+            seen[x as usize] = true;
+        }
+    }
+}
@@ -0,0 +1,235 @@
+use std::{
+    iter::FusedIterator,
+    thread,
+    time::{Duration, Instant},
+};
+
+/// An iterator adapter that yields at most a fixed number of items per
+/// second, like masscan's `--rate`.
+///
+/// Items are scheduled at even intervals from when the first one is taken.
+/// If the consumer stalls, the schedule doesn't fall more than one item
+/// behind, so the rate isn't exceeded to make up for the stall. Use
+/// [`Throttled::with_burst`] to allow a few items to be yielded back to back
+/// instead.
+///
+/// [`Iterator::next`] blocks the thread until the next item is due. Async
+/// code should use [`Throttled::try_next`] instead and wait with its own
+/// runtime's timer.
+///
+/// ```
+/// # use std::time::{Duration, Instant};
+/// # use perfect_rand::{PerfectRng, Throttled};
+/// let randomizer = PerfectRng::from_range(1000u64);
+/// let start = Instant::now();
+/// for value in Throttled::new(randomizer.iter(), 1000).take(11) {
+///     println!("{value}");
+/// }
+/// assert!(start.elapsed() >= Duration::from_millis(10));
+/// ```
+#[derive(Debug, Clone)]
+pub struct Throttled<I> {
+    iter: I,
+    per_second: u64,
+    /// When the first item was taken.
+    start: Option<Instant>,
+    /// The number of items that have been taken.
+    taken: u64,
+    /// How many items can be yielded back to back after a stall.
+    burst: u64,
+}
+
+impl<I: Iterator> Throttled<I> {
+    /// Limit an iterator to `per_second` items per second.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `per_second` is 0.
+    #[must_use]
+    pub fn new(iter: I, per_second: u64) -> Self {
+        assert!(per_second > 0, "the rate must not be 0");
+        Throttled {
+            iter,
+            per_second,
+            start: None,
+            taken: 0,
+            burst: 1,
+        }
+    }
+
+    /// Allow up to `burst` items to be yielded back to back when the
+    /// consumer has fallen behind, instead of the default of 1. The average
+    /// rate is the same, but a stall is caught up on with at most `burst`
+    /// items at once. The first `burst` items are also yielded without
+    /// waiting, like a full token bucket.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `burst` is 0.
+    ///
+    /// ```
+    /// # use perfect_rand::{PerfectRng, Throttled};
+    /// let randomizer = PerfectRng::from_range(1000u64);
+    /// let throttled = Throttled::new(randomizer.iter(), 1000).with_burst(10);
+    /// ```
+    #[must_use]
+    pub fn with_burst(mut self, burst: u64) -> Self {
+        assert!(burst > 0, "the burst must not be 0");
+        self.burst = burst;
+        self
+    }
+
+    /// The number of items that are yielded per second.
+    #[must_use]
+    pub fn rate(&self) -> u64 {
+        self.per_second
+    }
+
+    /// Change the number of items that are yielded per second, keeping the
+    /// items that were already taken at the old rate.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `per_second` is 0.
+    pub fn set_rate(&mut self, per_second: u64) {
+        assert!(per_second > 0, "the rate must not be 0");
+        self.start = self.start.map(|start| start + self.due_after());
+        self.taken = 0;
+        self.per_second = per_second;
+    }
+
+    /// How long until the next item is due, which is zero if it's due
+    /// already.
+    #[must_use]
+    pub fn time_until_next(&self) -> Duration {
+        match self.start {
+            Some(start) => (start + self.due_after()).saturating_duration_since(Instant::now()),
+            None => Duration::ZERO,
+        }
+    }
+
+    /// Get the next item if it's due, without blocking. If it isn't due yet,
+    /// this returns how long until it is.
+    ///
+    /// ```
+    /// # use perfect_rand::{PerfectRng, Throttled};
+    /// let randomizer = PerfectRng::from_range(1000u64);
+    /// let mut throttled = Throttled::new(randomizer.iter(), 1);
+    /// assert!(throttled.try_next().unwrap().is_some());
+    /// assert!(throttled.try_next().is_err());
+    /// ```
+    pub fn try_next(&mut self) -> Result<Option<I::Item>, Duration> {
+        let wait = self.time_until_next();
+        if !wait.is_zero() {
+            return Err(wait);
+        }
+        Ok(self.take_next())
+    }
+
+    /// The inner iterator.
+    #[must_use]
+    pub fn into_inner(self) -> I {
+        self.iter
+    }
+
+    /// How long after `start` the next item is due.
+    fn due_after(&self) -> Duration {
+        self.duration_of(self.taken)
+    }
+
+    /// How long it takes to yield `items` items at the current rate.
+    fn duration_of(&self, items: u64) -> Duration {
+        let nanos = u128::from(items) * 1_000_000_000 / u128::from(self.per_second);
+        Duration::from_nanos(u64::try_from(nanos).unwrap_or(u64::MAX))
+    }
+
+    fn take_next(&mut self) -> Option<I::Item> {
+        let item = self.iter.next()?;
+        let now = Instant::now();
+        // if the consumer stalled, start the schedule again as if this item
+        // were the first of a burst, so the items that were missed aren't
+        // all yielded at once
+        let slack = self.duration_of(self.burst - 1);
+        let late = self
+            .start
+            .is_none_or(|start| start + self.due_after() + slack < now);
+        if late {
+            self.start = Some(now.checked_sub(slack).unwrap_or(now));
+            self.taken = 0;
+        }
+        self.taken += 1;
+        Some(item)
+    }
+}
+
+impl<I: Iterator> Iterator for Throttled<I> {
+    type Item = I::Item;
+
+    fn next(&mut self) -> Option<I::Item> {
+        let wait = self.time_until_next();
+        if !wait.is_zero() {
+            thread::sleep(wait);
+        }
+        self.take_next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+}
+
+impl<I: ExactSizeIterator> ExactSizeIterator for Throttled<I> {}
+
+impl<I: FusedIterator> FusedIterator for Throttled<I> {}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use super::Throttled;
+
+    #[test]
+    fn limits_rate() {
+        let start = Instant::now();
+        let mut throttled = Throttled::new(0..100, 500);
+        assert_eq!(throttled.by_ref().take(26).count(), 26);
+        assert!(start.elapsed() >= Duration::from_millis(50));
+
+        throttled.set_rate(1_000_000);
+        let start = Instant::now();
+        assert_eq!(throttled.len(), 74);
+        assert!(throttled.eq(26..100));
+        assert!(start.elapsed() < Duration::from_millis(50));
+    }
+
+    #[test]
+    fn stall() {
+        // 50ms between items
+        let mut throttled = Throttled::new(0..100, 20);
+        assert_eq!(throttled.try_next(), Ok(Some(0)));
+        std::thread::sleep(Duration::from_millis(300));
+        assert_eq!(throttled.try_next(), Ok(Some(1)));
+        assert!(throttled.try_next().is_err());
+
+        let mut throttled = Throttled::new(0..100, 20).with_burst(3);
+        assert_eq!(throttled.try_next(), Ok(Some(0)));
+        std::thread::sleep(Duration::from_millis(300));
+        for i in 1..=3 {
+            assert_eq!(throttled.try_next(), Ok(Some(i)));
+        }
+        assert!(throttled.try_next().is_err());
+    }
+
+    #[test]
+    fn try_next() {
+        let mut throttled = Throttled::new(0..3, 10);
+        assert_eq!(throttled.try_next(), Ok(Some(0)));
+        let wait = throttled.try_next().unwrap_err();
+        assert!(wait <= Duration::from_millis(100) && !wait.is_zero());
+        std::thread::sleep(wait);
+        assert_eq!(throttled.try_next(), Ok(Some(1)));
+        assert_eq!(throttled.next(), Some(2));
+        std::thread::sleep(Duration::from_millis(100));
+        assert_eq!(throttled.try_next(), Ok(None));
+    }
+}
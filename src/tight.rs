@@ -0,0 +1,261 @@
+//! A domain split with division instead of bits, so the Feistel network's
+//! domain is barely bigger than the range.
+//!
+//! Splitting by bits pads the range to the next power of two, so a range
+//! that's just past one (like `2^32 + 1`) has to cycle walk almost half of
+//! its encryptions. Splitting into halves of sizes `a` and `b` with
+//! `a * b >= range` instead pads it by less than `a`, which is around the
+//! square root of the range. This is the same kind of split that
+//! [`Backend::Blackrock2`](crate::Backend::Blackrock2) does, but with the usual round functions.
+
+use crate::{PerfectRng, PerfectRngError, UnsignedInt};
+
+/// How many sizes below the square root are tried for `a`, to find halves
+/// that waste fewer values.
+const CANDIDATES: u128 = 64;
+
+impl<T: UnsignedInt> PerfectRng<T> {
+    /// Split the domain with division instead of bits, so almost nothing has
+    /// to be cycle walked. This is about twice as fast for ranges that are a
+    /// little past a power of two, but can be slower for ranges just below
+    /// one, where there's little walking to save and division isn't free.
+    ///
+    /// This only changes backends that use the crate's Feistel network, and
+    /// ranges smaller than 4 or that are a power of two keep the bit split.
+    /// It gives a different permutation for the same key.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the `PerfectRng` is in constant-time mode, since division
+    /// doesn't take the same time for every input on every CPU.
+    ///
+    /// ```
+    /// # use perfect_rand::PerfectRng;
+    /// let range = 2u64.pow(32) + 1;
+    /// assert_eq!(PerfectRng::from_range(range).raw_max(), 2u64.pow(33) - 1);
+    /// let randomizer = PerfectRng::from_range(range).with_tight_domain();
+    /// assert!(randomizer.raw_max() < range + 2u64.pow(16));
+    /// ```
+    #[must_use]
+    pub fn with_tight_domain(mut self) -> Self {
+        self.set_tight(true)
+            .expect("the tight domain isn't constant-time");
+        self
+    }
+
+    /// Like [`PerfectRng::with_tight_domain`], but returns an error instead
+    /// of panicking.
+    pub(crate) fn set_tight(&mut self, tight: bool) -> Result<(), PerfectRngError> {
+        if tight && self.constant_time {
+            return Err(PerfectRngError::NotConstantTime);
+        }
+        self.tight = tight;
+        // the backend decides whether the split is used
+        self.set_backend(self.backend)
+    }
+
+    /// Whether the domain is split with division. See
+    /// [`PerfectRng::with_tight_domain`].
+    #[must_use]
+    pub fn is_tight_domain(&self) -> bool {
+        self.tight
+    }
+
+    #[inline]
+    pub(crate) fn tight_encrypt(&self, m: T) -> T {
+        let [a, b] = self.split;
+
+        let (mut right, mut left) = div_rem(m, a);
+        for j in 1..=self.rounds {
+            let modulus = if j % 2 != 0 { a } else { b };
+            let tmp = add_mod(left, div_rem(self.round(j, right), modulus).1, modulus);
+            left = right;
+            right = tmp;
+        }
+
+        if !self.rounds.is_multiple_of(2) {
+            T::from_u128(a.as_u128() * left.as_u128()) + right
+        } else {
+            T::from_u128(a.as_u128() * right.as_u128()) + left
+        }
+    }
+
+    #[inline]
+    pub(crate) fn tight_decrypt(&self, c: T) -> T {
+        let [a, b] = self.split;
+
+        let (high, low) = div_rem(c, a);
+        let (mut left, mut right) = if !self.rounds.is_multiple_of(2) {
+            (high, low)
+        } else {
+            (low, high)
+        };
+        for j in (1..=self.rounds).rev() {
+            let modulus = if j % 2 != 0 { a } else { b };
+            let f = div_rem(self.round(j, left), modulus).1;
+            // right and f are both less than the modulus
+            let tmp = if right >= f {
+                right - f
+            } else {
+                right.wrapping_sub(f).wrapping_add(modulus)
+            };
+            right = left;
+            left = tmp;
+        }
+
+        T::from_u128(a.as_u128() * right.as_u128()) + left
+    }
+}
+
+/// Choose the sizes of the halves for a domain of `0..=max`, or `None` if
+/// the bit split is at least as good.
+pub(crate) fn split<T: UnsignedInt>(max: T) -> Option<[T; 2]> {
+    // the bits already fit a power of two exactly
+    if max & max.wrapping_add(T::ONE) == T::ZERO {
+        return None;
+    }
+    let range = max.as_u128() + 1;
+    let root = range.isqrt();
+    // one of the halves would only have one value, so nothing gets mixed
+    if root < 2 {
+        return None;
+    }
+
+    // a doesn't have to be the square root, so look for one that divides the
+    // range more evenly
+    let mut best = None;
+    for a in (root.saturating_sub(CANDIDATES).max(2)..=root).rev() {
+        let b = (range - 1) / a + 1;
+        // the halves are padded, so they can multiply to more than T fits
+        let Some(size) = a.checked_mul(b).filter(|&n| n - 1 <= T::MAX.as_u128()) else {
+            continue;
+        };
+        if best.is_none_or(|(_, best_size)| size < best_size) {
+            best = Some(([a, b], size));
+        }
+        if size == range {
+            break;
+        }
+    }
+    best.map(|(halves, _)| halves.map(T::from_u128))
+}
+
+/// Divide, with 64-bit division when `T` fits in it since 128-bit division is
+/// much slower.
+#[inline]
+fn div_rem<T: UnsignedInt>(n: T, d: T) -> (T, T) {
+    if T::BITS <= 64 {
+        let (n, d) = (n.as_u128() as u64, d.as_u128() as u64);
+        (T::from_u128((n / d).into()), T::from_u128((n % d).into()))
+    } else {
+        let (n, d) = (n.as_u128(), d.as_u128());
+        (T::from_u128(n / d), T::from_u128(n % d))
+    }
+}
+
+/// Add `x` and `y` modulo `m`, where both are less than `m`.
+#[inline]
+fn add_mod<T: UnsignedInt>(x: T, y: T, m: T) -> T {
+    let sum = x.wrapping_add(y);
+    // if the sum overflowed, it's more than m anyway
+    if sum < x || sum >= m {
+        sum.wrapping_sub(m)
+    } else {
+        sum
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::split;
+    use crate::{Backend, PerfectRng, PerfectRngError};
+
+    #[test]
+    fn splits() {
+        assert_eq!(split(1023u64), None);
+        assert_eq!(split(2u64), None);
+        assert_eq!(split(u64::MAX), None);
+        // 1000 = 25 * 40
+        assert_eq!(split(999u64), Some([25, 40]));
+        let [a, b] = split(1u64 << 32).unwrap();
+        assert!(a * b > 1 << 32 && a * b - (1 << 32) < a);
+
+        for max in [4u32, 5, 100, 65536, u32::MAX - 1, u32::MAX / 3] {
+            let [a, b] = split(max).unwrap();
+            assert!(a as u64 * b as u64 > max as u64, "{max}");
+        }
+    }
+
+    #[test]
+    fn is_a_permutation() {
+        for (range, rounds) in [
+            (6u64, 4),
+            (5, 3),
+            (1000, 4),
+            (1025, 1),
+            (65537, 4),
+            (12345, 5),
+        ] {
+            let randomizer = PerfectRng::with_key(range, [1, 2], rounds).with_tight_domain();
+            assert!(randomizer.split[0] != 0, "{range}");
+            let mut list = vec![0; range as usize];
+            for i in 0..range {
+                let x = randomizer.shuffle(i);
+                assert_eq!(randomizer.unshuffle(x), i);
+                list[x as usize] += 1;
+            }
+            assert!(list.into_iter().all(|n| n == 1), "{range}");
+        }
+    }
+
+    #[test]
+    fn near_max() {
+        for range in [u64::MAX - 1, u64::MAX / 3, (1 << 63) + 1] {
+            let randomizer = PerfectRng::with_key(range, [1, 2], 4).with_tight_domain();
+            for i in [0, 1, 2, range / 2, range - 2, range - 1] {
+                let x = randomizer.shuffle(i);
+                assert!(x < range);
+                assert_eq!(randomizer.unshuffle(x), i);
+            }
+        }
+        let range = u128::MAX - 12345;
+        let randomizer = PerfectRng::with_key(range, [1, 2], 4).with_tight_domain();
+        for i in [0, range / 2, range - 1] {
+            assert_eq!(randomizer.unshuffle(randomizer.shuffle(i)), i);
+        }
+    }
+
+    #[test]
+    fn backends() {
+        let range = 20_000u32;
+        let randomizer = PerfectRng::with_key(range, [1, 2], 4)
+            .with_tight_domain()
+            .with_backend(Backend::ChaCha)
+            .with_offset(7);
+        assert!(randomizer.raw_max() < 20_200);
+        let mut shuffled = randomizer.iter().collect::<Vec<_>>();
+        shuffled.sort_unstable();
+        assert!(shuffled.into_iter().eq(7..range + 7));
+
+        // backends with their own networks ignore it
+        let randomizer = PerfectRng::with_key(range, [1, 2], 4)
+            .with_tight_domain()
+            .with_backend(Backend::SwapOrNot);
+        assert!(randomizer.is_tight_domain());
+        assert_eq!(randomizer.split, [0, 0]);
+    }
+
+    #[test]
+    fn constant_time() {
+        let mut randomizer = PerfectRng::with_key(1000u64, [1, 2], 4).with_constant_time();
+        assert_eq!(
+            randomizer.set_tight(true).unwrap_err(),
+            PerfectRngError::NotConstantTime
+        );
+        let mut randomizer = PerfectRng::with_key(1000u64, [1, 2], 4).with_tight_domain();
+        assert_eq!(
+            randomizer.set_constant_time(true).unwrap_err(),
+            PerfectRngError::NotConstantTime
+        );
+    }
+}
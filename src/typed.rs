@@ -0,0 +1,343 @@
+use std::{
+    net::{Ipv4Addr, Ipv6Addr},
+    ops::Range,
+};
+
+use crate::{PerfectRng, UnsignedInt};
+
+/// A type whose values can be numbered, so they can be shuffled with
+/// [`PerfectRng::shuffle_typed`] instead of converting to and from integers
+/// by hand.
+///
+/// Every value has an index from 0 to [`Shuffleable::MAX_INDEX`], with no
+/// gaps, so a `PerfectRng` over `0..=MAX_INDEX` shuffles every value of the
+/// type.
+///
+/// With the `derive` feature, this can be derived for enums without fields,
+/// which are numbered in the order the variants are declared, and for
+/// structs where every field is `Shuffleable`, which are numbered like a
+/// mixed-radix number with the first field changing fastest. The index of a
+/// derived implementation is always a `u64`.
+///
+/// ```
+/// # use perfect_rand::{PerfectRng, Shuffleable};
+/// let randomizer = PerfectRng::with_key_inclusive(0..=char::MAX_INDEX, [1, 2], 4);
+/// let c: char = randomizer.shuffle_typed(0);
+/// assert_eq!(randomizer.unshuffle_typed(c), 0);
+/// ```
+pub trait Shuffleable: Sized {
+    /// The integer type of the indices.
+    type Index: UnsignedInt;
+
+    /// The index of the last value.
+    const MAX_INDEX: Self::Index;
+
+    /// The index of this value.
+    fn to_index(self) -> Self::Index;
+
+    /// The value with the given index.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the index is more than [`Shuffleable::MAX_INDEX`].
+    fn from_index(index: Self::Index) -> Self;
+}
+
+macro_rules! impl_shuffleable {
+    ($($ty:ty),*) => {
+        $(
+            /// Every value of the integer, like port numbers for `u16`.
+            impl Shuffleable for $ty {
+                type Index = $ty;
+                const MAX_INDEX: $ty = <$ty>::MAX;
+
+                fn to_index(self) -> $ty {
+                    self
+                }
+
+                fn from_index(index: $ty) -> $ty {
+                    index
+                }
+            }
+        )*
+    };
+}
+
+impl_shuffleable!(u16, u32, u64, u128);
+
+macro_rules! impl_shuffleable_signed {
+    ($($ty:ty => $unsigned:ty),*) => {
+        $(
+            /// Every value of the integer. The indices are biased so they're
+            /// in the same order as the values, with `MIN` at 0.
+            impl Shuffleable for $ty {
+                type Index = $unsigned;
+                const MAX_INDEX: $unsigned = <$unsigned>::MAX;
+
+                fn to_index(self) -> $unsigned {
+                    self as $unsigned ^ (1 << (<$unsigned>::BITS - 1))
+                }
+
+                fn from_index(index: $unsigned) -> $ty {
+                    (index ^ (1 << (<$unsigned>::BITS - 1))) as $ty
+                }
+            }
+        )*
+    };
+}
+
+impl_shuffleable_signed!(i16 => u16, i32 => u32, i64 => u64, i128 => u128);
+
+impl Shuffleable for bool {
+    type Index = u16;
+    const MAX_INDEX: u16 = 1;
+
+    fn to_index(self) -> u16 {
+        self.into()
+    }
+
+    fn from_index(index: u16) -> bool {
+        assert!(index <= 1, "the index must be at most MAX_INDEX");
+        index == 1
+    }
+}
+
+impl Shuffleable for u8 {
+    type Index = u16;
+    const MAX_INDEX: u16 = u8::MAX as u16;
+
+    fn to_index(self) -> u16 {
+        self.into()
+    }
+
+    fn from_index(index: u16) -> u8 {
+        u8::try_from(index).expect("the index must be at most MAX_INDEX")
+    }
+}
+
+impl Shuffleable for Ipv4Addr {
+    type Index = u32;
+    const MAX_INDEX: u32 = u32::MAX;
+
+    fn to_index(self) -> u32 {
+        self.into()
+    }
+
+    fn from_index(index: u32) -> Ipv4Addr {
+        index.into()
+    }
+}
+
+impl Shuffleable for Ipv6Addr {
+    type Index = u128;
+    const MAX_INDEX: u128 = u128::MAX;
+
+    fn to_index(self) -> u128 {
+        self.into()
+    }
+
+    fn from_index(index: u128) -> Ipv6Addr {
+        index.into()
+    }
+}
+
+/// The number of surrogate code points, which aren't `char`s.
+const SURROGATES: u32 = 0xe000 - 0xd800;
+
+/// Every `char`, with the surrogate code points skipped so there are no gaps.
+impl Shuffleable for char {
+    type Index = u32;
+    const MAX_INDEX: u32 = char::MAX as u32 - SURROGATES;
+
+    fn to_index(self) -> u32 {
+        let n = u32::from(self);
+        if n >= 0xe000 {
+            n - SURROGATES
+        } else {
+            n
+        }
+    }
+
+    fn from_index(index: u32) -> char {
+        let n = if index >= 0xd800 {
+            index + SURROGATES
+        } else {
+            index
+        };
+        char::from_u32(n).expect("the index must be at most MAX_INDEX")
+    }
+}
+
+impl<T: UnsignedInt> PerfectRng<T> {
+    /// Create a new `PerfectRng` over the values in a range of signed
+    /// integers, with a random key and default rounds. Use it with
+    /// [`PerfectRng::shuffle_signed`] and [`PerfectRng::unshuffle_signed`].
+    ///
+    /// Internally, the values are biased so `MIN` is 0, which keeps them in
+    /// the same order. This works for any [`Shuffleable`] type where that's
+    /// true, like `char`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the range is empty.
+    ///
+    /// ```
+    /// # use perfect_rand::PerfectRng;
+    /// let randomizer = PerfectRng::from_signed_range(-1_000_000..1_000_000i64);
+    /// let shuffled: i64 = randomizer.shuffle_signed(-5);
+    /// assert!((-1_000_000..1_000_000).contains(&shuffled));
+    /// assert_eq!(randomizer.unshuffle_signed(shuffled), -5);
+    /// ```
+    #[must_use]
+    pub fn from_signed_range<S: Shuffleable<Index = T>>(range: Range<S>) -> Self {
+        Self::with_key_signed(range, [T::random(), T::random()], 4)
+    }
+
+    /// Like [`PerfectRng::from_signed_range`], but keyed like
+    /// [`PerfectRng::with_key`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if the range is empty.
+    #[must_use]
+    pub fn with_key_signed<S: Shuffleable<Index = T>>(
+        range: Range<S>,
+        key: [T; 2],
+        rounds: usize,
+    ) -> Self {
+        let (start, end) = (range.start.to_index(), range.end.to_index());
+        assert!(start < end, "the range must not be empty");
+        Self::with_key_inclusive(start..=end - T::ONE, key, rounds)
+    }
+
+    /// Shuffle a value in the range given to
+    /// [`PerfectRng::from_signed_range`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `m` isn't in the range.
+    #[must_use]
+    pub fn shuffle_signed<S: Shuffleable<Index = T>>(&self, m: S) -> S {
+        S::from_index(self.shuffle(m.to_index()))
+    }
+
+    /// Reverse [`PerfectRng::shuffle_signed`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `c` isn't in the range.
+    #[must_use]
+    pub fn unshuffle_signed<S: Shuffleable<Index = T>>(&self, c: S) -> S {
+        S::from_index(self.unshuffle(c.to_index()))
+    }
+
+    /// Like [`PerfectRng::shuffle`], but returns the value of a
+    /// [`Shuffleable`] type with the shuffled index.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `m` isn't in the range, or the shuffled index is more than
+    /// [`Shuffleable::MAX_INDEX`].
+    ///
+    /// ```
+    /// # use std::net::Ipv4Addr;
+    /// # use perfect_rand::PerfectRng;
+    /// let randomizer = PerfectRng::from_range_inclusive(0..=u32::MAX);
+    /// for i in 0..10 {
+    ///     let ip = randomizer.shuffle_typed::<Ipv4Addr>(i);
+    ///     println!("{ip}");
+    /// }
+    /// ```
+    #[must_use]
+    pub fn shuffle_typed<S: Shuffleable<Index = T>>(&self, m: T) -> S {
+        S::from_index(self.shuffle(m))
+    }
+
+    /// Reverse [`PerfectRng::shuffle_typed`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if the index of `value` isn't in the range.
+    #[must_use]
+    pub fn unshuffle_typed<S: Shuffleable<Index = T>>(&self, value: S) -> T {
+        self.unshuffle(value.to_index())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::{Ipv4Addr, Ipv6Addr};
+
+    use super::Shuffleable;
+    use crate::{PerfectRng128, PerfectRng16, PerfectRng32};
+
+    #[test]
+    fn chars() {
+        for c in ['\0', 'a', '\u{d7ff}', '\u{e000}', '\u{ffff}', char::MAX] {
+            assert_eq!(char::from_index(c.to_index()), c);
+        }
+        assert_eq!('\u{e000}'.to_index(), 0xd800);
+        assert_eq!(char::MAX.to_index(), char::MAX_INDEX);
+
+        let randomizer = PerfectRng32::with_key_inclusive(0..=char::MAX_INDEX, [1, 2], 4);
+        for i in (0..=char::MAX_INDEX).step_by(997) {
+            let c: char = randomizer.shuffle_typed(i);
+            assert_eq!(randomizer.unshuffle_typed(c), i);
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "at most MAX_INDEX")]
+    fn char_out_of_range() {
+        let _ = char::from_index(char::MAX_INDEX + 1);
+    }
+
+    #[test]
+    fn signed() {
+        for n in [i64::MIN, -1, 0, 1, i64::MAX] {
+            assert_eq!(i64::from_index(n.to_index()), n);
+        }
+        assert_eq!(i64::MIN.to_index(), 0);
+        assert_eq!((-1i64).to_index() + 1, 0i64.to_index());
+        assert_eq!(i16::MAX.to_index(), u16::MAX);
+
+        let randomizer = PerfectRng32::with_key_signed(-500..500i32, [1, 2], 4);
+        let mut seen = (-500..500)
+            .map(|n| randomizer.shuffle_signed(n))
+            .collect::<Vec<_>>();
+        for n in -500..500 {
+            assert_eq!(randomizer.unshuffle_signed(randomizer.shuffle_signed(n)), n);
+        }
+        seen.sort_unstable();
+        assert!(seen.into_iter().eq(-500..500));
+
+        let randomizer = PerfectRng128::with_key_signed(i128::MIN..i128::MAX, [1, 2], 4);
+        let shuffled = randomizer.shuffle_signed(i128::MIN);
+        assert_ne!(shuffled, i128::MAX);
+        assert_eq!(randomizer.unshuffle_signed(shuffled), i128::MIN);
+    }
+
+    #[test]
+    #[should_panic(expected = "must not be empty")]
+    #[allow(clippy::reversed_empty_ranges)]
+    fn empty_signed_range() {
+        let _ = PerfectRng32::with_key_signed(5..-5i32, [1, 2], 4);
+    }
+
+    #[test]
+    fn addresses_and_ports() {
+        let randomizer = PerfectRng32::with_key_inclusive(0..=u32::MAX, [1, 2], 4);
+        let ip: Ipv4Addr = randomizer.shuffle_typed(5);
+        assert_eq!(u32::from(ip), randomizer.shuffle(5));
+        assert_eq!(randomizer.unshuffle_typed(ip), 5);
+
+        let randomizer = PerfectRng128::with_key_inclusive(0..=u128::MAX, [1, 2], 4);
+        let ip: Ipv6Addr = randomizer.shuffle_typed(5);
+        assert_eq!(randomizer.unshuffle_typed(ip), 5);
+
+        let randomizer = PerfectRng16::with_key_inclusive(1..=u16::MAX, [1, 2], 4);
+        let port: u16 = randomizer.shuffle_typed(80);
+        assert_ne!(port, 0);
+        assert_eq!(randomizer.unshuffle_typed(port), 80);
+    }
+}
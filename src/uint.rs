@@ -0,0 +1,317 @@
+use std::{
+    fmt::Debug,
+    hash::Hash,
+    ops::{Add, BitAnd, BitOr, BitXor, Shl, Shr, Sub},
+};
+
+use rand::{Rng, RngCore};
+
+use crate::siphash::siphash128_u64;
+
+mod sealed {
+    pub trait Sealed {}
+}
+
+/// An unsigned integer type that a [`PerfectRng`](crate::PerfectRng) can
+/// shuffle.
+///
+/// This is implemented for [`u16`], [`u32`], [`u64`], and [`u128`], and
+/// can't be implemented outside of this crate. It lets you write code that's
+/// generic over the width of the domain:
+///
+/// ```
+/// # use perfect_rand::{PerfectRng, UnsignedInt};
+/// fn first<T: UnsignedInt>(rng: &PerfectRng<T>) -> T {
+///     rng.shuffle(T::ZERO)
+/// }
+///
+/// assert!(first(&PerfectRng::<u16>::from_range(100)) < 100);
+/// assert!(first(&PerfectRng::<u128>::from_range(100)) < 100);
+/// ```
+pub trait UnsignedInt:
+    sealed::Sealed
+    + Copy
+    + Default
+    + Debug
+    + Eq
+    + Ord
+    + Hash
+    + Send
+    + Sync
+    + Add<Output = Self>
+    + Sub<Output = Self>
+    + BitAnd<Output = Self>
+    + BitOr<Output = Self>
+    + BitXor<Output = Self>
+    + Shl<u32, Output = Self>
+    + Shr<u32, Output = Self>
+    + 'static
+{
+    /// The size of this integer type in bits.
+    const BITS: u32;
+    const ZERO: Self;
+    const ONE: Self;
+    const MAX: Self;
+
+    /// The number of bits needed to represent this number.
+    fn count_bits(self) -> u32;
+
+    fn wrapping_add(self, rhs: Self) -> Self;
+    fn wrapping_sub(self, rhs: Self) -> Self;
+
+    /// Convert from a `usize`, truncating if it doesn't fit.
+    fn from_usize(n: usize) -> Self;
+    /// Convert to a `u128`, which every supported type fits in.
+    fn as_u128(self) -> u128;
+    /// Convert from a `u128`, truncating if it doesn't fit.
+    fn from_u128(n: u128) -> Self;
+
+    #[doc(hidden)]
+    fn random() -> Self;
+
+    #[doc(hidden)]
+    fn random_from<R: RngCore + ?Sized>(rng: &mut R) -> Self;
+
+    #[cfg(feature = "zeroize")]
+    #[doc(hidden)]
+    fn zeroize(&mut self);
+
+    /// Derive the key for round `j` from the cipher's key.
+    #[doc(hidden)]
+    fn round_key(key: [Self; 2], j: usize) -> [Self; 2];
+
+    /// The Feistel round function, implemented with `siprounds` SipHash
+    /// rounds that are the same width as the integer. `j` is the round number
+    /// xored with the tweak.
+    #[doc(hidden)]
+    fn round(key: [Self; 2], j: Self, right: Self, siprounds: usize) -> Self;
+}
+
+macro_rules! impl_unsigned_int {
+    ($ty:ty, $round_key:ident, $round:ident) => {
+        impl sealed::Sealed for $ty {}
+
+        impl UnsignedInt for $ty {
+            const BITS: u32 = <$ty>::BITS;
+            const ZERO: Self = 0;
+            const ONE: Self = 1;
+            const MAX: Self = <$ty>::MAX;
+
+            #[inline]
+            fn count_bits(self) -> u32 {
+                <$ty>::BITS - self.leading_zeros()
+            }
+
+            #[inline]
+            fn wrapping_add(self, rhs: Self) -> Self {
+                <$ty>::wrapping_add(self, rhs)
+            }
+
+            #[inline]
+            fn wrapping_sub(self, rhs: Self) -> Self {
+                <$ty>::wrapping_sub(self, rhs)
+            }
+
+            #[inline]
+            fn from_usize(n: usize) -> Self {
+                n as $ty
+            }
+
+            #[inline]
+            fn as_u128(self) -> u128 {
+                self as u128
+            }
+
+            #[inline]
+            fn from_u128(n: u128) -> Self {
+                n as $ty
+            }
+
+            fn random() -> Self {
+                rand::random()
+            }
+
+            fn random_from<R: RngCore + ?Sized>(rng: &mut R) -> Self {
+                rng.gen()
+            }
+
+            #[cfg(feature = "zeroize")]
+            fn zeroize(&mut self) {
+                zeroize::Zeroize::zeroize(self);
+            }
+
+            #[inline]
+            fn round_key(key: [Self; 2], j: usize) -> [Self; 2] {
+                $round_key(key, j)
+            }
+
+            #[inline]
+            fn round(key: [Self; 2], j: Self, right: Self, siprounds: usize) -> Self {
+                $round(key, j, right, siprounds)
+            }
+        }
+    };
+}
+
+impl_unsigned_int!(u16, round_key16, round16);
+impl_unsigned_int!(u32, round_key32, round32);
+impl_unsigned_int!(u64, round_key64, round64);
+impl_unsigned_int!(u128, round_key128, round128);
+
+macro_rules! round_key_fn {
+    ($name:ident, $ty:ty) => {
+        /// Derive independent key material for each round by hashing the
+        /// round number with SipHash, keyed with the cipher's key.
+        #[inline]
+        pub(crate) const fn $name(key: [$ty; 2], j: usize) -> [$ty; 2] {
+            let hash = siphash128_u64([key[0] as u64, key[1] as u64], j as u64);
+            [hash as $ty, (hash >> 64) as $ty]
+        }
+    };
+}
+
+round_key_fn!(round_key16, u16);
+round_key_fn!(round_key32, u32);
+round_key_fn!(round_key64, u64);
+
+/// The 128-bit key halves are too big to both fit in one SipHash key, so each
+/// half of the round key comes from its own hash.
+#[inline]
+pub(crate) const fn round_key128(key: [u128; 2], j: usize) -> [u128; 2] {
+    [
+        siphash128_u64([key[0] as u64, (key[0] >> 64) as u64], j as u64),
+        siphash128_u64([key[1] as u64, (key[1] >> 64) as u64], j as u64),
+    ]
+}
+
+#[inline]
+pub(crate) const fn sipround(
+    mut v0: u64,
+    mut v1: u64,
+    mut v2: u64,
+    mut v3: u64,
+) -> (u64, u64, u64, u64) {
+    v0 = v0.wrapping_add(v1);
+    v2 = v2.wrapping_add(v3);
+    v1 = v1.rotate_left(13) ^ v0;
+    v3 = v3.rotate_left(16) ^ v2;
+    v0 = v0.rotate_left(32);
+
+    v2 = v2.wrapping_add(v1);
+    v0 = v0.wrapping_add(v3);
+    v1 = v1.rotate_left(17) ^ v2;
+    v3 = v3.rotate_left(21) ^ v0;
+    v2 = v2.rotate_left(32);
+
+    (v0, v1, v2, v3)
+}
+
+#[inline]
+const fn sipround32(mut v0: u32, mut v1: u32, mut v2: u32, mut v3: u32) -> (u32, u32, u32, u32) {
+    v0 = v0.wrapping_add(v1);
+    v2 = v2.wrapping_add(v3);
+    v1 = v1.rotate_left(5) ^ v0;
+    v3 = v3.rotate_left(8) ^ v2;
+    v0 = v0.rotate_left(16);
+
+    v2 = v2.wrapping_add(v1);
+    v0 = v0.wrapping_add(v3);
+    v1 = v1.rotate_left(13) ^ v2;
+    v3 = v3.rotate_left(17) ^ v0;
+    v2 = v2.rotate_left(16);
+
+    (v0, v1, v2, v3)
+}
+
+#[inline]
+const fn sipround128(
+    mut v0: u128,
+    mut v1: u128,
+    mut v2: u128,
+    mut v3: u128,
+) -> (u128, u128, u128, u128) {
+    v0 = v0.wrapping_add(v1);
+    v2 = v2.wrapping_add(v3);
+    v1 = v1.rotate_left(26) ^ v0;
+    v3 = v3.rotate_left(32) ^ v2;
+    v0 = v0.rotate_left(64);
+
+    v2 = v2.wrapping_add(v1);
+    v0 = v0.wrapping_add(v3);
+    v1 = v1.rotate_left(34) ^ v2;
+    v3 = v3.rotate_left(42) ^ v0;
+    v2 = v2.rotate_left(64);
+
+    (v0, v1, v2, v3)
+}
+
+#[inline]
+pub(crate) const fn round16(key: [u16; 2], j: u16, right: u16, siprounds: usize) -> u16 {
+    // there's no 16-bit sipround, so do the 32-bit one and truncate
+    round32(
+        [key[0] as u32, key[1] as u32],
+        j as u32,
+        right as u32,
+        siprounds,
+    ) as u16
+}
+
+#[inline]
+pub(crate) const fn round64(key: [u64; 2], j: u64, right: u64, siprounds: usize) -> u64 {
+    let v0 = key[0];
+    let v1 = j;
+    let v2 = right;
+    // all zeroes will lead to an all-zero output,
+    // this adds some randomness for that case.
+    // the second half of the key is mixed in here too.
+    let v3: u64 = 0xf3016d19bc9ad940 ^ key[1];
+
+    let mut v = (v0, v1, v2, v3);
+    let mut i = 0;
+    while i < siprounds {
+        v = sipround(v.0, v.1, v.2, v.3);
+        i += 1;
+    }
+
+    v.0
+}
+
+#[inline]
+pub(crate) const fn round32(key: [u32; 2], j: u32, right: u32, siprounds: usize) -> u32 {
+    let v0 = key[0];
+    let v1 = j;
+    let v2 = right;
+    // all zeroes will lead to an all-zero output,
+    // this adds some randomness for that case.
+    // the second half of the key is mixed in here too.
+    let v3: u32 = 0xbc9ad940 ^ key[1];
+
+    let mut v = (v0, v1, v2, v3);
+    let mut i = 0;
+    while i < siprounds {
+        v = sipround32(v.0, v.1, v.2, v.3);
+        i += 1;
+    }
+
+    v.0
+}
+
+#[inline]
+pub(crate) const fn round128(key: [u128; 2], j: u128, right: u128, siprounds: usize) -> u128 {
+    let v0 = key[0];
+    let v1 = j;
+    let v2 = right;
+    // all zeroes will lead to an all-zero output,
+    // this adds some randomness for that case.
+    // the second half of the key is mixed in here too.
+    let v3: u128 = 0x5be0cd19137e2179_f3016d19bc9ad940 ^ key[1];
+
+    let mut v = (v0, v1, v2, v3);
+    let mut i = 0;
+    while i < siprounds {
+        v = sipround128(v.0, v.1, v.2, v.3);
+        i += 1;
+    }
+
+    v.0
+}
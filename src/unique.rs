@@ -0,0 +1,88 @@
+use std::iter::FusedIterator;
+
+use rand::RngCore;
+
+use crate::{PerfectRng, ShuffleCursor, UnsignedInt};
+
+/// A random number generator that never returns the same number twice, until
+/// every number in its range has been returned.
+///
+/// This owns its [`PerfectRng`] and keeps track of how many numbers it has
+/// generated, so it can be stored and passed around like any other generator.
+///
+/// ```
+/// # use perfect_rand::UniqueRng;
+/// let mut rng = UniqueRng::from_rng(10u64, &mut rand::thread_rng());
+/// let mut numbers = rng.by_ref().collect::<Vec<_>>();
+/// numbers.sort();
+/// assert_eq!(numbers, (0..10).collect::<Vec<_>>());
+/// assert_eq!(rng.next(), None);
+/// ```
+#[derive(Debug)]
+pub struct UniqueRng<T: UnsignedInt = u64> {
+    cursor: ShuffleCursor<T>,
+}
+
+impl<T: UnsignedInt> UniqueRng<T> {
+    /// Create a generator over `0..range` from a [`PerfectRng`].
+    #[must_use]
+    pub fn new(rng: PerfectRng<T>) -> Self {
+        UniqueRng {
+            cursor: ShuffleCursor::new(rng),
+        }
+    }
+
+    /// Create a generator over `0..range`, keyed from the given random
+    /// number generator. See [`PerfectRng::from_rng`].
+    #[must_use]
+    pub fn from_rng<R: RngCore + ?Sized>(range: T, rng: &mut R) -> Self {
+        Self::new(PerfectRng::from_rng(range, rng))
+    }
+
+    /// The [`PerfectRng`] that the numbers come from.
+    #[must_use]
+    pub fn rng(&self) -> &PerfectRng<T> {
+        self.cursor.rng()
+    }
+}
+
+impl<T: UnsignedInt> Iterator for UniqueRng<T> {
+    type Item = T;
+
+    /// Generate a number that hasn't been generated before, or `None` if
+    /// every number in the range has been generated.
+    #[inline]
+    fn next(&mut self) -> Option<T> {
+        self.cursor.next()
+    }
+}
+
+impl<T: UnsignedInt> FusedIterator for UniqueRng<T> {}
+
+#[cfg(test)]
+mod tests {
+    use rand::{rngs::StdRng, SeedableRng};
+
+    use super::UniqueRng;
+
+    #[test]
+    fn never_repeats() {
+        let mut rng = UniqueRng::from_rng(1000u32, &mut StdRng::seed_from_u64(1));
+        let mut seen = vec![false; 1000];
+        for n in rng.by_ref() {
+            assert!(!seen[n as usize]);
+            seen[n as usize] = true;
+        }
+        assert!(seen.into_iter().all(|seen| seen));
+        assert_eq!(rng.next(), None);
+    }
+
+    #[test]
+    fn seeded_is_reproducible() {
+        let a = UniqueRng::from_rng(1000u64, &mut StdRng::seed_from_u64(1));
+        let b = UniqueRng::from_rng(1000u64, &mut StdRng::seed_from_u64(1));
+        let c = UniqueRng::from_rng(1000u64, &mut StdRng::seed_from_u64(2));
+        assert!(a.rng().iter().eq(b.rng().iter()));
+        assert!(!a.eq(c));
+    }
+}
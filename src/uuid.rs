@@ -0,0 +1,117 @@
+use crate::PerfectRng;
+
+/// The number of random bits in a version 4 UUID.
+const RANDOM_BITS: u32 = 122;
+
+/// A permutation of the random bits of version 4 UUIDs, for turning
+/// sequential counters into UUIDs that look random but can be mapped back.
+///
+/// UUIDs are `u128`s in the same byte order as `Uuid::from_u128` in the
+/// `uuid` crate. The version and variant bits are always set like in a
+/// random UUID, and the other 122 bits are the shuffled counter.
+///
+/// ```
+/// # use perfect_rand::PerfectRng;
+/// let uuids = PerfectRng::uuids();
+/// let uuid = uuids.uuid(1);
+/// assert_eq!(uuid >> 76 & 0xf, 4);
+/// assert_eq!(uuid >> 62 & 0b11, 0b10);
+/// assert_eq!(uuids.counter(uuid), Some(1));
+/// ```
+#[derive(Debug)]
+pub struct UuidRng {
+    rng: PerfectRng<u128>,
+}
+
+impl PerfectRng<u128> {
+    /// Create a permutation of the version 4 UUIDs with a random key and
+    /// default rounds. See [`UuidRng`].
+    #[must_use]
+    pub fn uuids() -> UuidRng {
+        UuidRng::with_key(rand::random(), 4)
+    }
+}
+
+impl UuidRng {
+    /// Create a permutation of the version 4 UUIDs, keyed like
+    /// [`PerfectRng::with_key`].
+    #[must_use]
+    pub fn with_key(key: [u128; 2], rounds: usize) -> Self {
+        UuidRng {
+            rng: PerfectRng::with_key(1 << RANDOM_BITS, key, rounds),
+        }
+    }
+
+    /// The UUID for a counter.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the counter doesn't fit in 122 bits.
+    #[must_use]
+    pub fn uuid(&self, counter: u128) -> u128 {
+        assert!(
+            counter >> RANDOM_BITS == 0,
+            "the counter must fit in 122 bits"
+        );
+        let random = self.rng.shuffle(counter);
+        // the top 48 bits, the version, the next 12 bits, the variant, then
+        // the last 62 bits
+        (random >> 74 << 80)
+            | (0x4 << 76)
+            | ((random >> 62 & 0xfff) << 64)
+            | (0b10 << 62)
+            | (random & ((1 << 62) - 1))
+    }
+
+    /// Reverse [`UuidRng::uuid`], or return `None` if the UUID isn't a
+    /// version 4 UUID.
+    #[must_use]
+    pub fn counter(&self, uuid: u128) -> Option<u128> {
+        if uuid >> 76 & 0xf != 0x4 || uuid >> 62 & 0b11 != 0b10 {
+            return None;
+        }
+        let random = (uuid >> 80 << 74) | ((uuid >> 64 & 0xfff) << 62) | (uuid & ((1 << 62) - 1));
+        Some(self.rng.unshuffle(random))
+    }
+
+    /// The [`PerfectRng`] over `0..2^122` that the random bits are shuffled
+    /// with.
+    #[must_use]
+    pub fn rng(&self) -> &PerfectRng<u128> {
+        &self.rng
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::UuidRng;
+
+    #[test]
+    fn round_trip() {
+        let uuids = UuidRng::with_key([1, 2], 4);
+        for counter in (0..1000).chain([(1 << 122) - 1, 1 << 100]) {
+            let uuid = uuids.uuid(counter);
+            // xxxxxxxx-xxxx-4xxx-[89ab]xxx-xxxxxxxxxxxx
+            let formatted = format!("{uuid:032x}");
+            assert_eq!(&formatted[12..13], "4", "{formatted}");
+            assert!("89ab".contains(&formatted[16..17]), "{formatted}");
+            assert_eq!(uuids.counter(uuid), Some(counter));
+        }
+        assert_ne!(uuids.uuid(0), uuids.uuid(1));
+    }
+
+    #[test]
+    fn not_v4() {
+        let uuids = UuidRng::with_key([1, 2], 4);
+        let uuid = uuids.uuid(5);
+        assert_eq!(uuids.counter(uuid ^ (1 << 77)), None);
+        assert_eq!(uuids.counter(uuid ^ (1 << 63)), None);
+        assert_eq!(uuids.counter(0), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "fit in 122 bits")]
+    fn counter_too_big() {
+        let _ = UuidRng::with_key([1, 2], 4).uuid(1 << 122);
+    }
+}
@@ -0,0 +1,150 @@
+use crate::{PerfectRng, UnsignedInt};
+
+/// The result of [`PerfectRng::verify_bijection`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BijectionReport<T> {
+    /// The number of inputs that were shuffled and unshuffled.
+    pub checked: u64,
+    /// Whether every input in the range was checked, so the permutation is
+    /// known to be a bijection if there was no failure.
+    pub exhaustive: bool,
+    /// The first input that wasn't shuffled correctly, if there was one.
+    pub failure: Option<BijectionFailure<T>>,
+}
+
+impl<T> BijectionReport<T> {
+    /// Whether every checked input was shuffled correctly.
+    #[must_use]
+    pub fn is_ok(&self) -> bool {
+        self.failure.is_none()
+    }
+}
+
+/// How an input failed [`PerfectRng::verify_bijection`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum BijectionFailure<T> {
+    /// The input was shuffled to something outside of the range.
+    OutOfRange {
+        /// The input that was shuffled.
+        input: T,
+        /// What it was shuffled to.
+        output: T,
+    },
+    /// Unshuffling the output didn't give back the input, so another input
+    /// is shuffled to the same output.
+    NotInverse {
+        /// The input that was shuffled.
+        input: T,
+        /// What it was shuffled to.
+        output: T,
+        /// What the output was unshuffled to.
+        unshuffled: T,
+    },
+}
+
+impl<T: UnsignedInt> PerfectRng<T> {
+    /// Check that the permutation is a bijection, by shuffling inputs and
+    /// making sure the outputs are in the range and unshuffle back to the
+    /// inputs. That's enough to show that no two inputs share an output.
+    ///
+    /// If the range is at most `limit`, every input is checked. Otherwise,
+    /// `limit` evenly spaced inputs are checked. This is meant for checking
+    /// custom configurations, like a [`Backend::Custom`](crate::Backend::Custom)
+    /// round function, before relying on them.
+    ///
+    /// ```
+    /// # use perfect_rand::PerfectRng;
+    /// let report = PerfectRng::from_range(1000u64).verify_bijection(1 << 20);
+    /// assert!(report.is_ok());
+    /// assert!(report.exhaustive);
+    ///
+    /// let report = PerfectRng::from_range(u64::MAX).verify_bijection(1000);
+    /// assert!(report.is_ok());
+    /// assert_eq!(report.checked, 1000);
+    /// ```
+    #[must_use]
+    pub fn verify_bijection(&self, limit: u64) -> BijectionReport<T> {
+        // the range itself doesn't fit in a u128 for the full domain
+        let max = self.max.as_u128();
+        let exhaustive = max < u128::from(limit);
+        let (checked, step) = if exhaustive {
+            (max as u64 + 1, 1)
+        } else {
+            (limit, max / u128::from(limit.max(1)))
+        };
+
+        for k in 0..checked {
+            let input = T::from_u128(u128::from(k) * step) + self.offset;
+            let output = self.shuffle(input);
+            let failure = if output < self.offset || output - self.offset > self.max {
+                Some(BijectionFailure::OutOfRange { input, output })
+            } else {
+                let unshuffled = self.unshuffle(output);
+                (unshuffled != input).then_some(BijectionFailure::NotInverse {
+                    input,
+                    output,
+                    unshuffled,
+                })
+            };
+            if failure.is_some() {
+                return BijectionReport {
+                    checked: k + 1,
+                    exhaustive,
+                    failure,
+                };
+            }
+        }
+        BijectionReport {
+            checked,
+            exhaustive,
+            failure: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Backend, PerfectRng, RoundFunction};
+
+    #[test]
+    fn exhaustive() {
+        let report =
+            PerfectRng::with_key_inclusive(100..=1099u32, [1, 2], 4).verify_bijection(1000);
+        assert!(report.is_ok());
+        assert!(report.exhaustive);
+        assert_eq!(report.checked, 1000);
+
+        let report = PerfectRng::with_key_inclusive(100..=1099u32, [1, 2], 4).verify_bijection(999);
+        assert!(report.is_ok());
+        assert!(!report.exhaustive);
+        assert_eq!(report.checked, 999);
+
+        assert_eq!(PerfectRng::from_range(10u64).verify_bijection(0).checked, 0);
+    }
+
+    #[test]
+    fn custom_backend() {
+        // the Feistel network is a bijection whatever the round function is
+        struct Constant;
+        impl RoundFunction for Constant {
+            fn name(&self) -> &str {
+                "constant"
+            }
+
+            fn round(&self, _key: u128, _j: u128, _right: u128) -> u128 {
+                5
+            }
+        }
+        let randomizer = PerfectRng::from_range(1000u64).with_backend(Backend::Custom(&Constant));
+        assert!(randomizer.verify_bijection(1000).is_ok());
+    }
+
+    #[test]
+    fn full_domain() {
+        let randomizer = PerfectRng::with_key_inclusive(0..=u128::MAX, [1, 2], 4);
+        let report = randomizer.verify_bijection(100);
+        assert!(report.is_ok());
+        assert!(!report.exhaustive);
+    }
+}